@@ -1,86 +1,257 @@
 //! This module contains types and traits associated with the calculator as a whole.
 
+use core::fmt;
 use core::iter::Iterator;
+use core::str::FromStr;
 
-use embedded_graphics::{pixelcolor::Rgb888, prelude::*};
+use embedded_graphics::{draw_target::DrawTarget, pixelcolor::Rgb888, primitives::Rectangle};
 
-use embedded_graphics_simulator::{OutputSettings, SimulatorDisplay, SimulatorEvent, Window};
+mod framebuffer;
+pub use framebuffer::*;
 
-use embedded_graphics_simulator::sdl2;
+mod headless;
+pub use headless::*;
 
-// TODO: add cfg flags to support different Display drivers, event fetcher, ...
+mod record;
+pub use record::*;
 
-/// This type represent the display of the calculator.
-/// Everything is drawn on it.
-pub type DeviceDislay = SimulatorDisplay<Rgb888>;
+mod keymap;
+pub use keymap::*;
+
+mod timer;
+pub use timer::*;
+
+#[cfg(feature = "simulator")]
+mod simulator;
+#[cfg(feature = "simulator")]
+pub use simulator::*;
+
+/// Width of the calculator display, in pixels.
+pub const SCREEN_WIDTH: u32 = 320;
+/// Height of the calculator display, in pixels.
+pub const SCREEN_HEIGHT: u32 = 240;
+
+/// Former name of [`Framebuffer`], the image of the display of the calculator.
+#[deprecated(note = "use `Framebuffer` instead")]
+pub type DeviceDislay<'a> = Framebuffer<'a>;
 /// This type represents the colors available on the calculator display.
 /// It should be the only type used for colors.
 pub type Color = Rgb888;
 
+/// A backend provides the display and the events of the calculator.
+///
+/// The SDL simulator is available as [`SimulatorBackend`] behind the `simulator` feature,
+/// and [`HeadlessBackend`] runs applications without any display.
+/// Other implementations may drive the real hardware, or run without any display at all.
+pub trait Backend {
+    /// Target on which applications draw their UI,
+    /// e.g. a [`Framebuffer`] or the driver of a physical display.
+    type Display: DrawTarget<Color = Color>;
+
+    /// Return the target on which applications draw their UI.
+    fn get_draw_target(&mut self) -> &mut Self::Display;
+    /// Send the display buffer to the physical display.
+    fn flush(&mut self);
+    /// Send only the given area of the display buffer to the physical display.
+    ///
+    /// By default, the whole display buffer is sent.
+    fn flush_area(&mut self, area: Rectangle) {
+        let _ = area;
+        self.flush();
+    }
+    /// Fetch the next pending event, if any.
+    fn poll_event(&mut self) -> Option<Event>;
+    /// Monotonic time in milliseconds, from an arbitrary starting point.
+    fn now(&self) -> u64;
+    /// Called with the events generated by the [`Calculator`] rather than polled from the backend,
+    /// e.g. [`Event::KeyRepeat`], so that they can be recorded.
+    ///
+    /// By default, nothing is done.
+    fn log_event(&mut self, e: Event) {
+        let _ = e;
+    }
+    /// Whether the events are replayed from a log which already holds the generated ones,
+    /// in which case the [`Calculator`] doesn't generate them again.
+    ///
+    /// By default, events aren't replayed.
+    fn replays_events(&self) -> bool {
+        false
+    }
+}
+
 /// This struct represents the calculator, as seen by an application.
 /// The OS may pass a `Calculator` object to the applications.
 ///
-/// # Panics
-/// `Calculator::render()` must be called before `Calculator::events()`,
-/// otherwise `Calculator::events()` will panic.
-pub struct Calculator {
-    display: DeviceDislay,
-    window: Window,
+/// `B` is the [`Backend`] the calculator runs on.
+pub struct Calculator<B: Backend> {
+    backend: B,
+    key_repeat: Option<KeyRepeat>,
+    held_key: Option<(KeyCode, u64)>, // key being held, and when it should repeat next
 }
 
-impl Calculator {
-    /// Request the calculator.
+impl<B: Backend> Calculator<B> {
+    /// Request the calculator, running on the given backend.
     /// May fail if the calculator has already been requested.
-    pub fn new() -> Option<Self> {
+    pub fn new(backend: B) -> Option<Self> {
         Some(Self {
-            display: DeviceDislay::new(Size::new(320, 240)),
-            window: Window::new("Numworks", &OutputSettings::default()),
+            backend,
+            key_repeat: Some(KeyRepeat::default()),
+            held_key: None,
         })
     }
 
     /// Render the buffer to the physical display.
     pub fn render(&mut self) {
-        self.window.update(&self.display);
+        self.backend.flush();
+    }
+    /// Render only the given area of the buffer to the physical display.
+    ///
+    /// Used with [`Widget::get_dirty_area()`](crate::gui::Widget::get_dirty_area()) to send only what changed.
+    pub fn render_area(&mut self, area: Rectangle) {
+        self.backend.flush_area(area);
     }
 
-    /// Return a `DeviceDisplay` representing the display buffer,
+    /// Return the target representing the display,
     /// on which applications should draw their UI.
-    pub fn get_draw_target(&mut self) -> &mut DeviceDislay {
-        &mut self.display
+    pub fn get_draw_target(&mut self) -> &mut B::Display {
+        self.backend.get_draw_target()
     }
 
     /// Iterator on events recieved by the OS at a certain point.
+    ///
+    /// Holding a [repeatable](KeyCode::is_repeatable()) key generates [`Event::KeyRepeat`],
+    /// according to the [`KeyRepeat`] settings.
+    /// The repetitions are given to [`Backend::log_event()`],
+    /// and aren't generated if the backend [replays](Backend::replays_events()) its events.
     pub fn events(&mut self) -> impl Iterator<Item = Event> + '_ {
-        self.window.events().filter_map(|sdl_event| match sdl_event {
-            SimulatorEvent::MouseButtonDown { .. }
-            | SimulatorEvent::MouseButtonUp { .. }
-            | SimulatorEvent::MouseMove { .. }
-            | SimulatorEvent::MouseWheel { .. } => None,
-            SimulatorEvent::Quit => Some(Event::HardQuit),
-            SimulatorEvent::KeyDown {
-                keycode,
-                keymod: _,
-                repeat: _,
-            } => KeyCode::try_from_sdl2(keycode).map(Event::KeyDown),
-            SimulatorEvent::KeyUp {
-                keycode,
-                keymod: _,
-                repeat: _,
-            } => KeyCode::try_from_sdl2(keycode).map(Event::KeyUp),
+        let mut repeat_checked = false;
+        core::iter::from_fn(move || match self.backend.poll_event() {
+            Some(e) => {
+                match e {
+                    Event::KeyDown(key) => {
+                        self.held_key = match self.key_repeat {
+                            Some(repeat) if key.is_repeatable() && !self.backend.replays_events() => {
+                                Some((key, self.backend.now() + repeat.delay as u64))
+                            }
+                            _ => None,
+                        }
+                    }
+                    Event::KeyUp(key) if self.held_key.map(|(held, _)| held) == Some(key) => self.held_key = None,
+                    _ => (),
+                }
+                Some(e)
+            }
+            None if !repeat_checked => {
+                repeat_checked = true;
+                let repeat = self.key_repeat?;
+                let (key, next_repeat) = self.held_key?;
+                let now = self.backend.now();
+                if now < next_repeat {
+                    return None;
+                }
+                self.held_key = Some((key, now + repeat.interval as u64));
+                self.backend.log_event(Event::KeyRepeat(key));
+                Some(Event::KeyRepeat(key))
+            }
+            None => None,
         })
     }
+    /// Set how held keys are repeated, `None` disables the repetition.
+    pub fn set_key_repeat(&mut self, key_repeat: Option<KeyRepeat>) {
+        self.key_repeat = key_repeat;
+        if key_repeat.is_none() {
+            self.held_key = None;
+        }
+    }
+    /// Current settings of the key repetition.
+    pub fn get_key_repeat(&self) -> Option<KeyRepeat> {
+        self.key_repeat
+    }
+    /// Monotonic time in milliseconds, from an arbitrary starting point.
+    pub fn now(&self) -> u64 {
+        self.backend.now()
+    }
+
+    /// Read access to the backend.
+    pub fn get_backend(&self) -> &B {
+        &self.backend
+    }
+    /// Mutable access to the backend.
+    pub fn get_backend_mut(&mut self) -> &mut B {
+        &mut self.backend
+    }
+}
+
+/// Settings of the repetition of held keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyRepeat {
+    /// Time between a key press and its first repetition, in milliseconds.
+    pub delay: u32,
+    /// Time between two repetitions, in milliseconds.
+    pub interval: u32,
+}
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self {
+            delay: 300,
+            interval: 80,
+        }
+    }
 }
 
 /// This struct holds an event produced by the OS.
-#[derive(Debug)]
+///
+/// Events are displayed and parsed in the format used by event logs,
+/// e.g. `KeyDown Ok` or `HardQuit`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Event {
     /// A key has been pressed.
     KeyDown(KeyCode),
     /// A key has been released.
     KeyUp(KeyCode),
+    /// A key is being held, see [`Calculator::events()`].
+    KeyRepeat(KeyCode),
+    /// A timer scheduled in [`Timers`] has expired. The inner value is the id of the timer.
+    Timer(u32),
     /// Quit whatsoever, may be handled by the OS directly,
     /// so applications may or may not recieve it.
     HardQuit,
+    /// A layer shown above the widget by an [`OverlayStack`](crate::gui::widgets::OverlayStack) has been dismissed.
+    ///
+    /// It isn't produced by the OS, but given by the stack to what was below the layer.
+    LayerDismissed,
+}
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KeyDown(key) => write!(f, "KeyDown {}", key.name()),
+            Self::KeyUp(key) => write!(f, "KeyUp {}", key.name()),
+            Self::KeyRepeat(key) => write!(f, "KeyRepeat {}", key.name()),
+            Self::Timer(id) => write!(f, "Timer {}", id),
+            Self::HardQuit => write!(f, "HardQuit"),
+            Self::LayerDismissed => write!(f, "LayerDismissed"),
+        }
+    }
+}
+impl FromStr for Event {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let event = match (words.next(), words.next()) {
+            (Some("KeyDown"), Some(key)) => Self::KeyDown(KeyCode::from_name(key).ok_or(())?),
+            (Some("KeyUp"), Some(key)) => Self::KeyUp(KeyCode::from_name(key).ok_or(())?),
+            (Some("KeyRepeat"), Some(key)) => Self::KeyRepeat(KeyCode::from_name(key).ok_or(())?),
+            (Some("Timer"), Some(id)) => Self::Timer(id.parse().map_err(|_| ())?),
+            (Some("HardQuit"), None) => Self::HardQuit,
+            (Some("LayerDismissed"), None) => Self::LayerDismissed,
+            _ => return Err(()),
+        };
+        match words.next() {
+            None => Ok(event),
+            Some(_) => Err(()),
+        }
+    }
 }
 
 /// Raw key codes representing each key on the physical keyboard.
@@ -135,55 +306,113 @@ pub enum KeyCode {
     Exe,
 }
 impl KeyCode {
-    pub fn try_from_sdl2(key: sdl2::Keycode) -> Option<Self> {
-        match key {
-            sdl2::Keycode::Left => Some(Self::Left),
-            sdl2::Keycode::Up => Some(Self::Up),
-            sdl2::Keycode::Down => Some(Self::Down),
-            sdl2::Keycode::Right => Some(Self::Right),
-            // Ok,
-            sdl2::Keycode::Escape => Some(Self::Back),
-            sdl2::Keycode::Home => Some(Self::Home),
-            // OnOff,
-            sdl2::Keycode::S => Some(Self::Shift),
-            sdl2::Keycode::A => Some(Self::Alpha),
-            // Xnt,
-            // Var,
-            sdl2::Keycode::Tab => Some(Self::Toolbox),
-            sdl2::Keycode::Backspace => Some(Self::Backspace),
-            // Exp,
-            // Ln,
-            // Log,
-            // Imaginary,
-            sdl2::Keycode::Comma => Some(Self::Comma),
-            // Power,
-            // Sine,
-            // Cosine,
-            // Tangent,
-            // Pi,
-            // Sqrt,
-            // Square,
-            sdl2::Keycode::Num7 => Some(Self::Num7),
-            sdl2::Keycode::Num8 => Some(Self::Num8),
-            sdl2::Keycode::Num9 => Some(Self::Num9),
-            // LeftParen,
-            // RightParen,
-            sdl2::Keycode::Num4 => Some(Self::Num4),
-            sdl2::Keycode::Num5 => Some(Self::Num5),
-            sdl2::Keycode::Num6 => Some(Self::Num6),
-            // Multiplication,
-            sdl2::Keycode::Slash => Some(Self::Division),
-            sdl2::Keycode::Num1 => Some(Self::Num1),
-            sdl2::Keycode::Num2 => Some(Self::Num2),
-            sdl2::Keycode::Num3 => Some(Self::Num3),
-            // Plus,
-            sdl2::Keycode::Minus => Some(Self::Minus),
-            sdl2::Keycode::Num0 => Some(Self::Num0),
-            sdl2::Keycode::Period => Some(Self::Dot),
-            // Ee,
-            // Ans,
-            sdl2::Keycode::Return => Some(Self::Exe),
-            _ => None,
+    /// Every key of the keyboard.
+    pub const ALL: [KeyCode; 46] = [
+        Self::Left,
+        Self::Up,
+        Self::Down,
+        Self::Right,
+        Self::Ok,
+        Self::Back,
+        Self::Home,
+        Self::OnOff,
+        Self::Shift,
+        Self::Alpha,
+        Self::Xnt,
+        Self::Var,
+        Self::Toolbox,
+        Self::Backspace,
+        Self::Exp,
+        Self::Ln,
+        Self::Log,
+        Self::Imaginary,
+        Self::Comma,
+        Self::Power,
+        Self::Sine,
+        Self::Cosine,
+        Self::Tangent,
+        Self::Pi,
+        Self::Sqrt,
+        Self::Square,
+        Self::Num7,
+        Self::Num8,
+        Self::Num9,
+        Self::LeftParen,
+        Self::RightParen,
+        Self::Num4,
+        Self::Num5,
+        Self::Num6,
+        Self::Multiplication,
+        Self::Division,
+        Self::Num1,
+        Self::Num2,
+        Self::Num3,
+        Self::Plus,
+        Self::Minus,
+        Self::Num0,
+        Self::Dot,
+        Self::Ee,
+        Self::Ans,
+        Self::Exe,
+    ];
+
+    /// Name of the key, as used in event logs.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Left => "Left",
+            Self::Up => "Up",
+            Self::Down => "Down",
+            Self::Right => "Right",
+            Self::Ok => "Ok",
+            Self::Back => "Back",
+            Self::Home => "Home",
+            Self::OnOff => "OnOff",
+            Self::Shift => "Shift",
+            Self::Alpha => "Alpha",
+            Self::Xnt => "Xnt",
+            Self::Var => "Var",
+            Self::Toolbox => "Toolbox",
+            Self::Backspace => "Backspace",
+            Self::Exp => "Exp",
+            Self::Ln => "Ln",
+            Self::Log => "Log",
+            Self::Imaginary => "Imaginary",
+            Self::Comma => "Comma",
+            Self::Power => "Power",
+            Self::Sine => "Sine",
+            Self::Cosine => "Cosine",
+            Self::Tangent => "Tangent",
+            Self::Pi => "Pi",
+            Self::Sqrt => "Sqrt",
+            Self::Square => "Square",
+            Self::Num7 => "Num7",
+            Self::Num8 => "Num8",
+            Self::Num9 => "Num9",
+            Self::LeftParen => "LeftParen",
+            Self::RightParen => "RightParen",
+            Self::Num4 => "Num4",
+            Self::Num5 => "Num5",
+            Self::Num6 => "Num6",
+            Self::Multiplication => "Multiplication",
+            Self::Division => "Division",
+            Self::Num1 => "Num1",
+            Self::Num2 => "Num2",
+            Self::Num3 => "Num3",
+            Self::Plus => "Plus",
+            Self::Minus => "Minus",
+            Self::Num0 => "Num0",
+            Self::Dot => "Dot",
+            Self::Ee => "Ee",
+            Self::Ans => "Ans",
+            Self::Exe => "Exe",
         }
     }
+    /// Key with the given name, see [`KeyCode::name()`].
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|key| key.name() == name)
+    }
+    /// Whether holding the key repeats it: arrows and Backspace.
+    pub fn is_repeatable(self) -> bool {
+        matches!(self, Self::Left | Self::Up | Self::Down | Self::Right | Self::Backspace)
+    }
 }
@@ -0,0 +1,91 @@
+use core::convert::Infallible;
+
+use embedded_graphics::prelude::*;
+
+use super::{Color, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// Number of pixels of the calculator display, and of a [`Framebuffer`].
+pub const PIXEL_COUNT: usize = (SCREEN_WIDTH * SCREEN_HEIGHT) as usize;
+
+/// An in-memory image of the calculator display, drawn into borrowed pixels.
+///
+/// The pixels are stored row by row, f.e. in a `Vec` or in a `static` buffer:
+/// they're borrowed rather than owned, as they're too large to be moved around on the stack.
+///
+/// Pixels drawn outside of the screen are silently discarded.
+///
+/// # Example
+/// ```
+/// use embedded_graphics::prelude::*;
+/// use nw_gui::calculator::{Color, Framebuffer, PIXEL_COUNT};
+///
+/// let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+/// let mut framebuffer = Framebuffer::new(&mut pixels);
+/// Pixel(Point::new(1, 2), Color::WHITE).draw(&mut framebuffer).unwrap();
+/// assert_eq!(framebuffer.get_pixel(Point::new(1, 2)), Some(Color::WHITE));
+/// ```
+pub struct Framebuffer<'a> {
+    pixels: &'a mut [Color],
+}
+impl<'a> Framebuffer<'a> {
+    /// Create a new framebuffer drawing into `pixels`, which keep their color.
+    ///
+    /// # Panics
+    /// The function panics if there aren't exactly [`PIXEL_COUNT`] pixels.
+    pub fn new(pixels: &'a mut [Color]) -> Self {
+        if pixels.len() != PIXEL_COUNT {
+            panic!("A framebuffer needs {} pixels, not {}.", PIXEL_COUNT, pixels.len());
+        }
+        Self { pixels }
+    }
+
+    /// Color of the pixel at the given point.
+    ///
+    /// Return `None` if the point is outside of the screen.
+    pub fn get_pixel(&self, point: Point) -> Option<Color> {
+        Self::index_of(point).map(|index| self.pixels[index])
+    }
+
+    /// Iterator over all the pixels of the framebuffer, row by row.
+    pub fn iter_pixels(&self) -> impl Iterator<Item = Pixel<Color>> + '_ {
+        self.pixels.iter().enumerate().map(|(index, color)| {
+            let point = Point::new(
+                (index as u32 % SCREEN_WIDTH) as i32,
+                (index as u32 / SCREEN_WIDTH) as i32,
+            );
+            Pixel(point, *color)
+        })
+    }
+
+    fn index_of(point: Point) -> Option<usize> {
+        if point.x < 0 || point.y < 0 || point.x >= SCREEN_WIDTH as i32 || point.y >= SCREEN_HEIGHT as i32 {
+            return None;
+        }
+        Some(point.y as usize * SCREEN_WIDTH as usize + point.x as usize)
+    }
+}
+impl<'a> OriginDimensions for Framebuffer<'a> {
+    fn size(&self) -> Size {
+        Size::new(SCREEN_WIDTH, SCREEN_HEIGHT)
+    }
+}
+impl<'a> DrawTarget for Framebuffer<'a> {
+    type Color = Color;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            if let Some(index) = Self::index_of(point) {
+                self.pixels[index] = color;
+            }
+        }
+        Ok(())
+    }
+    fn clear(&mut self, color: Self::Color) -> Result<(), Self::Error> {
+        self.pixels.fill(color);
+        Ok(())
+    }
+}
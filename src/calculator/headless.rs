@@ -0,0 +1,155 @@
+use embedded_graphics::primitives::Rectangle;
+use heapless::Deque;
+
+use super::{Backend, Color, Event, Framebuffer};
+
+/// Default duration of a frame of [`HeadlessBackend`], in milliseconds.
+pub const DEFAULT_FRAME_DURATION: u32 = 20;
+
+/// [`Backend`] rendering into memory, with events taken from a scripted queue.
+///
+/// It doesn't need any display, so it can be used to run applications in automated tests.
+///
+/// Each call to [`Backend::flush()`] ends a frame, and advances the simulated time
+/// by the frame duration ([`DEFAULT_FRAME_DURATION`] by default).
+/// Events are scheduled for a given frame and are delivered once that frame has been flushed.
+/// `N` is the maximum number of events waiting in the queue.
+///
+/// The pixels of the display are borrowed rather than owned, see [`Framebuffer`].
+///
+/// # Example
+/// ```
+/// use embedded_graphics::prelude::*;
+/// use nw_gui::calculator::{Calculator, Color, Event, HeadlessBackend, KeyCode, PIXEL_COUNT};
+///
+/// let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+/// let mut calc = Calculator::new(HeadlessBackend::<8>::new(&mut pixels)).unwrap();
+/// calc.get_backend_mut().push_event(Event::KeyDown(KeyCode::Ok)).unwrap();
+/// calc.get_backend_mut().set_frame_limit(2);
+///
+/// calc.render();
+/// assert_eq!(calc.events().next(), Some(Event::KeyDown(KeyCode::Ok)));
+/// calc.render();
+/// assert_eq!(calc.events().next(), Some(Event::HardQuit));
+/// assert_eq!(calc.events().next(), None);
+/// ```
+pub struct HeadlessBackend<'a, const N: usize> {
+    framebuffer: Framebuffer<'a>,
+    events: Deque<(u32, Event), N>,
+    frame: u32,
+    frame_limit: Option<u32>,
+    quit: bool,
+    time: u64,
+    frame_duration: u32,
+    last_flushed_area: Option<Rectangle>,
+}
+impl<'a, const N: usize> HeadlessBackend<'a, N> {
+    /// Create a new backend rendering into `pixels`, with no scripted events.
+    ///
+    /// # Panics
+    /// The function panics if there aren't exactly [`PIXEL_COUNT`](super::PIXEL_COUNT) pixels.
+    pub fn new(pixels: &'a mut [Color]) -> Self {
+        Self {
+            framebuffer: Framebuffer::new(pixels),
+            events: Deque::new(),
+            frame: 0,
+            frame_limit: None,
+            quit: false,
+            time: 0,
+            frame_duration: DEFAULT_FRAME_DURATION,
+            last_flushed_area: None,
+        }
+    }
+
+    /// Schedule an event to be delivered after the current frame.
+    ///
+    /// Return the event back if it can't be scheduled, see [`push_event_at()`](Self::push_event_at()).
+    pub fn push_event(&mut self, e: Event) -> Result<(), Event> {
+        self.push_event_at(self.frame, e)
+    }
+    /// Schedule an event to be delivered after the given frame has been flushed.
+    ///
+    /// Events must be pushed in chronological order.
+    /// Return the event back if the queue is full,
+    /// or if an event is already scheduled for a later frame.
+    pub fn push_event_at(&mut self, frame: u32, e: Event) -> Result<(), Event> {
+        if matches!(self.events.back(), Some((last, _)) if *last > frame) {
+            return Err(e);
+        }
+        self.events.push_back((frame, e)).map_err(|(_, e)| e)
+    }
+
+    /// Stop the application after `frames` more frames.
+    ///
+    /// Once `frames` frames have been flushed, [`Backend::poll_event()`] returns [`Event::HardQuit`] once,
+    /// and then no event at all.
+    pub fn set_frame_limit(&mut self, frames: u32) {
+        self.frame_limit = Some(self.frame + frames);
+        self.quit = false;
+    }
+    /// Remove the frame limit.
+    pub fn clear_frame_limit(&mut self) {
+        self.frame_limit = None;
+        self.quit = false;
+    }
+
+    /// Set by how many milliseconds the simulated time advances at each frame.
+    pub fn set_frame_duration(&mut self, duration: u32) {
+        self.frame_duration = duration;
+    }
+    /// Advance the simulated time, in milliseconds.
+    pub fn advance_time(&mut self, duration: u32) {
+        self.time += duration as u64;
+    }
+
+    /// Number of frames flushed since the creation of the backend.
+    pub fn get_frame(&self) -> u32 {
+        self.frame
+    }
+    /// Area sent by the last frame, `None` if the whole display was sent.
+    pub fn get_last_flushed_area(&self) -> Option<Rectangle> {
+        self.last_flushed_area
+    }
+    /// Read access to the framebuffer, to inspect what has been rendered.
+    pub fn get_framebuffer(&self) -> &Framebuffer<'a> {
+        &self.framebuffer
+    }
+    /// Number of scripted events not delivered yet.
+    pub fn pending_events(&self) -> usize {
+        self.events.len()
+    }
+}
+impl<'a, const N: usize> Backend for HeadlessBackend<'a, N> {
+    type Display = Framebuffer<'a>;
+
+    fn get_draw_target(&mut self) -> &mut Framebuffer<'a> {
+        &mut self.framebuffer
+    }
+    fn flush(&mut self) {
+        self.frame += 1;
+        self.time += self.frame_duration as u64;
+        self.last_flushed_area = None;
+    }
+    fn flush_area(&mut self, area: Rectangle) {
+        self.flush();
+        self.last_flushed_area = Some(area);
+    }
+    fn poll_event(&mut self) -> Option<Event> {
+        if let Some(limit) = self.frame_limit {
+            if self.frame >= limit {
+                if self.quit {
+                    return None;
+                }
+                self.quit = true;
+                return Some(Event::HardQuit);
+            }
+        }
+        match self.events.front() {
+            Some((frame, _)) if *frame < self.frame => self.events.pop_front().map(|(_, e)| e),
+            _ => None,
+        }
+    }
+    fn now(&self) -> u64 {
+        self.time
+    }
+}
@@ -0,0 +1,113 @@
+use heapless::Vec;
+
+use super::KeyCode;
+
+/// Table translating the keys of a host keyboard into [`KeyCode`]s.
+///
+/// `K` is the type of the host keys, e.g. SDL keycodes for the simulator.
+/// Several host keys can be bound to the same [`KeyCode`].
+/// `N` is the maximum number of bindings.
+///
+/// # Example
+/// ```
+/// use nw_gui::calculator::{KeyCode, Keymap};
+///
+/// let mut keymap: Keymap<char> = Keymap::new();
+/// keymap.bind('o', KeyCode::Ok).unwrap();
+/// keymap
+///     .parse_bindings("# exe and back\ne = Exe\nb = Back\n", |name| name.chars().next())
+///     .unwrap();
+///
+/// assert_eq!(keymap.get('o'), Some(KeyCode::Ok));
+/// assert_eq!(keymap.get('b'), Some(KeyCode::Back));
+/// assert_eq!(keymap.get('z'), None);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Keymap<K, const N: usize = 128> {
+    bindings: Vec<(K, KeyCode), N>,
+}
+impl<K: Copy + PartialEq, const N: usize> Keymap<K, N> {
+    /// Create an empty keymap.
+    pub fn new() -> Self {
+        Self { bindings: Vec::new() }
+    }
+
+    /// Bind a host key to a [`KeyCode`], replacing any previous binding of that host key.
+    ///
+    /// Return the binding back if the keymap is full.
+    pub fn bind(&mut self, key: K, code: KeyCode) -> Result<(), (K, KeyCode)> {
+        match self.bindings.iter_mut().find(|(bound, _)| *bound == key) {
+            Some(binding) => {
+                binding.1 = code;
+                Ok(())
+            }
+            None => self.bindings.push((key, code)),
+        }
+    }
+    /// Remove the binding of a host key.
+    pub fn unbind(&mut self, key: K) {
+        self.bindings.retain(|(bound, _)| *bound != key);
+    }
+    /// [`KeyCode`] bound to the given host key.
+    pub fn get(&self, key: K) -> Option<KeyCode> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, code)| *code)
+    }
+    /// Iterator over the host keys bound to the given [`KeyCode`].
+    pub fn keys_for(&self, code: KeyCode) -> impl Iterator<Item = K> + '_ {
+        self.bindings
+            .iter()
+            .filter(move |(_, bound)| *bound == code)
+            .map(|(key, _)| *key)
+    }
+
+    /// Add the bindings described in `text`.
+    ///
+    /// Each line has the form `<host key> = <key code>`, where `<key code>` is a [`KeyCode::name()`]
+    /// and `<host key>` is translated by `resolve`.
+    /// Empty lines and lines starting with `#` are ignored.
+    ///
+    /// On error, the bindings of the lines before the faulty one have been added.
+    pub fn parse_bindings(&mut self, text: &str, resolve: impl Fn(&str) -> Option<K>) -> Result<(), KeymapError> {
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |kind| KeymapError { line: n + 1, kind };
+            let (key, code) = line.rsplit_once('=').ok_or(error(KeymapErrorKind::Syntax))?;
+            let key = resolve(key.trim()).ok_or(error(KeymapErrorKind::UnknownHostKey))?;
+            let code = KeyCode::from_name(code.trim()).ok_or(error(KeymapErrorKind::UnknownKeyCode))?;
+            self.bind(key, code).map_err(|_| error(KeymapErrorKind::Full))?;
+        }
+        Ok(())
+    }
+}
+impl<K: Copy + PartialEq, const N: usize> Default for Keymap<K, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Error returned when parsing a [`Keymap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeymapError {
+    /// Line of the error, starting at 1.
+    pub line: usize,
+    /// What went wrong.
+    pub kind: KeymapErrorKind,
+}
+/// Kind of a [`KeymapError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeymapErrorKind {
+    /// The line isn't of the form `<host key> = <key code>`.
+    Syntax,
+    /// The host key isn't known.
+    UnknownHostKey,
+    /// The key code isn't a [`KeyCode::name()`].
+    UnknownKeyCode,
+    /// The keymap can't hold more bindings.
+    Full,
+}
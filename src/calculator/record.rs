@@ -0,0 +1,278 @@
+use core::fmt::Write;
+use core::iter::Enumerate;
+use core::str::Lines;
+
+use embedded_graphics::primitives::Rectangle;
+
+use super::{Backend, Event};
+
+/// [`Backend`] wrapper writing every event polled from the inner backend to a log,
+/// as well as the events generated by the [`Calculator`](super::Calculator), e.g. [`Event::KeyRepeat`].
+///
+/// Each line of the log holds the number of frames flushed before the event was received,
+/// the time given by [`Backend::now()`], and the event, e.g. `12 240 KeyDown Ok`.
+/// The time is also written without any event when recording starts and after each frame, e.g. `13 260`,
+/// so that timers expire at the same frames when the log is replayed.
+/// The log can be fed back with [`ReplayBackend`].
+///
+/// `W` is where the log is written, e.g. a [`heapless::String`].
+pub struct RecordingBackend<B: Backend, W: Write> {
+    inner: B,
+    log: W,
+    frame: u32,
+    write_failed: bool,
+}
+impl<B: Backend, W: Write> RecordingBackend<B, W> {
+    /// Record the events of `inner` into `log`.
+    pub fn new(inner: B, log: W) -> Self {
+        let mut recorder = Self {
+            inner,
+            log,
+            frame: 0,
+            write_failed: false,
+        };
+        recorder.write_time();
+        recorder
+    }
+
+    /// Read access to the log.
+    pub fn get_log(&self) -> &W {
+        &self.log
+    }
+    /// Whether writing to the log has failed, in which case the log is incomplete.
+    pub fn has_write_failed(&self) -> bool {
+        self.write_failed
+    }
+    /// Read access to the inner backend.
+    pub fn get_inner(&self) -> &B {
+        &self.inner
+    }
+    /// Mutable access to the inner backend.
+    pub fn get_inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+    /// Stop recording, and return the inner backend and the log.
+    pub fn into_parts(self) -> (B, W) {
+        (self.inner, self.log)
+    }
+
+    fn write_event(&mut self, e: Event) {
+        if writeln!(self.log, "{} {} {}", self.frame, self.inner.now(), e).is_err() {
+            self.write_failed = true;
+        }
+    }
+    fn write_time(&mut self) {
+        if writeln!(self.log, "{} {}", self.frame, self.inner.now()).is_err() {
+            self.write_failed = true;
+        }
+    }
+}
+impl<B: Backend, W: Write> Backend for RecordingBackend<B, W> {
+    type Display = B::Display;
+
+    fn get_draw_target(&mut self) -> &mut B::Display {
+        self.inner.get_draw_target()
+    }
+    fn flush(&mut self) {
+        self.inner.flush();
+        self.frame += 1;
+        self.write_time();
+    }
+    fn flush_area(&mut self, area: Rectangle) {
+        self.inner.flush_area(area);
+        self.frame += 1;
+        self.write_time();
+    }
+    fn poll_event(&mut self) -> Option<Event> {
+        let e = self.inner.poll_event()?;
+        self.write_event(e);
+        Some(e)
+    }
+    fn now(&self) -> u64 {
+        self.inner.now()
+    }
+    fn log_event(&mut self, e: Event) {
+        self.inner.log_event(e);
+        self.write_event(e);
+    }
+    fn replays_events(&self) -> bool {
+        self.inner.replays_events()
+    }
+}
+
+/// [`Backend`] wrapper replaying a log written by [`RecordingBackend`].
+///
+/// The display is provided by the inner backend, whose events are discarded,
+/// except [`Event::HardQuit`].
+/// The log holds the repetitions of held keys, so the [`Calculator`](super::Calculator) doesn't generate them again.
+/// [`Backend::now()`] returns the time of the last line replayed, rather than the time of the inner backend,
+/// so that the replay doesn't depend on the frame duration.
+/// Once the log is over, or if a line can't be parsed, [`Event::HardQuit`] is returned,
+/// and the faulty line is given by [`get_error()`](ReplayBackend::get_error()).
+/// Empty lines and lines starting with `#` are ignored.
+///
+/// # Example
+/// ```
+/// use embedded_graphics::prelude::*;
+/// use nw_gui::calculator::{Calculator, Color, Event, HeadlessBackend, KeyCode, ReplayBackend, PIXEL_COUNT};
+///
+/// let log = "# pressing Ok once the first frame has been flushed\n0 0\n1 20\n1 20 KeyDown Ok\n";
+/// let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+/// let headless = HeadlessBackend::<1>::new(&mut pixels);
+/// let mut calc = Calculator::new(ReplayBackend::new(headless, log)).unwrap();
+/// assert_eq!(calc.events().next(), None);
+/// calc.render();
+/// assert_eq!(calc.events().next(), Some(Event::KeyDown(KeyCode::Ok)));
+/// assert_eq!(calc.now(), 20);
+/// assert_eq!(calc.events().next(), Some(Event::HardQuit));
+/// assert!(calc.get_backend().is_finished());
+/// ```
+pub struct ReplayBackend<'a, B: Backend> {
+    inner: B,
+    log: Enumerate<Lines<'a>>,
+    next: Option<(u32, u64, Option<Event>)>,
+    frame: u32,
+    time: u64,
+    finished: bool,
+    error: Option<ReplayError>,
+}
+impl<'a, B: Backend> ReplayBackend<'a, B> {
+    /// Replay `log` on the display of `inner`.
+    pub fn new(inner: B, log: &'a str) -> Self {
+        let mut replay = Self {
+            inner,
+            log: log.lines().enumerate(),
+            next: None,
+            frame: 0,
+            time: 0,
+            finished: false,
+            error: None,
+        };
+        replay.next = replay.parse_next();
+        replay.replay_time();
+        replay
+    }
+
+    /// Whether every event of the log has been replayed.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+    /// Line which couldn't be parsed, stopping the replay, if any.
+    pub fn get_error(&self) -> Option<ReplayError> {
+        self.error
+    }
+    /// Read access to the inner backend.
+    pub fn get_inner(&self) -> &B {
+        &self.inner
+    }
+    /// Mutable access to the inner backend.
+    pub fn get_inner_mut(&mut self) -> &mut B {
+        &mut self.inner
+    }
+
+    /// Skip the lines without any event up to the current frame, keeping the last time.
+    fn replay_time(&mut self) {
+        while let Some((frame, time, None)) = self.next {
+            if frame > self.frame {
+                break;
+            }
+            self.time = time;
+            self.next = self.parse_next();
+        }
+    }
+
+    fn parse_next(&mut self) -> Option<(u32, u64, Option<Event>)> {
+        for (n, line) in self.log.by_ref() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |kind| ReplayError { line: n + 1, kind };
+            let mut fields = line.splitn(3, ' ');
+            let parsed = match (fields.next(), fields.next(), fields.next()) {
+                (Some(frame), Some(time), e) => parse_fields(frame, time, e).map_err(error),
+                _ => Err(error(ReplayErrorKind::Syntax)),
+            };
+            match parsed {
+                Ok(parsed) => return Some(parsed),
+                Err(e) => {
+                    self.error = Some(e);
+                    return None;
+                }
+            }
+        }
+        self.finished = true;
+        None
+    }
+}
+impl<'a, B: Backend> Backend for ReplayBackend<'a, B> {
+    type Display = B::Display;
+
+    fn get_draw_target(&mut self) -> &mut B::Display {
+        self.inner.get_draw_target()
+    }
+    fn flush(&mut self) {
+        self.inner.flush();
+        self.frame += 1;
+        self.replay_time();
+    }
+    fn flush_area(&mut self, area: Rectangle) {
+        self.inner.flush_area(area);
+        self.frame += 1;
+        self.replay_time();
+    }
+    fn poll_event(&mut self) -> Option<Event> {
+        while let Some(e) = self.inner.poll_event() {
+            if e == Event::HardQuit {
+                return Some(e);
+            }
+        }
+        self.replay_time();
+        match self.next {
+            Some((frame, time, Some(e))) if frame <= self.frame => {
+                self.time = time;
+                self.next = self.parse_next();
+                Some(e)
+            }
+            Some(_) => None,
+            None => Some(Event::HardQuit),
+        }
+    }
+    fn now(&self) -> u64 {
+        self.time
+    }
+    fn replays_events(&self) -> bool {
+        true
+    }
+}
+
+fn parse_fields(frame: &str, time: &str, e: Option<&str>) -> Result<(u32, u64, Option<Event>), ReplayErrorKind> {
+    let frame = frame.parse().map_err(|_| ReplayErrorKind::InvalidFrame)?;
+    let time = time.parse().map_err(|_| ReplayErrorKind::InvalidTime)?;
+    let e = match e {
+        Some(e) => Some(e.parse().map_err(|_| ReplayErrorKind::InvalidEvent)?),
+        None => None,
+    };
+    Ok((frame, time, e))
+}
+
+/// Error stopping a [`ReplayBackend`], when a line of the log can't be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReplayError {
+    /// Line of the error, starting at 1.
+    pub line: usize,
+    /// What went wrong.
+    pub kind: ReplayErrorKind,
+}
+/// Kind of a [`ReplayError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayErrorKind {
+    /// The line isn't of the form `<frame> <time> <event>` or `<frame> <time>`.
+    Syntax,
+    /// The frame isn't a number.
+    InvalidFrame,
+    /// The time isn't a number.
+    InvalidTime,
+    /// The event can't be parsed, see [`Event`].
+    InvalidEvent,
+}
@@ -0,0 +1,213 @@
+use std::time::Instant;
+
+use embedded_graphics::prelude::*;
+use embedded_graphics_simulator::{sdl2, OutputSettings, SimulatorDisplay, SimulatorEvent, Window};
+
+use super::{Backend, Color, Event, KeyCode, Keymap, KeymapError, SCREEN_HEIGHT, SCREEN_WIDTH};
+
+/// [`Backend`] drawing into an SDL window on the host computer.
+///
+/// # Panics
+/// [`Backend::flush()`] must be called before [`Backend::poll_event()`],
+/// otherwise [`Backend::poll_event()`] will panic.
+pub struct SimulatorBackend {
+    display: SimulatorDisplay<Color>, // the pixels are stored on the heap by the simulator
+    window: Window,
+    keymap: Keymap<sdl2::Keycode>,
+    start: Instant,
+}
+impl SimulatorBackend {
+    /// Create a new backend, opening a window titled `Numworks`.
+    ///
+    /// Keys are translated with [`Keymap::sdl_default()`].
+    pub fn new() -> Self {
+        Self::with_keymap(Keymap::sdl_default())
+    }
+    /// Create a new backend translating keys with the given keymap.
+    pub fn with_keymap(keymap: Keymap<sdl2::Keycode>) -> Self {
+        Self {
+            display: SimulatorDisplay::new(Size::new(SCREEN_WIDTH, SCREEN_HEIGHT)),
+            window: Window::new("Numworks", &OutputSettings::default()),
+            keymap,
+            start: Instant::now(),
+        }
+    }
+
+    /// Read access to the keymap.
+    pub fn get_keymap(&self) -> &Keymap<sdl2::Keycode> {
+        &self.keymap
+    }
+    /// Mutable access to the keymap, which can be modified while the simulator runs.
+    pub fn get_keymap_mut(&mut self) -> &mut Keymap<sdl2::Keycode> {
+        &mut self.keymap
+    }
+}
+impl Default for SimulatorBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Backend for SimulatorBackend {
+    type Display = SimulatorDisplay<Color>;
+
+    fn get_draw_target(&mut self) -> &mut SimulatorDisplay<Color> {
+        &mut self.display
+    }
+    fn flush(&mut self) {
+        self.window.update(&self.display);
+    }
+    fn poll_event(&mut self) -> Option<Event> {
+        let keymap = &self.keymap;
+        self.window.events().find_map(|sdl_event| match sdl_event {
+            SimulatorEvent::MouseButtonDown { .. }
+            | SimulatorEvent::MouseButtonUp { .. }
+            | SimulatorEvent::MouseMove { .. }
+            | SimulatorEvent::MouseWheel { .. } => None,
+            SimulatorEvent::Quit => Some(Event::HardQuit),
+            // repetitions are generated by the calculator
+            SimulatorEvent::KeyDown { repeat: true, .. } => None,
+            SimulatorEvent::KeyDown {
+                keycode,
+                keymod: _,
+                repeat: false,
+            } => keymap.get(keycode).map(Event::KeyDown),
+            SimulatorEvent::KeyUp {
+                keycode,
+                keymod: _,
+                repeat: _,
+            } => keymap.get(keycode).map(Event::KeyUp),
+        })
+    }
+    fn now(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+}
+
+/// Default bindings of the simulator keyboard.
+///
+/// Every [`KeyCode`] is reachable. Letters are chosen after the name of the key when possible:
+///
+/// | Key | Host keys | Key | Host keys |
+/// |-----|-----------|-----|-----------|
+/// | Ok | Space | OnOff | End |
+/// | Back | Escape | Home | Home |
+/// | Shift | S | Alpha | A |
+/// | Xnt | X | Var | V |
+/// | Toolbox | Tab | Backspace | Backspace, Delete |
+/// | Exp | E | Ln | N |
+/// | Log | L | Imaginary | I |
+/// | Power | P, `^` | Sqrt | R |
+/// | Square | Q | Sine, Cosine, Tangent | F1, F2, F3 |
+/// | Pi | F4 | Ee | F5 |
+/// | Ans | F6 | Exe | Return, Keypad Enter |
+/// | LeftParen, RightParen | `[`, `]` | Plus | `=`, `+` |
+///
+/// Arrows, digits and the remaining operators are bound to the corresponding keys, including on the keypad.
+/// The host Shift keys aren't bound, as they are needed to type symbols such as `+` or `(` on many layouts,
+/// which would otherwise be combined with [`KeyCode::Shift`].
+pub const DEFAULT_SDL_BINDINGS: [(sdl2::Keycode, KeyCode); 67] = [
+    (sdl2::Keycode::Left, KeyCode::Left),
+    (sdl2::Keycode::Up, KeyCode::Up),
+    (sdl2::Keycode::Down, KeyCode::Down),
+    (sdl2::Keycode::Right, KeyCode::Right),
+    (sdl2::Keycode::Space, KeyCode::Ok),
+    (sdl2::Keycode::Escape, KeyCode::Back),
+    (sdl2::Keycode::Home, KeyCode::Home),
+    (sdl2::Keycode::End, KeyCode::OnOff),
+    (sdl2::Keycode::S, KeyCode::Shift),
+    (sdl2::Keycode::A, KeyCode::Alpha),
+    (sdl2::Keycode::X, KeyCode::Xnt),
+    (sdl2::Keycode::V, KeyCode::Var),
+    (sdl2::Keycode::Tab, KeyCode::Toolbox),
+    (sdl2::Keycode::Backspace, KeyCode::Backspace),
+    (sdl2::Keycode::Delete, KeyCode::Backspace),
+    (sdl2::Keycode::E, KeyCode::Exp),
+    (sdl2::Keycode::N, KeyCode::Ln),
+    (sdl2::Keycode::L, KeyCode::Log),
+    (sdl2::Keycode::I, KeyCode::Imaginary),
+    (sdl2::Keycode::Comma, KeyCode::Comma),
+    (sdl2::Keycode::P, KeyCode::Power),
+    (sdl2::Keycode::Caret, KeyCode::Power),
+    (sdl2::Keycode::F1, KeyCode::Sine),
+    (sdl2::Keycode::F2, KeyCode::Cosine),
+    (sdl2::Keycode::F3, KeyCode::Tangent),
+    (sdl2::Keycode::F4, KeyCode::Pi),
+    (sdl2::Keycode::R, KeyCode::Sqrt),
+    (sdl2::Keycode::Q, KeyCode::Square),
+    (sdl2::Keycode::Num7, KeyCode::Num7),
+    (sdl2::Keycode::Kp7, KeyCode::Num7),
+    (sdl2::Keycode::Num8, KeyCode::Num8),
+    (sdl2::Keycode::Kp8, KeyCode::Num8),
+    (sdl2::Keycode::Num9, KeyCode::Num9),
+    (sdl2::Keycode::Kp9, KeyCode::Num9),
+    (sdl2::Keycode::LeftBracket, KeyCode::LeftParen),
+    (sdl2::Keycode::LeftParen, KeyCode::LeftParen),
+    (sdl2::Keycode::RightBracket, KeyCode::RightParen),
+    (sdl2::Keycode::RightParen, KeyCode::RightParen),
+    (sdl2::Keycode::Num4, KeyCode::Num4),
+    (sdl2::Keycode::Kp4, KeyCode::Num4),
+    (sdl2::Keycode::Num5, KeyCode::Num5),
+    (sdl2::Keycode::Kp5, KeyCode::Num5),
+    (sdl2::Keycode::Num6, KeyCode::Num6),
+    (sdl2::Keycode::Kp6, KeyCode::Num6),
+    (sdl2::Keycode::Asterisk, KeyCode::Multiplication),
+    (sdl2::Keycode::KpMultiply, KeyCode::Multiplication),
+    (sdl2::Keycode::Slash, KeyCode::Division),
+    (sdl2::Keycode::KpDivide, KeyCode::Division),
+    (sdl2::Keycode::Num1, KeyCode::Num1),
+    (sdl2::Keycode::Kp1, KeyCode::Num1),
+    (sdl2::Keycode::Num2, KeyCode::Num2),
+    (sdl2::Keycode::Kp2, KeyCode::Num2),
+    (sdl2::Keycode::Num3, KeyCode::Num3),
+    (sdl2::Keycode::Kp3, KeyCode::Num3),
+    (sdl2::Keycode::Equals, KeyCode::Plus),
+    (sdl2::Keycode::Plus, KeyCode::Plus),
+    (sdl2::Keycode::KpPlus, KeyCode::Plus),
+    (sdl2::Keycode::Minus, KeyCode::Minus),
+    (sdl2::Keycode::KpMinus, KeyCode::Minus),
+    (sdl2::Keycode::Num0, KeyCode::Num0),
+    (sdl2::Keycode::Kp0, KeyCode::Num0),
+    (sdl2::Keycode::Period, KeyCode::Dot),
+    (sdl2::Keycode::KpPeriod, KeyCode::Dot),
+    (sdl2::Keycode::F5, KeyCode::Ee),
+    (sdl2::Keycode::F6, KeyCode::Ans),
+    (sdl2::Keycode::Return, KeyCode::Exe),
+    (sdl2::Keycode::KpEnter, KeyCode::Exe),
+];
+
+impl Keymap<sdl2::Keycode> {
+    /// Keymap containing the [`DEFAULT_SDL_BINDINGS`].
+    pub fn sdl_default() -> Self {
+        let mut keymap = Self::new();
+        for (key, code) in DEFAULT_SDL_BINDINGS {
+            // the default capacity of a keymap is larger than the default bindings
+            keymap.bind(key, code).unwrap();
+        }
+        keymap
+    }
+    /// Add the bindings described in `text`, using SDL key names (e.g. `Return` or `Left Shift`).
+    ///
+    /// See [`Keymap::parse_bindings()`] for the format.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use nw_gui::calculator::{Keymap, SimulatorBackend};
+    ///
+    /// let mut keymap = Keymap::sdl_default();
+    /// keymap.parse_sdl_bindings(&std::fs::read_to_string("keymap.txt").unwrap()).unwrap();
+    /// let backend = SimulatorBackend::with_keymap(keymap);
+    /// ```
+    pub fn parse_sdl_bindings(&mut self, text: &str) -> Result<(), KeymapError> {
+        self.parse_bindings(text, sdl2::Keycode::from_name)
+    }
+}
+
+impl KeyCode {
+    /// Translate an SDL keycode with the [`DEFAULT_SDL_BINDINGS`].
+    pub fn try_from_sdl2(key: sdl2::Keycode) -> Option<Self> {
+        DEFAULT_SDL_BINDINGS
+            .iter()
+            .find(|(bound, _)| *bound == key)
+            .map(|(_, code)| *code)
+    }
+}
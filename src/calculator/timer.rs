@@ -0,0 +1,114 @@
+use heapless::Vec;
+
+use super::Event;
+
+/// Queue of timers, each one identified by an id chosen by the application.
+///
+/// Timers are one-shot: a timer is removed once it has expired,
+/// and must be scheduled again to fire periodically.
+///
+/// The application sets the clock of the queue with [`Calculator::now()`](super::Calculator::now()),
+/// then dispatches the expired timers returned by [`poll_event()`](Timers::poll_event()).
+/// `N` is the maximum number of pending timers.
+///
+/// # Example
+/// ```
+/// use nw_gui::calculator::Timers;
+///
+/// let mut timers: Timers = Timers::new();
+/// timers.schedule(1, 500).unwrap();
+/// timers.schedule(2, 100).unwrap();
+///
+/// timers.set_now(200);
+/// assert_eq!(timers.pop_expired(), Some(2));
+/// assert_eq!(timers.pop_expired(), None);
+/// timers.set_now(500);
+/// assert_eq!(timers.pop_expired(), Some(1));
+/// ```
+///
+/// In the main loop of an application, with the timers stored in the shared state:
+/// ```ignore
+/// self.state.timers.set_now(calc.now());
+/// while let Some(e) = self.state.timers.poll_event() {
+///     self.gui.on_event(e, &mut self.state);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Timers<const N: usize = 8> {
+    now: u64,
+    pending: Vec<(u64, u32), N>,
+}
+impl<const N: usize> Timers<N> {
+    /// Create an empty queue, whose clock is at 0.
+    pub fn new() -> Self {
+        Self {
+            now: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Current time of the queue, in milliseconds.
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+    /// Set the current time of the queue, in milliseconds.
+    pub fn set_now(&mut self, now: u64) {
+        self.now = now;
+    }
+
+    /// Schedule the timer `id` to expire after `delay` milliseconds.
+    ///
+    /// A timer with the same id that is already pending is rescheduled.
+    /// Return the id back if the queue is full.
+    pub fn schedule(&mut self, id: u32, delay: u32) -> Result<(), u32> {
+        let deadline = self.now + delay as u64;
+        match self.pending.iter_mut().find(|(_, pending)| *pending == id) {
+            Some(timer) => {
+                timer.0 = deadline;
+                Ok(())
+            }
+            None => self.pending.push((deadline, id)).map_err(|(_, id)| id),
+        }
+    }
+    /// Cancel the timer `id`, if it is pending.
+    pub fn cancel(&mut self, id: u32) {
+        self.pending.retain(|(_, pending)| *pending != id);
+    }
+    /// Whether the timer `id` is pending.
+    pub fn is_scheduled(&self, id: u32) -> bool {
+        self.pending.iter().any(|(_, pending)| *pending == id)
+    }
+    /// Time at which the next timer expires, if any.
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.pending.iter().map(|(deadline, _)| *deadline).min()
+    }
+
+    /// Remove the earliest expired timer, and return its id.
+    pub fn pop_expired(&mut self) -> Option<u32> {
+        let (index, _) = self
+            .pending
+            .iter()
+            .enumerate()
+            .filter(|(_, (deadline, _))| *deadline <= self.now)
+            .min_by_key(|(_, (deadline, _))| *deadline)?;
+        Some(self.pending.remove(index).1)
+    }
+    /// Remove the earliest expired timer, and return the corresponding [`Event::Timer`].
+    pub fn poll_event(&mut self) -> Option<Event> {
+        self.pop_expired().map(Event::Timer)
+    }
+}
+impl<const N: usize> Default for Timers<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// This trait is implemented by types giving access to a [`Timers`] queue.
+///
+/// Widgets can require a bound on it for their context type,
+/// so that they can request to be woken up after a delay.
+pub trait TimerContext {
+    /// Read-write access to the timers.
+    fn get_timers(&mut self) -> &mut Timers;
+}
@@ -1,10 +1,10 @@
 //! This module contains types and traits used to create a GUI.
 
-use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::{prelude::*, primitives::Rectangle};
 
 use either::Either;
 
-use crate::calculator::{DeviceDislay, Event};
+use crate::calculator::{Color, Event};
 
 pub mod storage;
 pub mod widgets;
@@ -24,8 +24,16 @@ pub trait Widget {
 
     /// The `render()` method draws the widget onto the given target.
     ///
+    /// The target can be the display, but also an off-screen buffer or a clipped or translated target,
+    /// see [`DrawTargetExt`](embedded_graphics::draw_target::DrawTargetExt).
+    ///
     /// The widgte should be rendered with the specified theme.
-    fn render(&self, target: &mut DeviceDislay, theme: &Theme);
+    ///
+    /// If the target fails to draw, the widget should still attempt to render the rest of itself,
+    /// and return the first error.
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>;
     /// The `on_event()` method dispatches the given event to the widget,
     /// which may pass the event to its children.
     ///
@@ -36,12 +44,57 @@ pub trait Widget {
     fn get_bounding_box(&self) -> Rectangle;
     /// Set the bounding box of a widget.
     fn set_bounding_box(&mut self, bounding_box: Rectangle);
+    /// Minimum and preferred size of the widget, if it can take at most `available`.
+    ///
+    /// Containers use it to fit their children to their content.
+    /// The preferred size may exceed `available` if the minimum size does, see [`SizeHint`].
+    /// By default, the widget has no minimum size and takes all the available space.
+    fn measure(&self, available: Size) -> SizeHint {
+        SizeHint {
+            min: Size::zero(),
+            preferred: available,
+        }
+    }
 
     /// Focused state of the widget.
     ///
     /// `None` if the widget is unfocusable,
     /// `Some(true)` if the widget is focused, `Some(false)` if the widget isn't focused but can be focused.
     fn get_focus(&self) -> Option<bool>;
+    /// Area of the focused widget, which can be this widget or one of its descendants.
+    ///
+    /// Return `None` if nothing is focused.
+    /// Scrolling containers use it to keep the focused widget visible.
+    /// By default, the whole bounding box if this widget is focused.
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        (self.get_focus() == Some(true)).then(|| self.get_bounding_box())
+    }
+
+    /// Area of the widget that changed since the last call to [`mark_clean()`](Widget::mark_clean()).
+    ///
+    /// Return `None` if the widget doesn't need to be rendered again.
+    /// By default, the whole bounding box is always considered as changed.
+    ///
+    /// Changes of the [`Theme`] aren't tracked: the whole widget must be rendered again after such a change.
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        Some(self.get_bounding_box())
+    }
+    /// Mark the widget and its children as up to date, usually after rendering them.
+    fn mark_clean(&mut self) {}
+    /// Render only the parts of the widget that changed,
+    /// see [`get_dirty_area()`](Widget::get_dirty_area()).
+    ///
+    /// By default, the whole widget is rendered if anything changed.
+    fn render_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        if self.get_dirty_area().is_some() {
+            self.render(target, theme)
+        } else {
+            Ok(())
+        }
+    }
 
     /// Focus the widget.
     ///
@@ -52,6 +105,7 @@ pub trait Widget {
     ///
     /// Return `Ok` on success, and `Err` if the widget cannot be focused *temporarily*.
     /// `Err` doesn't indicate that the widget can never be focused.
+    #[allow(clippy::result_unit_err)]
     fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()>;
 }
 
@@ -66,7 +120,10 @@ impl<T, L: Widget<Context = T>, R: Widget<Context = T>> Widget for Either<L, R>
     ) -> Option<crate::calculator::Event> {
         either::for_both!(self, w => w.on_event(e, context))
     }
-    fn render(&self, target: &mut crate::calculator::DeviceDislay, theme: &Theme) {
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
         either::for_both!(self, w => w.render(target, theme))
     }
     fn set_bounding_box(&mut self, bounding_box: embedded_graphics::primitives::Rectangle) {
@@ -75,12 +132,93 @@ impl<T, L: Widget<Context = T>, R: Widget<Context = T>> Widget for Either<L, R>
     fn get_bounding_box(&self) -> Rectangle {
         either::for_both!(self, w => w.get_bounding_box())
     }
+    fn measure(&self, available: Size) -> SizeHint {
+        either::for_both!(self, w => w.measure(available))
+    }
     fn get_focus(&self) -> Option<bool> {
         either::for_both!(self, w => w.get_focus())
     }
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        either::for_both!(self, w => w.get_focused_area())
+    }
     fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
         either::for_both!(self, w => w.set_focus(from_dir))
     }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        either::for_both!(self, w => w.get_dirty_area())
+    }
+    fn mark_clean(&mut self) {
+        either::for_both!(self, w => w.mark_clean())
+    }
+    fn render_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        either::for_both!(self, w => w.render_dirty(target, theme))
+    }
+}
+
+/// Smallest rectangle containing both `a` and `b`, if any.
+///
+/// Useful to merge dirty areas, see [`Widget::get_dirty_area()`].
+pub fn union_area(a: Option<Rectangle>, b: Option<Rectangle>) -> Option<Rectangle> {
+    let is_empty = |area: &Rectangle| area.size.width == 0 || area.size.height == 0;
+    match (a.filter(|a| !is_empty(a)), b.filter(|b| !is_empty(b))) {
+        (Some(a), Some(b)) => {
+            let top_left = a.top_left.component_min(b.top_left);
+            let bottom_right = (a.top_left + a.size).component_max(b.top_left + b.size);
+            Some(Rectangle::with_corners(top_left, bottom_right - Point::new(1, 1)))
+        }
+        (a, None) => a,
+        (None, b) => b,
+    }
+}
+
+/// Size requirements of a widget, see [`Widget::measure()`].
+///
+/// The preferred size is never smaller than the minimum size, even if the minimum size isn't available.
+/// [`SizeHint::new()`] enforces it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeHint {
+    /// Below this size, the widget can't be rendered correctly.
+    pub min: Size,
+    /// Size the widget would like to have, at most the available size unless `min` is larger.
+    pub preferred: Size,
+}
+impl SizeHint {
+    /// Size hint of a widget needing `min`, and preferring `preferred` if it's available.
+    ///
+    /// The preferred size is clamped between `min` and `available`, `min` taking precedence.
+    pub fn new(min: Size, preferred: Size, available: Size) -> Self {
+        Self {
+            min,
+            preferred: preferred.component_min(available).component_max(min),
+        }
+    }
+}
+
+/// Space left around text by widgets fitting their content, in pixels.
+pub(crate) const TEXT_PADDING: u32 = 4;
+
+/// Size of `text` rendered with [`NORMAL_FONT`], on one line.
+pub(crate) fn text_size(text: &str) -> Size {
+    let characters = text.chars().count() as u32;
+    Size::new(
+        characters * NORMAL_FONT.character_size.width + characters.saturating_sub(1) * NORMAL_FONT.character_spacing,
+        NORMAL_FONT.character_size.height,
+    )
+}
+/// Number of characters of [`NORMAL_FONT`] fitting in `width` pixels, on one line.
+pub(crate) fn text_columns(width: u32) -> usize {
+    ((width + NORMAL_FONT.character_spacing) / (NORMAL_FONT.character_size.width + NORMAL_FONT.character_spacing))
+        as usize
+}
+
+/// Error returned when a widget can't be rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderError {
+    /// The draw target failed to draw.
+    DrawTarget,
 }
 
 /// Direction from which focus is coming when moving the focus.
@@ -98,3 +236,7 @@ pub enum FocusFrom {
 /// Callbacks used by widgets.
 #[derive(Debug, Clone, Copy)]
 pub struct Callback<T>(fn(&mut T));
+
+/// Callbacks used by widgets made of several items, receiving the index of the item.
+#[derive(Debug, Clone, Copy)]
+pub struct IndexCallback<T>(fn(&mut T, usize));
@@ -1,6 +1,11 @@
 //! Types and traits to store collections of widgets used by the GUI.
 
-use super::Widget;
+use core::fmt::Debug;
+use core::ops::DerefMut;
+
+use super::{theme::Theme, union_area, FocusFrom, RenderError, Widget};
+use crate::calculator::Color;
+use embedded_graphics::{prelude::*, primitives::Rectangle};
 use heapless::Vec;
 
 /// Types implementing this trait can be used as uniform storage for [`Widget`]s.
@@ -32,9 +37,17 @@ use heapless::Vec;
 /// ```
 pub trait WidgetCollection {
     type Item: Widget;
+    /// Storage of one value for each widget of the collection, with the same capacity.
+    ///
+    /// Containers use it to keep data about their children, e.g. their layout.
+    type Values<V: Clone + Debug>: ValueCollection<V>;
 
     /// Number of widgets in the collection.
     fn len(&self) -> usize;
+    /// Whether the collection is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
     /// Read-only access to the n-th widget in the collection.
     fn get(&self, n: usize) -> Option<&Self::Item>;
     /// Mutable access to the n-th widget in the collection.
@@ -42,11 +55,98 @@ pub trait WidgetCollection {
     /// Add a widget to the collection.
     /// Return the widget back if it can't be added.
     fn add_widget(&mut self, widget: Self::Item) -> Result<(), Self::Item>;
+
+    /// Render every widget of the collection, in order.
+    ///
+    /// The widgets are expected not to overlap.
+    /// Rendering goes on after an error, and the first error is returned.
+    fn render_all<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let mut result = Ok(());
+        for n in 0..self.len() {
+            if let Some(widget) = self.get(n) {
+                result = result.and(widget.render(target, theme));
+            }
+        }
+        result
+    }
+    /// Render what changed in every widget of the collection, see [`Widget::render_dirty()`].
+    fn render_all_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let mut result = Ok(());
+        for n in 0..self.len() {
+            if let Some(widget) = self.get(n) {
+                result = result.and(widget.render_dirty(target, theme));
+            }
+        }
+        result
+    }
+    /// Union of the dirty areas of the widgets, see [`Widget::get_dirty_area()`].
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        (0..self.len()).fold(None, |area, n| {
+            union_area(area, self.get(n).and_then(|widget| widget.get_dirty_area()))
+        })
+    }
+    /// Mark every widget as clean, see [`Widget::mark_clean()`].
+    fn mark_clean(&mut self) {
+        for n in 0..self.len() {
+            if let Some(widget) = self.get_mut(n) {
+                widget.mark_clean();
+            }
+        }
+    }
+
+    /// Try to focus the n-th widget, and unfocus the `previous` one on success.
+    #[allow(clippy::result_unit_err)]
+    fn focus_widget(&mut self, n: usize, previous: Option<usize>, from_dir: FocusFrom) -> Result<(), ()> {
+        self.get_mut(n).ok_or(())?.set_focus(Some(from_dir))?;
+        if let Some(widget) = previous.filter(|previous| *previous != n).and_then(|n| self.get_mut(n)) {
+            widget.set_focus(None).unwrap_or_default();
+        }
+        Ok(())
+    }
+    /// Focus the `focused` widget again if possible,
+    /// otherwise the first widget that can be focused, starting from the end if `from_end` is `true`.
+    ///
+    /// Return the id of the focused widget, or an error if no widget can be focused.
+    #[allow(clippy::result_unit_err)]
+    fn focus_any(&mut self, focused: Option<usize>, from_dir: FocusFrom, from_end: bool) -> Result<usize, ()> {
+        if let Some(n) = focused {
+            if self.focus_widget(n, focused, from_dir).is_ok() {
+                return Ok(n);
+            }
+        }
+        let len = self.len();
+        (0..len)
+            .map(|i| if from_end { len - 1 - i } else { i })
+            .find(|n| self.focus_widget(*n, focused, from_dir).is_ok())
+            .ok_or(())
+    }
+    /// Unfocus the `focused` widget, if any.
+    #[allow(clippy::result_unit_err)]
+    fn unfocus_widget(&mut self, focused: Option<usize>) -> Result<(), ()> {
+        match focused.and_then(|n| self.get_mut(n)) {
+            Some(widget) => widget.set_focus(None),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Fixed-capacity storage of values, see [`WidgetCollection::Values`].
+pub trait ValueCollection<V>: Default + Clone + Debug + DerefMut<Target = [V]> {
+    /// Add a value at the end of the collection.
+    /// Return the value back if the collection is full.
+    fn push(&mut self, value: V) -> Result<(), V>;
 }
 
 /// [`Vec`](heapless::Vec) can be used as a WidgetCollection.
 impl<U: Widget, const N: usize> WidgetCollection for Vec<U, N> {
     type Item = U;
+    type Values<V: Clone + Debug> = Vec<V, N>;
 
     // the double dereference transforming &Vec -> Vec -> &[T]
     // forces the use of the underlying methods
@@ -64,3 +164,9 @@ impl<U: Widget, const N: usize> WidgetCollection for Vec<U, N> {
         self.push(widget)
     }
 }
+
+impl<V: Clone + Debug, const N: usize> ValueCollection<V> for Vec<V, N> {
+    fn push(&mut self, value: V) -> Result<(), V> {
+        Vec::push(self, value)
+    }
+}
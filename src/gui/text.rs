@@ -2,12 +2,12 @@
 //!
 //! # Example
 //! ```
-//! use nw_gui::gui::text::TextInputContext;
+//! use nw_gui::gui::text::TextInputState;
 //! use nw_gui::gui::text::TextInput;
 //! use nw_gui::calculator::{Event, KeyCode};
 //!
 //! use heapless::Vec;
-//! let mut context = TextInputContext::new();
+//! let mut context = TextInputState::new();
 //! let mut keys: Vec<KeyCode, 5> = Vec::new();
 //! keys.push(KeyCode::Num6);
 //! keys.push(KeyCode::Shift);
@@ -32,7 +32,9 @@
 //! assert_eq!(result, expected);
 //! ```
 
-use crate::calculator::{Event, KeyCode};
+use heapless::{String, Vec};
+
+use crate::calculator::{Event, KeyCode, Timers};
 
 /// State of the alpha key.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -75,9 +77,13 @@ pub enum Action {
     Left(bool),
     Ok,
     Paste,
+    /// Shift + Plus.
+    Redo,
     /// The inner bool is whether Shift was active.
     Right(bool),
     Toolbox,
+    /// Shift + Minus.
+    Undo,
     /// The inner bool is whether Shift was active.
     Up(bool),
     Var,
@@ -110,9 +116,10 @@ impl TextInputState {
     }
     /// Use the context to process the given event.
     ///
+    /// [`Event::KeyRepeat`] is processed as a key press.
     /// This may modifiy the context, f.e. when pressing Shift or Alpha.
     pub fn text_from_event(&mut self, e: &Event) -> TextInput {
-        if let Event::KeyDown(key) = e {
+        if let Event::KeyDown(key) | Event::KeyRepeat(key) = e {
             match key {
                 KeyCode::Alpha => match self.alpha {
                     AlphaState::No => {
@@ -236,7 +243,7 @@ impl TextInputState {
                 KeyCode::RightParen => self.basic_key(")", ")", "q", "Q"),
                 KeyCode::Ln => self.basic_key("ln(", "]", "b", "B"),
                 KeyCode::Log => self.basic_key("log(", "{", "c", "C"),
-                KeyCode::Minus => self.shift_action_key("-", TextInput::None, " ", " "),
+                KeyCode::Minus => self.shift_action_key("-", TextInput::Action(Action::Undo), " ", " "),
                 KeyCode::Multiplication => self.shift_action_key("*", TextInput::None, "u", "U"),
                 KeyCode::Num0 => self.shift_action_key("0", TextInput::None, "?", "?"),
                 KeyCode::Num1 => self.shift_action_key("1", TextInput::None, "w", "W"),
@@ -250,7 +257,7 @@ impl TextInputState {
                 KeyCode::Num9 => self.shift_action_key("9", TextInput::None, "o", "O"),
                 KeyCode::OnOff => TextInput::None,
                 KeyCode::Pi => self.basic_key("pi", "=", "j", "J"),
-                KeyCode::Plus => self.shift_action_key("+", TextInput::None, "z", "Z"),
+                KeyCode::Plus => self.shift_action_key("+", TextInput::Action(Action::Redo), "z", "Z"),
                 KeyCode::Power => self.basic_key("^", "->", "f", "F"),
                 KeyCode::Sine => self.basic_key("sin(", "asin(", "g", "G"),
                 KeyCode::Tangent => self.basic_key("tan(", "atan(", "i", "I"),
@@ -336,11 +343,384 @@ impl TextInputState {
     }
 }
 
+impl Default for TextInputState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// This trait is implemented by types able to act as an input context.
-/// 
+///
 /// Widget such as [`TextBox`](crate::gui::widgets::TextBox) can require a bound on it for their context type,
 /// so that the input state can be shared with the whole application context.
 pub trait TextInputContext {
     /// Read-write access to the context
     fn get_context(&mut self) -> &mut TextInputState;
+    /// Timers used to make the cursor of text fields blink, if any.
+    ///
+    /// By default, there are no timers and the cursor doesn't blink.
+    fn get_timers(&mut self) -> Option<&mut Timers> {
+        None
+    }
+    /// Clipboard shared by the text fields, if any.
+    ///
+    /// By default, there is no clipboard and Copy, Cut and Paste aren't used.
+    fn get_clipboard(&mut self) -> Option<&mut Clipboard> {
+        None
+    }
+    /// Popup menus asked for by the text fields, if any.
+    ///
+    /// By default, there are no popups and Toolbox and Var aren't used.
+    fn get_popups(&mut self) -> Option<&mut Popups> {
+        None
+    }
+}
+
+/// Maximum length of the text in a [`Clipboard`], in bytes.
+pub const CLIPBOARD_CAPACITY: usize = 64;
+
+/// Text copied by the user, shared between text fields through their [`TextInputContext`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Clipboard {
+    text: String<CLIPBOARD_CAPACITY>,
+}
+impl Clipboard {
+    /// Create an empty clipboard.
+    pub fn new() -> Self {
+        Self { text: String::new() }
+    }
+    /// Text in the clipboard.
+    pub fn get(&self) -> &str {
+        &self.text
+    }
+    /// Replace the text in the clipboard.
+    ///
+    /// Text longer than [`CLIPBOARD_CAPACITY`] is truncated.
+    pub fn set(&mut self, text: &str) {
+        self.text.clear();
+        for c in text.chars() {
+            if self.text.push(c).is_err() {
+                break;
+            }
+        }
+    }
+    /// Empty the clipboard.
+    pub fn clear(&mut self) {
+        self.text.clear();
+    }
+    /// Whether the clipboard is empty.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+}
+
+/// Popup menu asked for by a text field, see [`Popups`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Popup {
+    /// Snippets to insert, asked for with Toolbox.
+    Toolbox,
+    /// Variables to insert, asked for with Var.
+    Var,
+}
+
+/// Maximum length of the text chosen in a popup, in bytes.
+pub const POPUP_TEXT_CAPACITY: usize = 32;
+
+/// Popup menus asked for by the text fields, and the text chosen in them,
+/// shared through the [`TextInputContext`].
+///
+/// A text field asks for a popup with [`request()`](Popups::request()).
+/// The application takes the request and shows the popup above the other widgets,
+/// f.e. a [`Toolbox`](crate::gui::widgets::Toolbox) pushed onto an [`OverlayStack`](crate::gui::widgets::OverlayStack),
+/// and the popup gives the chosen text back with [`choose()`](Popups::choose()).
+/// The text field inserts it when it gets [`Event::LayerDismissed`](crate::calculator::Event::LayerDismissed),
+/// which the [`OverlayStack`](crate::gui::widgets::OverlayStack) gives once the popup is dismissed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Popups {
+    request: Option<Popup>,
+    chosen: Option<String<POPUP_TEXT_CAPACITY>>,
+}
+impl Popups {
+    /// Create a channel without requests.
+    pub fn new() -> Self {
+        Self {
+            request: None,
+            chosen: None,
+        }
+    }
+    /// Ask the application to show a popup.
+    pub fn request(&mut self, popup: Popup) {
+        self.request = Some(popup);
+        self.chosen = None;
+    }
+    /// Take the popup asked for, if any.
+    pub fn take_request(&mut self) -> Option<Popup> {
+        self.request.take()
+    }
+    /// Give the text chosen in a popup to the text field which asked for it.
+    pub fn choose(&mut self, text: String<POPUP_TEXT_CAPACITY>) {
+        self.chosen = Some(text);
+    }
+    /// Take the text chosen in a popup, if any.
+    pub fn take_chosen(&mut self) -> Option<String<POPUP_TEXT_CAPACITY>> {
+        self.chosen.take()
+    }
+}
+
+/// Size of an [`EditHistory`] by default, in bytes.
+pub const HISTORY_SIZE: usize = 256;
+
+/// Bytes taken by an edit in an [`EditHistory`], besides the removed and inserted texts:
+/// where it happened and the cursor before it, and the lengths of the texts at both ends of the edit
+/// to go through the edits both ways, on 4 bytes each.
+const EDIT_OVERHEAD: usize = 24;
+
+/// Change of an edited text, its fields being in bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Edit {
+    position: usize, // where the text was removed, then inserted
+    cursor: usize,   // position of the cursor before the change
+    removed: usize,  // length of the removed text
+    inserted: usize, // length of the inserted text
+}
+impl Edit {
+    fn len(&self) -> usize {
+        self.removed + self.inserted + EDIT_OVERHEAD
+    }
+}
+
+/// Replace `old` at `position` in `text` by `new`.
+///
+/// Returns false if `old` isn't there or if `new` doesn't fit, leaving `text` unchanged.
+fn replace<const N: usize>(text: &mut String<N>, position: usize, old: &[u8], new: &[u8]) -> bool {
+    let end = position + old.len();
+    let replaced = match (text.get(..position), text.get(position..end), text.get(end..)) {
+        (Some(start), Some(current), Some(rest)) if current.as_bytes() == old => {
+            let mut replaced: String<N> = String::new();
+            let new = core::str::from_utf8(new).unwrap_or_default();
+            let fits = [start, new, rest].iter().all(|part| replaced.push_str(part).is_ok());
+            fits.then_some(replaced)
+        }
+        _ => None,
+    };
+    replaced.map(|replaced| *text = replaced).is_some()
+}
+
+/// Bounded history of the changes of an edited text, to undo and redo them.
+///
+/// Only the text removed and inserted by each change is kept,
+/// the changes undone and the ones to redo sharing the same `B` bytes.
+/// Once they are full, the oldest changes are forgotten.
+/// With `B` being 0, f.e. for text fields which can't be edited, no change is kept.
+#[derive(Debug, Clone, Default)]
+pub struct EditHistory<const B: usize = HISTORY_SIZE> {
+    // changes to undo, oldest first, followed by the changes to redo, next one first
+    edits: Vec<u8, B>,
+    undone: usize, // start of the changes to redo in `edits`
+}
+impl<const B: usize> EditHistory<B> {
+    /// Create an empty history.
+    pub fn new() -> Self {
+        Self {
+            edits: Vec::new(),
+            undone: 0,
+        }
+    }
+    /// Record a change of the text from `before` to `after`, the cursor being at `cursor` before it.
+    ///
+    /// Nothing is recorded if the text is the same.
+    /// The changes undone can't be redone anymore.
+    /// A change too large to fit in the history makes it forget every change.
+    pub fn record(&mut self, before: &str, after: &str, cursor: usize) {
+        let prefix = before.bytes().zip(after.bytes()).take_while(|(a, b)| a == b).count();
+        let suffix = before.as_bytes()[prefix..]
+            .iter()
+            .rev()
+            .zip(after.as_bytes()[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        // the change is made of whole characters
+        let mut position = prefix;
+        while !before.is_char_boundary(position) || !after.is_char_boundary(position) {
+            position -= 1;
+        }
+        let (mut removed_end, mut inserted_end) = (before.len() - suffix, after.len() - suffix);
+        while !before.is_char_boundary(removed_end) || !after.is_char_boundary(inserted_end) {
+            removed_end += 1;
+            inserted_end += 1;
+        }
+        let (removed, inserted) = (&before[position..removed_end], &after[position..inserted_end]);
+        if removed.is_empty() && inserted.is_empty() {
+            return;
+        }
+        self.edits.truncate(self.undone);
+        let edit = Edit {
+            position,
+            cursor,
+            removed: removed.len(),
+            inserted: inserted.len(),
+        };
+        if edit.len() > B {
+            self.clear();
+            return;
+        }
+        // the changes to redo have been forgotten already
+        while B - self.edits.len() < edit.len() {
+            self.forget_oldest();
+        }
+        // can't fail: there is enough space left
+        self.push_lengths(&edit);
+        self.edits.extend_from_slice(removed.as_bytes()).unwrap_or_default();
+        self.edits.extend_from_slice(inserted.as_bytes()).unwrap_or_default();
+        for field in [edit.position, edit.cursor] {
+            self.push_field(field);
+        }
+        self.push_lengths(&edit);
+        self.undone = self.edits.len();
+    }
+    /// Undo the last change, restoring the text and the cursor as they were before it.
+    ///
+    /// Returns whether there was a change to undo.
+    /// If the text has been changed without being recorded, the history is forgotten.
+    pub fn undo<const N: usize>(&mut self, text: &mut String<N>, cursor: &mut usize) -> bool {
+        if self.undone == 0 {
+            return false;
+        }
+        let edit = self.edit_before(self.undone);
+        let start = self.undone - edit.len();
+        let (removed, inserted) = self.texts(start, &edit);
+        if !replace(text, edit.position, inserted, removed) {
+            self.clear();
+            return false;
+        }
+        *cursor = edit.cursor;
+        self.undone = start;
+        true
+    }
+    /// Redo the last change undone, putting the cursor after the inserted text.
+    ///
+    /// Returns whether there was a change to redo.
+    /// If the text has been changed without being recorded, the history is forgotten.
+    pub fn redo<const N: usize>(&mut self, text: &mut String<N>, cursor: &mut usize) -> bool {
+        if self.undone == self.edits.len() {
+            return false;
+        }
+        let edit = self.edit_at(self.undone);
+        let (removed, inserted) = self.texts(self.undone, &edit);
+        if !replace(text, edit.position, removed, inserted) {
+            self.clear();
+            return false;
+        }
+        *cursor = edit.position + edit.inserted;
+        self.undone += edit.len();
+        true
+    }
+    /// Whether there is a change to undo.
+    pub fn can_undo(&self) -> bool {
+        self.undone > 0
+    }
+    /// Whether there is a change to redo.
+    pub fn can_redo(&self) -> bool {
+        self.undone < self.edits.len()
+    }
+    /// Forget all the changes.
+    pub fn clear(&mut self) {
+        self.edits.clear();
+        self.undone = 0;
+    }
+
+    /// Removed and inserted texts of the edit starting at `start`.
+    fn texts(&self, start: usize, edit: &Edit) -> (&[u8], &[u8]) {
+        let removed = start + 8;
+        let inserted = removed + edit.removed;
+        (
+            &self.edits[removed..inserted],
+            &self.edits[inserted..inserted + edit.inserted],
+        )
+    }
+    /// Edit starting at `start`.
+    fn edit_at(&self, start: usize) -> Edit {
+        let removed = self.field(start);
+        let inserted = self.field(start + 4);
+        self.edit_before(start + removed + inserted + EDIT_OVERHEAD)
+    }
+    /// Edit ending at `end`.
+    fn edit_before(&self, end: usize) -> Edit {
+        Edit {
+            position: self.field(end - 16),
+            cursor: self.field(end - 12),
+            removed: self.field(end - 8),
+            inserted: self.field(end - 4),
+        }
+    }
+    /// Remove the oldest change to undo.
+    fn forget_oldest(&mut self) {
+        let len = self.edit_at(0).len();
+        self.edits.copy_within(len.., 0);
+        self.edits.truncate(self.edits.len() - len);
+        self.undone -= len;
+    }
+    fn field(&self, start: usize) -> usize {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&self.edits[start..start + 4]);
+        u32::from_le_bytes(bytes) as usize
+    }
+    fn push_field(&mut self, field: usize) {
+        // can't fail: the space has been checked by the caller
+        self.edits
+            .extend_from_slice(&(field as u32).to_le_bytes())
+            .unwrap_or_default();
+    }
+    fn push_lengths(&mut self, edit: &Edit) {
+        self.push_field(edit.removed);
+        self.push_field(edit.inserted);
+    }
+}
+
+/// Lines of a text wrapped to a number of columns, as their start and end in the text, in bytes.
+///
+/// Lines are broken after their last space if they're too long, or in the middle of a word
+/// too long to fit on a line. The space where a line is broken isn't part of any line.
+pub(crate) struct Lines<'a> {
+    text: &'a str,
+    columns: usize,
+    next: Option<usize>, // start of the next line
+}
+impl<'a> Lines<'a> {
+    pub(crate) fn new(text: &'a str, columns: usize) -> Self {
+        Self {
+            text,
+            columns: columns.max(1),
+            next: Some(0),
+        }
+    }
+}
+impl Iterator for Lines<'_> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        let start = self.next?;
+        let mut last_space = None;
+        for (column, (position, c)) in self.text[start..].char_indices().enumerate() {
+            let position = start + position;
+            if c == '\n' {
+                self.next = Some(position + 1);
+                return Some((start, position));
+            }
+            if column == self.columns {
+                let (end, next) = match last_space {
+                    _ if c == ' ' => (position, position + 1),
+                    Some(space) => (space, space + 1),
+                    None => (position, position),
+                };
+                self.next = Some(next);
+                return Some((start, end));
+            }
+            if c == ' ' {
+                last_space = Some(position);
+            }
+        }
+        self.next = None;
+        Some((start, self.text.len()))
+    }
 }
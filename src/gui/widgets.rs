@@ -3,8 +3,8 @@
 mod rectangles;
 pub use rectangles::*;
 
-// mod grid;
-// pub use grid::*;
+mod grid;
+pub use grid::*;
 
 mod buttons;
 pub use buttons::*;
@@ -12,5 +12,38 @@ pub use buttons::*;
 mod layout;
 pub use layout::*;
 
+mod linear_layout;
+pub use linear_layout::*;
+
+mod flex_layout;
+pub use flex_layout::*;
+
+mod scroll_view;
+pub use scroll_view::*;
+
+mod list_view;
+pub use list_view::*;
+
+mod tab_layout;
+pub use tab_layout::*;
+
+mod overlay;
+pub use overlay::*;
+
+mod dialog;
+pub use dialog::*;
+
+mod toolbox;
+pub use toolbox::*;
+
+mod label;
+pub use label::*;
+
+mod status_bar;
+pub use status_bar::*;
+
 mod text_box;
 pub use text_box::*;
+
+mod text_area;
+pub use text_area::*;
@@ -1,6 +1,8 @@
 use super::ColorRect;
-use crate::calculator::{Event, KeyCode};
-use crate::gui::{theme::Theme, Callback, FocusFrom, Widget, NORMAL_FONT};
+use crate::calculator::{Color, Event, KeyCode};
+use crate::gui::{
+    text_size, theme::Theme, Callback, FocusFrom, RenderError, SizeHint, Widget, NORMAL_FONT, TEXT_PADDING,
+};
 
 use embedded_graphics::{
     mono_font::MonoTextStyleBuilder,
@@ -20,6 +22,7 @@ pub struct Button<T> {
     pressed: bool,
     on_pressed: Callback<T>,
     focused: bool,
+    dirty: bool,
 }
 impl<T> Button<T> {
     /// Creates a new button.
@@ -30,11 +33,13 @@ impl<T> Button<T> {
             pressed: false,
             on_pressed: Callback(on_pressed),
             focused: false,
+            dirty: true,
         }
     }
     /// Modify the text displayed on the button.
     pub fn set_text(&mut self, text: String<16>) {
         self.text = text;
+        self.dirty = true;
     }
 }
 impl<T> Widget for Button<T> {
@@ -45,17 +50,22 @@ impl<T> Widget for Button<T> {
         match e {
             Event::KeyDown(KeyCode::Ok) | Event::KeyDown(KeyCode::Exe) => {
                 self.pressed = true;
+                self.dirty = true;
                 (self.on_pressed.0)(context);
                 None
             }
             Event::KeyUp(KeyCode::Ok) | Event::KeyUp(KeyCode::Exe) => {
                 self.pressed = false;
+                self.dirty = true;
                 None
             }
             _ => Some(e),
         }
     }
-    fn render(&self, target: &mut crate::calculator::DeviceDislay, theme: &Theme) {
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
         let text_color = if self.pressed {
             theme.active
         } else if self.focused {
@@ -63,7 +73,7 @@ impl<T> Widget for Button<T> {
         } else {
             theme.foreground
         };
-        self.background.render(target, theme);
+        let background = self.background.render(target, theme);
         let character_style = MonoTextStyleBuilder::new()
             .font(&NORMAL_FONT)
             .text_color(text_color)
@@ -79,7 +89,8 @@ impl<T> Widget for Button<T> {
             text_style,
         )
         .draw(target)
-        .unwrap();
+        .map_err(|_| RenderError::DrawTarget)?;
+        background
     }
     fn set_bounding_box(&mut self, bounding_box: embedded_graphics::primitives::Rectangle) {
         self.background.set_bounding_box(bounding_box);
@@ -87,11 +98,31 @@ impl<T> Widget for Button<T> {
     fn get_bounding_box(&self) -> embedded_graphics::primitives::Rectangle {
         self.background.get_bounding_box()
     }
+    /// A button prefers to fit its text.
+    fn measure(&self, available: Size) -> SizeHint {
+        let text = text_size(&self.text);
+        SizeHint::new(text, text + Size::new(2 * TEXT_PADDING, 2 * TEXT_PADDING), available)
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        if self.dirty {
+            Some(self.get_bounding_box())
+        } else {
+            self.background.get_dirty_area()
+        }
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+        self.background.mark_clean();
+    }
     fn get_focus(&self) -> Option<bool> {
         Some(self.focused)
     }
     fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
-        self.focused = from_dir.is_some();
+        let focused = from_dir.is_some();
+        if focused != self.focused || (!focused && self.pressed) {
+            self.dirty = true;
+        }
+        self.focused = focused;
         if !self.focused {
             self.pressed = false;
         }
@@ -0,0 +1,219 @@
+use super::{Button, ChildSize, LinearLayout, Modal, SplitDirection};
+use crate::calculator::{Color, Event, KeyCode};
+use crate::gui::theme::Theme;
+use crate::gui::{text_size, FocusFrom, RenderError, SizeHint, Widget, NORMAL_FONT, TEXT_PADDING};
+
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    prelude::*,
+    primitives::{PrimitiveStyleBuilder, Rectangle},
+    text::{Baseline, Text},
+};
+use heapless::{String, Vec};
+
+/// Space between the border of a [`Dialog`] and its content, in pixels.
+pub const DIALOG_PADDING: u32 = 2 * TEXT_PADDING;
+/// Height of the row of buttons of a [`Dialog`].
+const BUTTONS_HEIGHT: u32 = NORMAL_FONT.character_size.height + 2 * TEXT_PADDING;
+
+/// A box with a title, a message, and up to 3 buttons in a row.
+///
+/// The dialog is dismissed by pressing Back, or by pressing one of its buttons
+/// after its callback is called.
+/// Without buttons, Ok and Exe also dismiss the dialog.
+///
+/// It is meant to be shown above other widgets with an [`OverlayStack`](super::OverlayStack).
+///
+/// `T` is the type of [`Widget::Context`].
+#[derive(Debug, Clone)]
+pub struct Dialog<T> {
+    title: String<32>,
+    message: String<128>,
+    buttons: LinearLayout<Vec<Button<T>, 3>>,
+    bounding_box: Rectangle,
+    focused: bool,
+    dismissed: bool,
+    dirty: bool, // the whole dialog must be rendered again
+}
+impl<T> Dialog<T> {
+    /// Create a new dialog without buttons.
+    ///
+    /// The message may be made of several lines separated by `'\n'`.
+    pub fn new(title: String<32>, message: String<128>) -> Self {
+        Self {
+            title,
+            message,
+            buttons: LinearLayout::new(
+                Rectangle::default(),
+                SplitDirection::Horizontal,
+                TEXT_PADDING,
+                Vec::new(),
+            ),
+            bounding_box: Rectangle::default(),
+            focused: false,
+            dismissed: false,
+            dirty: true,
+        }
+    }
+
+    /// Add a button after the others.
+    ///
+    /// `on_pressed` is called when the button is pressed, then the dialog is dismissed.
+    ///
+    /// If the button is successfully added, then its id is returned,
+    /// otherwise the button is returned back.
+    pub fn add_button(&mut self, text: String<16>, on_pressed: fn(&mut T)) -> Result<usize, Button<T>> {
+        let button = Button::new(text, Rectangle::default(), on_pressed);
+        let id = self.buttons.add_child(button, ChildSize::Weight(1), (0, 0))?;
+        self.dirty = true;
+        Ok(id)
+    }
+    /// Modify the message displayed by the dialog.
+    pub fn set_message(&mut self, message: String<128>) {
+        self.message = message;
+        self.dirty = true;
+    }
+    /// Id of the focused button, if any.
+    pub fn get_focused_button(&self) -> Option<usize> {
+        self.buttons.get_focused_child()
+    }
+    /// Number of lines of the message.
+    fn message_lines(&self) -> u32 {
+        self.message.split('\n').count() as u32
+    }
+}
+impl<T> Modal for Dialog<T> {
+    fn is_dismissed(&self) -> bool {
+        self.dismissed
+    }
+}
+impl<T> Widget for Dialog<T> {
+    type Context = T;
+
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let style = PrimitiveStyleBuilder::new()
+            .fill_color(theme.background)
+            .stroke_color(theme.foreground)
+            .stroke_width(theme.rect_border)
+            .build();
+        let mut result = self
+            .bounding_box
+            .into_styled(style)
+            .draw(target)
+            .map_err(|_| RenderError::DrawTarget);
+        let mut content = target.clipped(&self.bounding_box);
+        let top_left = self.bounding_box.top_left + Size::new(DIALOG_PADDING, DIALOG_PADDING);
+        result = result.and(
+            Text::with_baseline(
+                &self.title,
+                top_left,
+                MonoTextStyle::new(&NORMAL_FONT, theme.focused),
+                Baseline::Top,
+            )
+            .draw(&mut content)
+            .map(|_| ())
+            .map_err(|_| RenderError::DrawTarget),
+        );
+        result = result.and(
+            Text::with_baseline(
+                &self.message,
+                top_left + Size::new(0, NORMAL_FONT.character_size.height + TEXT_PADDING),
+                MonoTextStyle::new(&NORMAL_FONT, theme.foreground),
+                Baseline::Top,
+            )
+            .draw(&mut content)
+            .map(|_| ())
+            .map_err(|_| RenderError::DrawTarget),
+        );
+        result.and(self.buttons.render(target, theme))
+    }
+    fn render_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        if self.dirty {
+            return self.render(target, theme);
+        }
+        self.buttons.render_dirty(target, theme)
+    }
+    fn on_event(&mut self, e: Event, context: &mut T) -> Option<Event> {
+        if e == Event::KeyDown(KeyCode::Back) {
+            self.dismissed = true;
+            return None;
+        }
+        let pressed = matches!(e, Event::KeyDown(KeyCode::Ok | KeyCode::Exe))
+            && (self.buttons.is_empty() || self.buttons.get_focused_child().is_some());
+        let remaining_event = self.buttons.on_event(e, context);
+        if pressed {
+            self.dismissed = true;
+            return None;
+        }
+        remaining_event
+    }
+    fn set_bounding_box(&mut self, bounding_box: Rectangle) {
+        self.bounding_box = bounding_box;
+        self.dirty = true;
+        let padding = Size::new(DIALOG_PADDING, DIALOG_PADDING);
+        let size = bounding_box.size.saturating_sub(padding * 2);
+        let height = BUTTONS_HEIGHT.min(size.height);
+        self.buttons.set_bounding_box(Rectangle::new(
+            bounding_box.top_left + padding + Size::new(0, size.height - height),
+            Size::new(size.width, height),
+        ));
+    }
+    fn get_bounding_box(&self) -> Rectangle {
+        self.bounding_box
+    }
+    /// A dialog prefers to fit its title, its message and its buttons.
+    fn measure(&self, available: Size) -> SizeHint {
+        let padding = 2 * DIALOG_PADDING;
+        let text_width = self
+            .message
+            .split('\n')
+            .map(|line| text_size(line).width)
+            .fold(text_size(&self.title).width, u32::max);
+        let buttons = self
+            .buttons
+            .measure(available.saturating_sub(Size::new(padding, padding)));
+        let mut height = (self.message_lines() + 1) * NORMAL_FONT.character_size.height + TEXT_PADDING;
+        if !self.buttons.is_empty() {
+            height += TEXT_PADDING + BUTTONS_HEIGHT;
+        }
+        let size = Size::new(text_width.max(buttons.preferred.width) + padding, height + padding);
+        SizeHint::new(size, size, available)
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        if self.dirty {
+            Some(self.bounding_box)
+        } else {
+            self.buttons.get_dirty_area()
+        }
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+        self.buttons.mark_clean();
+    }
+    fn get_focus(&self) -> Option<bool> {
+        Some(self.focused)
+    }
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        match (self.focused, self.buttons.is_empty()) {
+            (true, false) => self.buttons.get_focused_area(),
+            (true, true) => Some(self.bounding_box),
+            (false, _) => None,
+        }
+    }
+    /// A dialog being focused is shown again, even if it was dismissed.
+    fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
+        self.focused = from_dir.is_some();
+        if self.focused {
+            self.dismissed = false;
+        }
+        // a dialog without buttons is still focused
+        self.buttons.set_focus(from_dir).unwrap_or_default();
+        Ok(())
+    }
+}
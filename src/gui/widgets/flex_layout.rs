@@ -0,0 +1,562 @@
+use crate::calculator::{Color, Event, KeyCode};
+use crate::gui::theme::Theme;
+use crate::gui::{
+    storage::{ValueCollection, WidgetCollection},
+    FocusFrom, RenderError, SizeHint, Widget,
+};
+
+use super::SplitDirection;
+
+use core::ops::Range;
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+/// A flexible row or column of widgets, similar to CSS flexbox.
+///
+/// Its children are stored in a collection of type `C`,
+/// and are placed one after the other in the layout direction (the main axis),
+/// separated by `spacing` pixels.
+/// Each child starts from its basis size, then grows or shrinks so that the line is filled,
+/// within its minimum and maximum sizes. See [`FlexItem`].
+///
+/// If wrapping is enabled, children that don't fit are placed on a new line,
+/// lines being separated by `spacing` pixels too.
+///
+/// The focus is moved with the arrow keys to the nearest child in that direction,
+/// skipping children that can't be focused.
+///
+/// # Example
+/// ```
+/// use embedded_graphics::{prelude::*, primitives::Rectangle};
+/// use nw_gui::gui::widgets::{Button, FlexAlign, FlexItem, FlexLayout, SplitDirection};
+/// use nw_gui::gui::Widget;
+/// use heapless::{String, Vec};
+/// // A row with a label fitting its text, and a button taking the rest of the space,
+/// // both vertically centered.
+/// let mut row: FlexLayout<Vec<Button<()>, 2>> = FlexLayout::new(
+///     Rectangle::new(Point::new(0, 0), Size::new(320, 40)),
+///     SplitDirection::Horizontal,
+///     4,
+///     Vec::new(),
+/// );
+/// row.set_align(FlexAlign::Center);
+/// row.add_child(Button::new(String::from("Angle"), Rectangle::default(), |_| ()), FlexItem::default()).unwrap();
+/// row.add_child(
+///     Button::new(String::from("Degrees"), Rectangle::default(), |_| ()),
+///     FlexItem { grow: 1, ..FlexItem::default() },
+/// ).unwrap();
+/// assert_eq!(
+///     row.get(1).unwrap().get_bounding_box(),
+///     Rectangle::new(Point::new(47, 9), Size::new(273, 21)),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct FlexLayout<C: WidgetCollection> {
+    direction: SplitDirection,
+    children: C,
+    items: C::Values<FlexItem>,
+    spacing: u32,
+    align: FlexAlign,
+    wrap: bool,
+    focused: Option<usize>,
+    bounding_box: Rectangle,
+    dirty: bool, // the whole layout must be rendered again
+}
+
+/// How a child of a [`FlexLayout`] is sized.
+///
+/// Sizes are in the layout direction, and don't include the margins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlexItem {
+    /// Size of the child before growing or shrinking.
+    /// If `None`, the preferred size of the child is used, see [`Widget::measure()`].
+    pub basis: Option<u32>,
+    /// Share of the free space of the line taken by the child, relative to the other children.
+    pub grow: u32,
+    /// How much the child shrinks when the line overflows, relative to the other children.
+    /// The shrink factor is weighted by the basis size.
+    pub shrink: u32,
+    /// The child doesn't shrink below this size.
+    pub min: u32,
+    /// The child doesn't grow above this size.
+    pub max: u32,
+    /// Alignment of the child across the layout direction, overriding the alignment of the layout.
+    pub align: Option<FlexAlign>,
+    /// (horizontal, vertical). The margins are applied at each side of the child.
+    pub margins: (u32, u32),
+}
+impl Default for FlexItem {
+    /// The child takes its preferred size, can shrink but doesn't grow.
+    fn default() -> Self {
+        Self {
+            basis: None,
+            grow: 0,
+            shrink: 1,
+            min: 0,
+            max: u32::MAX,
+            align: None,
+            margins: (0, 0),
+        }
+    }
+}
+
+/// Alignment of the children of a [`FlexLayout`] across the layout direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlexAlign {
+    /// Left or top.
+    Start,
+    /// Centered.
+    Center,
+    /// Right or bottom.
+    End,
+    /// The child takes the whole line.
+    Stretch,
+}
+
+impl<C: WidgetCollection> FlexLayout<C> {
+    /// Create a new `FlexLayout` filling the specified bounding box.
+    ///
+    /// The children are stretched across the layout direction, and aren't wrapped.
+    ///
+    /// `children` is a the collection used to store the children of the layout.
+    /// It should be empty.
+    ///
+    /// # Panics
+    /// The function panics if `children` isn't empty.
+    pub fn new(bounding_box: Rectangle, direction: SplitDirection, spacing: u32, children: C) -> Self {
+        if !children.is_empty() {
+            panic!("Can't create a flex layout from a non-empty collection.");
+        }
+        Self {
+            direction,
+            children,
+            items: Default::default(),
+            spacing,
+            align: FlexAlign::Stretch,
+            wrap: false,
+            focused: None,
+            bounding_box,
+            dirty: true,
+        }
+    }
+
+    /// Add a child widget at the end of the layout.
+    ///
+    /// If the child is successfully added, then its id is returned,
+    /// otherwise the child is returned back.
+    /// The bounding boxes of all the children are calculated again.
+    pub fn add_child(&mut self, child: C::Item, item: FlexItem) -> Result<usize, C::Item> {
+        self.children.add_widget(child)?;
+        // can't fail: the items have the capacity of the children
+        self.items.push(item).unwrap_or_default();
+        self.dirty = true;
+        self.set_children_bounding_box();
+        Ok(self.children.len() - 1)
+    }
+
+    /// Change how a child is sized.
+    ///
+    /// The item is returned back if there is no such child.
+    pub fn set_item(&mut self, n: usize, item: FlexItem) -> Result<(), FlexItem> {
+        *self.items.get_mut(n).ok_or(item)? = item;
+        self.dirty = true;
+        self.set_children_bounding_box();
+        Ok(())
+    }
+    /// Change the alignment of the children across the layout direction.
+    pub fn set_align(&mut self, align: FlexAlign) {
+        self.align = align;
+        self.dirty = true;
+        self.set_children_bounding_box();
+    }
+    /// Choose whether the children that don't fit are placed on a new line.
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+        self.dirty = true;
+        self.set_children_bounding_box();
+    }
+
+    /// Number of children.
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+    /// Whether the layout has no children.
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+    /// Read-only acces to a child.
+    pub fn get(&self, n: usize) -> Option<&C::Item> {
+        self.children.get(n)
+    }
+    /// Mutable acces to a child.
+    pub fn get_mut(&mut self, n: usize) -> Option<&mut C::Item> {
+        self.children.get_mut(n)
+    }
+    /// Id of the focused child, if any.
+    pub fn get_focused_child(&self) -> Option<usize> {
+        self.focused
+    }
+
+    /// Try to focus the given child, unfocusing the previously focused child on success.
+    fn focus_child(&mut self, n: usize, from_dir: FocusFrom) -> Result<(), ()> {
+        self.children.focus_widget(n, self.focused, from_dir)?;
+        self.focused = Some(n);
+        Ok(())
+    }
+
+    /// Move the focus to the nearest child in the given direction.
+    fn move_focus(&mut self, key: KeyCode) -> Result<(), ()> {
+        let from_dir = match key {
+            KeyCode::Left => FocusFrom::Right,
+            KeyCode::Right => FocusFrom::Left,
+            KeyCode::Up => FocusFrom::Down,
+            KeyCode::Down => FocusFrom::Up,
+            _ => return Err(()),
+        };
+        let focused = self.focused.ok_or(())?;
+        let current = self.children.get(focused).ok_or(())?.get_bounding_box();
+        // (distance in the direction, distance across it) to the children in the given direction
+        let mut candidates = C::Values::<Option<(u32, u32)>>::default();
+        for n in 0..self.children.len() {
+            let other = self.children.get(n).unwrap().get_bounding_box();
+            let distance = if n == focused {
+                None
+            } else {
+                direction_distance(current, other, key)
+            };
+            // can't fail: there is one candidate per child
+            candidates.push(distance).unwrap_or_default();
+        }
+        // try the nearest children first
+        while let Some((n, _)) = candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(n, distance)| distance.map(|distance| (n, distance)))
+            .min_by_key(|(_, distance)| *distance)
+        {
+            if self.focus_child(n, from_dir).is_ok() {
+                return Ok(());
+            }
+            candidates[n] = None;
+        }
+        Err(())
+    }
+
+    /// Length of `size` in the layout direction, and across it.
+    fn main_and_cross(&self, size: Size) -> (u32, u32) {
+        match self.direction {
+            SplitDirection::Horizontal => (size.width, size.height),
+            SplitDirection::Vertical => (size.height, size.width),
+        }
+    }
+    fn size_of_main_and_cross(&self, main: u32, cross: u32) -> Size {
+        match self.direction {
+            SplitDirection::Horizontal => Size::new(main, cross),
+            SplitDirection::Vertical => Size::new(cross, main),
+        }
+    }
+
+    /// Margins of a child at both ends (in the layout direction, across it).
+    fn margins(&self, n: usize) -> (u32, u32) {
+        let margins = self.items[n].margins;
+        self.main_and_cross(Size::new(margins.0 * 2, margins.1 * 2))
+    }
+
+    /// Basis (in the layout direction, across it) of the children, and their minimum size, margins excluded.
+    fn bases(&self, available: Size) -> C::Values<[(u32, u32); 2]> {
+        let mut bases = C::Values::default();
+        for (n, item) in self.items.iter().enumerate() {
+            let margins = Size::new(item.margins.0 * 2, item.margins.1 * 2);
+            let hint = self.children.get(n).map_or(
+                SizeHint {
+                    min: Size::zero(),
+                    preferred: Size::zero(),
+                },
+                |child| child.measure(available.saturating_sub(margins)),
+            );
+            let (preferred_main, preferred_cross) = self.main_and_cross(hint.preferred);
+            let (min_main, min_cross) = self.main_and_cross(hint.min);
+            let basis = item
+                .basis
+                .unwrap_or(preferred_main)
+                .clamp(item.min, item.max.max(item.min));
+            // can't fail: there are as many bases as items
+            bases
+                .push([(basis, preferred_cross), (min_main.max(item.min), min_cross)])
+                .unwrap_or_default();
+        }
+        bases
+    }
+
+    /// End of the line starting with the `start`-th child.
+    fn line_end(&self, start: usize, bases: &[[(u32, u32); 2]], main_length: u32) -> usize {
+        if !self.wrap {
+            return self.items.len();
+        }
+        let mut line_length = bases[start][0].0 + self.margins(start).0;
+        let mut end = start + 1;
+        while end < self.items.len() {
+            line_length += self.spacing + bases[end][0].0 + self.margins(end).0;
+            if line_length > main_length {
+                break;
+            }
+            end += 1;
+        }
+        end
+    }
+
+    /// Grow or shrink the children of a line so that it fills `main_length`.
+    fn resolve_line(&self, line: Range<usize>, bases: &[[(u32, u32); 2]], main_length: u32) -> C::Values<u32> {
+        let mut sizes = C::Values::<u32>::default();
+        let mut frozen = C::Values::<bool>::default();
+        for n in line.clone() {
+            // can't fail: a line has at most as many children as the layout
+            sizes.push(bases[n][0].0).unwrap_or_default();
+            frozen.push(false).unwrap_or_default();
+        }
+        let spacing = self.spacing * (line.len() as u32).saturating_sub(1);
+        // each iteration freezes at least one child
+        loop {
+            let used: u64 = line
+                .clone()
+                .map(|n| sizes[n - line.start] as u64 + self.margins(n).0 as u64)
+                .sum::<u64>()
+                + spacing as u64;
+            let growing = used < main_length as u64;
+            let free = (main_length as u64).abs_diff(used);
+            let factor = |n: usize| {
+                if growing {
+                    self.items[n].grow as u64
+                } else {
+                    self.items[n].shrink as u64 * bases[n][0].0 as u64
+                }
+            };
+            let total_factor: u64 = line.clone().filter(|n| !frozen[n - line.start]).map(factor).sum();
+            if free == 0 || total_factor == 0 {
+                return sizes;
+            }
+
+            // computed from the cumulated factor, so that rounding errors don't add up
+            let mut distributed_factor = 0;
+            let mut distributed = 0;
+            let mut violation = false;
+            let mut targets = C::Values::<(u32, bool)>::default();
+            for n in line.clone() {
+                let i = n - line.start;
+                if frozen[i] {
+                    targets.push((sizes[i], false)).unwrap_or_default();
+                    continue;
+                }
+                distributed_factor += factor(n);
+                let end = free * distributed_factor / total_factor;
+                let delta = (end - distributed) as u32;
+                distributed = end;
+                let target = if growing {
+                    sizes[i].saturating_add(delta)
+                } else {
+                    sizes[i].saturating_sub(delta)
+                };
+                let clamped = target.clamp(bases[n][1].0, self.items[n].max.max(bases[n][1].0));
+                violation |= clamped != target;
+                targets.push((clamped, clamped != target)).unwrap_or_default();
+            }
+            for (i, &(target, violated)) in targets.iter().enumerate() {
+                if !violation {
+                    sizes[i] = target;
+                } else if violated {
+                    // this child can't grow or shrink anymore, the free space is distributed again
+                    sizes[i] = target;
+                    frozen[i] = true;
+                }
+            }
+            if !violation {
+                return sizes;
+            }
+        }
+    }
+
+    fn set_children_bounding_box(&mut self) {
+        let (main_length, cross_length) = self.main_and_cross(self.bounding_box.size);
+        let bases = self.bases(self.bounding_box.size);
+        let mut bounding_boxes = C::Values::<Rectangle>::default();
+
+        let mut start = 0;
+        let mut cross_offset = 0;
+        while start < self.items.len() {
+            let end = self.line_end(start, &bases, main_length);
+            let sizes = self.resolve_line(start..end, &bases, main_length);
+            let line_cross = if self.wrap {
+                (start..end)
+                    .map(|n| bases[n][0].1 + self.margins(n).1)
+                    .max()
+                    .unwrap_or(0)
+                    .min(cross_length - cross_offset)
+            } else {
+                cross_length
+            };
+
+            let mut main_offset = 0;
+            for n in start..end {
+                let (main_margin, cross_margin) = self.margins(n);
+                // children that don't fit are shrinked
+                let slot_main = (sizes[n - start] + main_margin).min(main_length - main_offset);
+                let main_margin = (main_margin / 2).min(slot_main / 2);
+                let cross_margin = (cross_margin / 2).min(line_cross / 2);
+                let available_cross = line_cross - 2 * cross_margin;
+                let child_cross = bases[n][0].1.min(available_cross);
+                let (child_cross, align_offset) = match self.items[n].align.unwrap_or(self.align) {
+                    FlexAlign::Stretch => (available_cross, 0),
+                    FlexAlign::Start => (child_cross, 0),
+                    FlexAlign::Center => (child_cross, (available_cross - child_cross) / 2),
+                    FlexAlign::End => (child_cross, available_cross - child_cross),
+                };
+                let top_left =
+                    self.size_of_main_and_cross(main_offset + main_margin, cross_offset + cross_margin + align_offset);
+                // can't fail: there are as many bounding boxes as items
+                bounding_boxes
+                    .push(Rectangle::new(
+                        self.bounding_box.top_left + top_left,
+                        self.size_of_main_and_cross(slot_main - 2 * main_margin, child_cross),
+                    ))
+                    .unwrap_or_default();
+                main_offset = (main_offset + slot_main + self.spacing).min(main_length);
+            }
+
+            cross_offset = (cross_offset + line_cross + self.spacing).min(cross_length);
+            start = end;
+        }
+
+        for (n, &bounding_box) in bounding_boxes.iter().enumerate() {
+            if let Some(child) = self.children.get_mut(n) {
+                child.set_bounding_box(bounding_box);
+            }
+        }
+    }
+}
+impl<C: WidgetCollection> Widget for FlexLayout<C> {
+    type Context = <<C as WidgetCollection>::Item as Widget>::Context;
+
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        self.children.render_all(target, theme)
+    }
+    fn render_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        if self.dirty {
+            return self.render(target, theme);
+        }
+        self.children.render_all_dirty(target, theme)
+    }
+    fn on_event(&mut self, e: Event, context: &mut Self::Context) -> Option<Event> {
+        let focused = match self.focused {
+            Some(focused) => focused,
+            None => return Some(e),
+        };
+        let remaining_event = match self.children.get_mut(focused) {
+            Some(child) => child.on_event(e, context),
+            None => Some(e),
+        };
+        match remaining_event {
+            Some(Event::KeyDown(key) | Event::KeyRepeat(key)) => match self.move_focus(key) {
+                Ok(()) => None,
+                Err(()) => remaining_event, // pass event to the parent
+            },
+            _ => remaining_event,
+        }
+    }
+    fn set_bounding_box(&mut self, bounding_box: Rectangle) {
+        self.bounding_box = bounding_box;
+        self.dirty = true;
+        self.set_children_bounding_box();
+    }
+    fn get_bounding_box(&self) -> Rectangle {
+        self.bounding_box
+    }
+    /// Without wrapping, the children are measured one after the other.
+    /// With wrapping, the lines are measured one above the other.
+    fn measure(&self, available: Size) -> SizeHint {
+        let (main_length, _) = self.main_and_cross(available);
+        let bases = self.bases(available);
+        let (mut min, mut preferred) = ((0, 0), (0, 0)); // (main, cross)
+        let mut start = 0;
+        while start < self.items.len() {
+            let end = self.line_end(start, &bases, main_length);
+            let spacing = self.spacing * (end - start - 1) as u32;
+            let preferred_line = (start..end).fold((spacing, 0), |(main, cross), n| {
+                let (main_margin, cross_margin) = self.margins(n);
+                let (child_main, child_cross) = bases[n][0];
+                (main + child_main + main_margin, cross.max(child_cross + cross_margin))
+            });
+            preferred.0 = u32::max(preferred.0, preferred_line.0);
+            preferred.1 += preferred_line.1 + if start > 0 { self.spacing } else { 0 };
+            start = end;
+        }
+        if self.wrap {
+            // a line can hold a single child
+            for n in 0..self.items.len() {
+                let (main_margin, cross_margin) = self.margins(n);
+                min.0 = u32::max(min.0, bases[n][1].0 + main_margin);
+                min.1 = u32::max(min.1, bases[n][1].1 + cross_margin);
+            }
+        } else if !self.items.is_empty() {
+            min.0 = self.spacing * (self.items.len() - 1) as u32;
+            for n in 0..self.items.len() {
+                let (main_margin, cross_margin) = self.margins(n);
+                min.0 += bases[n][1].0 + main_margin;
+                min.1 = u32::max(min.1, bases[n][1].1 + cross_margin);
+            }
+        }
+        SizeHint::new(
+            self.size_of_main_and_cross(min.0, min.1),
+            self.size_of_main_and_cross(preferred.0, preferred.1),
+            available,
+        )
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        if self.dirty {
+            return Some(self.bounding_box);
+        }
+        self.children.get_dirty_area()
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+        self.children.mark_clean();
+    }
+    fn get_focus(&self) -> Option<bool> {
+        Some(self.focused.is_some())
+    }
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        self.focused
+            .and_then(|n| self.children.get(n))
+            .and_then(|child| child.get_focused_area())
+    }
+    fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
+        let from_dir = match from_dir {
+            None => return self.children.unfocus_widget(self.focused.take()),
+            Some(from_dir) => from_dir,
+        };
+        // keep the focus if we were already focused,
+        // otherwise enter the layout from the side the focus comes from
+        let from_end = matches!(from_dir, FocusFrom::Right | FocusFrom::Down);
+        self.focused = Some(self.children.focus_any(self.focused, from_dir, from_end)?);
+        Ok(())
+    }
+}
+
+/// Distance from `from` to `to` (in the direction of the arrow `key`, across it),
+/// if `to` is in that direction.
+fn direction_distance(from: Rectangle, to: Rectangle, key: KeyCode) -> Option<(u32, u32)> {
+    let (from_center, to_center) = (from.center(), to.center());
+    let (from_end, to_end) = (from.top_left + from.size, to.top_left + to.size);
+    let (gap, across) = match key {
+        KeyCode::Left => (from.top_left.x - to_end.x, from_center.y - to_center.y),
+        KeyCode::Right => (to.top_left.x - from_end.x, from_center.y - to_center.y),
+        KeyCode::Up => (from.top_left.y - to_end.y, from_center.x - to_center.x),
+        KeyCode::Down => (to.top_left.y - from_end.y, from_center.x - to_center.x),
+        _ => return None,
+    };
+    (gap >= 0).then_some((gap as u32, across.unsigned_abs()))
+}
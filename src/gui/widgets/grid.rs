@@ -1,21 +1,33 @@
-use crate::calculator::{DeviceDislay, Event, KeyCode};
-use crate::gui::{storage::WidgetCollection, Widget};
+use crate::calculator::{Color, Event, KeyCode};
+use crate::gui::theme::Theme;
+use crate::gui::{storage::WidgetCollection, FocusFrom, RenderError, SizeHint, Widget};
 use embedded_graphics::{prelude::*, primitives::Rectangle};
 
 /// A widget grid.
 /// The grid has `X` cells horizontally, and `Y` cells vertically.
 ///
 /// Its children are stored in a collection of type `C`.
+/// A child can span several cells, and cells can be left empty.
 ///
-/// The bounding box of the children is calculated when they're added.
+/// The bounding box of the children is calculated when they're added,
+/// and again when the bounding box of the grid changes.
+/// By default all the cells have the same size,
+/// see [`set_fit_content()`](Grid::set_fit_content()) to fit the cells to their content instead.
+///
+/// The focus is moved between the children with the arrow keys,
+/// skipping empty cells and children that can't be focused.
 ///
 /// See [`add_child_at()`](Grid::add_child_at()) for an example.
 #[derive(Debug, Clone, Copy)]
 pub struct Grid<const X: usize, const Y: usize, C: WidgetCollection> {
     bounding_box: Rectangle,
     grid: [[Option<usize>; Y]; X],
+    padding: [[u32; Y]; X], // padding of each child, stored at its top-left cell
     selected: (usize, usize),
+    focused: bool,
     children: C,
+    fit_content: bool,
+    dirty: bool,
 }
 impl<const X: usize, const Y: usize, C: WidgetCollection> Grid<X, Y, C> {
     /// Create a new grid filling the specified bounding box.
@@ -26,14 +38,18 @@ impl<const X: usize, const Y: usize, C: WidgetCollection> Grid<X, Y, C> {
     /// # Panics
     /// The function panics if `children` isn't empty, i.e. if `children.len() > 0`.
     pub fn new(bounding_box: Rectangle, children: C) -> Self {
-        if children.len() > 0 {
+        if !children.is_empty() {
             panic!("Can't create a grid from a non-empty collection.");
         }
         Self {
             bounding_box,
             grid: [[None; Y]; X],
+            padding: [[0; Y]; X],
             selected: (0, 0),
+            focused: false,
             children,
+            fit_content: false,
+            dirty: true,
         }
     }
     /// Add a child widget to the grid.
@@ -44,89 +60,81 @@ impl<const X: usize, const Y: usize, C: WidgetCollection> Grid<X, Y, C> {
     ///
     /// If the child is successfully added, then its id is returned,
     /// otherwise the child is returned back.
+    /// Adding a child fails if the requested cells are outside of the grid,
+    /// or if they're already used by another child.
     ///
     /// # Example
     /// ```
     /// use embedded_graphics::{prelude::*, primitives::Rectangle};
     /// use nw_gui::gui::widgets::{ColorRect, Grid};
-    /// use nw_gui::calculator::Color;
     /// use heapless::Vec;
     /// // This layout:
     /// // +----+----+----+----+----+
-    /// // |    |    |    |  GREEN  |
+    /// // |    |    |    |    A    |
     /// // +----+----+----+----+----+
-    /// // |     BLUE     |    |    |
+    /// // |      B       |    |    |
     /// // +----+----+----+----+----+
-    /// // |    |     RED      |    |
+    /// // |    |      C       |    |
     /// // +----+----+----+----+----+
     /// // can be contructed as follows:
-    /// let mut grid: Grid<5, 3, Vec<ColorRect, 3>> = Grid::new(
+    /// let mut grid: Grid<5, 3, Vec<ColorRect<()>, 3>> = Grid::new(
     ///     Rectangle::new(Point::new(0, 0), Size::new(320, 240)),
     ///     Vec::new(),
     /// );
-    /// grid.add_child_at(
-    ///     ColorRect::new(Color::GREEN, Color::WHITE, 2, Rectangle::default()),
-    ///     (0, 0),
-    ///     (2, 1),
-    ///     4,
-    /// ).unwrap();
-    /// grid.add_child_at(
-    ///     ColorRect::new(Color::BLUE, Color::WHITE, 2, Rectangle::default()),
-    ///     (0, 1),
-    ///     (3, 1),
-    ///     4,
-    /// ).unwrap();
-    /// grid.add_child_at(
-    ///     ColorRect::new(Color::RED, Color::WHITE, 2, Rectangle::default()),
-    ///     (1, 2),
-    ///     (3, 1),
-    ///     4,
-    /// ).unwrap();
+    /// grid.add_child_at(ColorRect::new(Rectangle::default()), (3, 0), (2, 1), 4).unwrap();
+    /// grid.add_child_at(ColorRect::new(Rectangle::default()), (0, 1), (3, 1), 4).unwrap();
+    /// grid.add_child_at(ColorRect::new(Rectangle::default()), (1, 2), (3, 1), 4).unwrap();
     /// ```
     pub fn add_child_at(
         &mut self,
-        mut child: C::Item,
+        child: C::Item,
         position: (usize, usize),
         size: (usize, usize),
         padding: u32,
     ) -> Result<usize, C::Item> {
-        let cell_size = (
-            self.bounding_box.size.width / X as u32,
-            self.bounding_box.size.height / Y as u32,
-        );
         if position.0 >= X || position.1 >= Y || position.0 + size.0 > X || position.1 + size.1 > Y {
             return Err(child);
         }
-
-        let mut bb = Rectangle::new(
-            Point::new(
-                position.0 as i32 * cell_size.0 as i32,
-                position.1 as i32 * cell_size.1 as i32,
-            ) + self.bounding_box.top_left,
-            Size::new(size.0 as u32 * cell_size.0, size.1 as u32 * cell_size.1),
-        );
-        let horizontal_padding = padding.clamp(0, bb.size.width / 2);
-        let vertical_padding = padding.clamp(0, bb.size.height / 2);
-        bb.top_left.x += horizontal_padding as i32;
-        bb.top_left.y += vertical_padding as i32;
-        bb.size.width -= 2 * horizontal_padding;
-        bb.size.height -= 2 * horizontal_padding;
-        child.set_bounding_box(bb);
+        for x in position.0..position.0 + size.0 {
+            for y in position.1..position.1 + size.1 {
+                if self.grid[x][y].is_some() {
+                    return Err(child);
+                }
+            }
+        }
 
         self.children.add_widget(child)?;
 
         // reserve cells in the grid
         let child_n = self.children.len() - 1;
-        for x_offset in 0..size.0 {
-            for y_offset in 0..size.1 {
+        for x in position.0..position.0 + size.0 {
+            for y in position.1..position.1 + size.1 {
                 // safe because we already checked that the requested position and size are contained in the grid
-                self.grid[x_offset + position.0][y_offset + position.1] = Some(child_n);
+                self.grid[x][y] = Some(child_n);
             }
         }
+        self.padding[position.0][position.1] = padding;
+        self.dirty = true;
+        self.set_children_bounding_box();
 
         Ok(child_n)
     }
 
+    /// Choose whether the columns and rows are sized to fit their content.
+    ///
+    /// If `fit_content` is `true`, each column (row) is as wide (high) as the preferred size
+    /// of its widest (highest) child, see [`Widget::measure()`].
+    /// Children spanning several columns (rows) aren't taken into account for these columns (rows).
+    /// The remaining space is shared between all the columns (rows),
+    /// and if there isn't enough space they're shrinked proportionally.
+    ///
+    /// If `fit_content` is `false`, all the cells have the same size.
+    pub fn set_fit_content(&mut self, fit_content: bool) {
+        self.fit_content = fit_content;
+        self.dirty = true;
+        self.set_children_bounding_box();
+    }
+
     /// Read-only acces to a child.
     pub fn get(&self, n: usize) -> Option<&C::Item> {
         self.children.get(n)
@@ -135,70 +143,289 @@ impl<const X: usize, const Y: usize, C: WidgetCollection> Grid<X, Y, C> {
     pub fn get_mut(&mut self, n: usize) -> Option<&mut C::Item> {
         self.children.get_mut(n)
     }
-}
-impl<const X: usize, const Y: usize, C: WidgetCollection> Widget for Grid<X, Y, C> {
-    type Context = <<C as WidgetCollection>::Item as Widget>::Context;
+    /// Id of the focused child, if any.
+    pub fn get_focused_child(&self) -> Option<usize> {
+        if self.focused {
+            self.grid[self.selected.0][self.selected.1]
+        } else {
+            None
+        }
+    }
 
-    fn render(&self, target: &mut DeviceDislay, focused: bool) {
-        let mut selected_child: Option<usize> = None;
+    /// Cells (position, size) used by the given child.
+    fn child_cells(&self, n: usize) -> Option<((usize, usize), (usize, usize))> {
+        let mut top_left: Option<(usize, usize)> = None;
+        let mut bottom_right = (0, 0);
+        for x in 0..X {
+            for y in 0..Y {
+                if self.grid[x][y] == Some(n) {
+                    if top_left.is_none() {
+                        top_left = Some((x, y));
+                    }
+                    bottom_right = (x, y);
+                }
+            }
+        }
+        top_left.map(|top_left| {
+            (
+                top_left,
+                (bottom_right.0 - top_left.0 + 1, bottom_right.1 - top_left.1 + 1),
+            )
+        })
+    }
+
+    /// Minimum and preferred size of the columns and rows, given the available size of the grid.
+    fn content_tracks(&self, available: Size) -> [([u32; X], [u32; Y]); 2] {
+        let mut tracks = [([0; X], [0; Y]); 2];
         for n in 0..self.children.len() {
-            let child_focused = self.grid[self.selected.0][self.selected.1]
-                .map(|selected_child| n == selected_child)
-                .unwrap_or(false);
-            if child_focused {
-                selected_child = Some(n);
-            } else {
-                self.children.get(n).unwrap().render(target, false);
+            if let (Some(child), Some((position, size))) = (self.children.get(n), self.child_cells(n)) {
+                let padding = self.padding[position.0][position.1] * 2;
+                let hint = child.measure(available.saturating_sub(Size::new(padding, padding)));
+                for (tracks, size_hint) in tracks.iter_mut().zip([hint.min, hint.preferred]) {
+                    if size.0 == 1 {
+                        tracks.0[position.0] = tracks.0[position.0].max(size_hint.width + padding);
+                    }
+                    if size.1 == 1 {
+                        tracks.1[position.1] = tracks.1[position.1].max(size_hint.height + padding);
+                    }
+                }
             }
         }
-        if let Some(selected_child) = selected_child {
-            self.children.get(selected_child).unwrap().render(target, focused);
+        tracks
+    }
+
+    /// Size of the columns and rows.
+    fn tracks(&self) -> ([u32; X], [u32; Y]) {
+        let size = self.bounding_box.size;
+        if self.fit_content {
+            let [_, preferred] = self.content_tracks(size);
+            (
+                fit_tracks(preferred.0, size.width),
+                fit_tracks(preferred.1, size.height),
+            )
+        } else {
+            ([size.width / X as u32; X], [size.height / Y as u32; Y])
         }
     }
+
+    fn cell_bounding_box(
+        &self,
+        tracks: &([u32; X], [u32; Y]),
+        position: (usize, usize),
+        size: (usize, usize),
+        padding: u32,
+    ) -> Rectangle {
+        let mut bb = Rectangle::new(
+            Point::new(
+                tracks.0[..position.0].iter().sum::<u32>() as i32,
+                tracks.1[..position.1].iter().sum::<u32>() as i32,
+            ) + self.bounding_box.top_left,
+            Size::new(
+                tracks.0[position.0..position.0 + size.0].iter().sum(),
+                tracks.1[position.1..position.1 + size.1].iter().sum(),
+            ),
+        );
+        let horizontal_padding = padding.clamp(0, bb.size.width / 2);
+        let vertical_padding = padding.clamp(0, bb.size.height / 2);
+        bb.top_left.x += horizontal_padding as i32;
+        bb.top_left.y += vertical_padding as i32;
+        bb.size.width -= 2 * horizontal_padding;
+        bb.size.height -= 2 * vertical_padding;
+        bb
+    }
+
+    fn set_children_bounding_box(&mut self) {
+        let tracks = self.tracks();
+        for n in 0..self.children.len() {
+            if let Some((position, size)) = self.child_cells(n) {
+                let bb = self.cell_bounding_box(&tracks, position, size, self.padding[position.0][position.1]);
+                if let Some(child) = self.children.get_mut(n) {
+                    child.set_bounding_box(bb);
+                }
+            }
+        }
+    }
+
+    /// Try to focus the child at the given cell, unfocusing the previously focused child on success.
+    fn focus_cell(&mut self, cell: (usize, usize), from_dir: FocusFrom) -> Result<(), ()> {
+        let n = self.grid[cell.0][cell.1].ok_or(())?;
+        self.children.focus_widget(n, self.get_focused_child(), from_dir)?;
+        self.selected = cell;
+        self.focused = true;
+        Ok(())
+    }
+
+    /// Move the focus from the focused child in the given direction.
+    ///
+    /// Every row (or column) spanned by the focused child is searched,
+    /// starting with the one of the selected cell and then the closest ones to it.
+    fn move_focus(&mut self, offset: (isize, isize), from_dir: FocusFrom) -> Result<(), ()> {
+        let current = self.grid[self.selected.0][self.selected.1];
+        let (position, size) = current
+            .and_then(|n| self.child_cells(n))
+            .unwrap_or((self.selected, (1, 1)));
+        // the cells are searched along `offset`, across the span of the child
+        let (span, selected) = if offset.0 != 0 {
+            (position.1..position.1 + size.1, self.selected.1)
+        } else {
+            (position.0..position.0 + size.0, self.selected.0)
+        };
+        let mut cell = (self.selected.0 as isize, self.selected.1 as isize);
+        loop {
+            cell = (cell.0 + offset.0, cell.1 + offset.1);
+            if cell.0 < 0 || cell.0 >= X as isize || cell.1 < 0 || cell.1 >= Y as isize {
+                return Err(());
+            }
+            for distance in 0..span.len() {
+                let sides = [
+                    selected.checked_sub(distance),
+                    Some(selected + distance).filter(|_| distance > 0),
+                ];
+                for across in sides.into_iter().flatten().filter(|across| span.contains(across)) {
+                    let cell = if offset.0 != 0 {
+                        (cell.0 as usize, across)
+                    } else {
+                        (across, cell.1 as usize)
+                    };
+                    // skip empty cells, the other cells of the current child, and unfocusable children
+                    if self.grid[cell.0][cell.1].is_some()
+                        && self.grid[cell.0][cell.1] != current
+                        && self.focus_cell(cell, from_dir).is_ok()
+                    {
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+}
+impl<const X: usize, const Y: usize, C: WidgetCollection> Widget for Grid<X, Y, C> {
+    type Context = <<C as WidgetCollection>::Item as Widget>::Context;
+
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        self.children.render_all(target, theme)
+    }
+    fn render_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        if self.dirty {
+            return self.render(target, theme);
+        }
+        self.children.render_all_dirty(target, theme)
+    }
     fn on_event(&mut self, e: Event, context: &mut Self::Context) -> Option<Event> {
-        // TODO: correctly manage focus :)
+        if !self.focused {
+            return Some(e);
+        }
         let mut remaining_event: Option<Event> = Some(e);
         if let Some(selected_index) = self.grid[self.selected.0][self.selected.1] {
             if let Some(selected_child) = self.children.get_mut(selected_index) {
-                remaining_event = selected_child.on_event(remaining_event.unwrap(), context);
+                remaining_event = selected_child.on_event(e, context);
             }
         }
-        if let Some(e) = remaining_event {
-            let mut focus_offset: (isize, isize) = (0, 0);
-            match e {
-                Event::KeyDown(KeyCode::Left) => focus_offset.0 = -1,
-                Event::KeyDown(KeyCode::Right) => focus_offset.0 = 1,
-                Event::KeyDown(KeyCode::Up) => focus_offset.1 = -1,
-                Event::KeyDown(KeyCode::Down) => focus_offset.1 = 1,
-                _ => return Some(e),
-            }
-            if focus_offset.0 != 0 || focus_offset.1 != 0 {
-                let mut new_selected = (self.selected.0 as isize, self.selected.1 as isize);
-                loop {
-                    new_selected = (new_selected.0 + focus_offset.0, new_selected.1 + focus_offset.1);
-                    if new_selected.0 < 0
-                        || new_selected.0 >= X as isize
-                        || new_selected.1 < 0
-                        || new_selected.1 >= Y as isize
-                    {
-                        // if the user moves outside the grid, they may want to move out of the grid,
-                        // so let the parent widget handle the event
-                        return Some(e);
-                    }
-                    if self.grid[new_selected.0 as usize][new_selected.1 as usize].is_some() {
-                        self.selected = (new_selected.0 as usize, new_selected.1 as usize);
-                        return None;
-                    }
+        match remaining_event {
+            Some(Event::KeyDown(key) | Event::KeyRepeat(key)) => {
+                let (offset, from_dir) = match key {
+                    KeyCode::Left => ((-1, 0), FocusFrom::Right),
+                    KeyCode::Right => ((1, 0), FocusFrom::Left),
+                    KeyCode::Up => ((0, -1), FocusFrom::Down),
+                    KeyCode::Down => ((0, 1), FocusFrom::Up),
+                    _ => return remaining_event,
+                };
+                match self.move_focus(offset, from_dir) {
+                    Ok(()) => None,
+                    // if the user moves outside the grid, they may want to move out of the grid,
+                    // so let the parent widget handle the event
+                    Err(()) => remaining_event,
                 }
             }
+            _ => remaining_event,
         }
-        None
     }
     fn set_bounding_box(&mut self, bounding_box: Rectangle) {
-        // TODO: resize dynamically all the children
         self.bounding_box = bounding_box;
+        self.dirty = true;
+        self.set_children_bounding_box();
     }
     fn get_bounding_box(&self) -> Rectangle {
         self.bounding_box
     }
+    fn measure(&self, available: Size) -> SizeHint {
+        let [min, preferred] = self.content_tracks(available);
+        let total = |tracks: ([u32; X], [u32; Y])| {
+            if self.fit_content {
+                Size::new(tracks.0.iter().sum(), tracks.1.iter().sum())
+            } else {
+                // all the cells have the size of the largest one
+                let max = |tracks: &[u32]| tracks.iter().copied().max().unwrap_or(0);
+                Size::new(max(&tracks.0) * X as u32, max(&tracks.1) * Y as u32)
+            }
+        };
+        SizeHint::new(total(min), total(preferred), available)
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        if self.dirty {
+            return Some(self.bounding_box);
+        }
+        self.children.get_dirty_area()
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+        self.children.mark_clean();
+    }
+    fn get_focus(&self) -> Option<bool> {
+        Some(self.focused)
+    }
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        self.get_focused_child()
+            .and_then(|n| self.children.get(n))
+            .and_then(|child| child.get_focused_area())
+    }
+    fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
+        let from_dir = match from_dir {
+            None => {
+                let focused_child = self.get_focused_child();
+                self.focused = false;
+                return self.children.unfocus_widget(focused_child);
+            }
+            Some(from_dir) => from_dir,
+        };
+        // if we were already focused, keep that focus
+        if self.focused && self.focus_cell(self.selected, from_dir).is_ok() {
+            return Ok(());
+        }
+        // otherwise enter the grid from the side the focus comes from
+        for i in 0..X * Y {
+            let cell = match from_dir {
+                FocusFrom::Left => (i / Y, i % Y),
+                FocusFrom::Right => (X - 1 - i / Y, i % Y),
+                FocusFrom::Up => (i % X, i / X),
+                FocusFrom::Down => (i % X, Y - 1 - i / X),
+            };
+            if self.focus_cell(cell, from_dir).is_ok() {
+                return Ok(());
+            }
+        }
+        Err(())
+    }
+}
+
+/// Share `length` between tracks of the given content sizes.
+fn fit_tracks<const L: usize>(mut tracks: [u32; L], length: u32) -> [u32; L] {
+    let content_length: u32 = tracks.iter().sum();
+    if content_length <= length {
+        let extra = (length - content_length) / L as u32;
+        for track in tracks.iter_mut() {
+            *track += extra;
+        }
+    } else {
+        for track in tracks.iter_mut() {
+            *track = (*track as u64 * length as u64 / content_length as u64) as u32;
+        }
+    }
+    tracks
 }
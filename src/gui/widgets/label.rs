@@ -0,0 +1,233 @@
+use core::marker::PhantomData;
+
+use crate::calculator::{Color, Event};
+use crate::gui::text::Lines;
+use crate::gui::theme::Theme;
+use crate::gui::{text_columns, text_size, FocusFrom, RenderError, SizeHint, Widget, NORMAL_FONT, TEXT_PADDING};
+
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Baseline, Text},
+};
+use heapless::String;
+
+/// Text shown at the end of a [`Label`] too long to fit.
+const ELLIPSIS: &str = "...";
+
+/// Horizontal alignment of the text of a [`Label`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HorizontalAlign {
+    Left,
+    Center,
+    Right,
+}
+
+/// Vertical alignment of the text of a [`Label`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerticalAlign {
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// A text that can't be edited nor focused.
+///
+/// The text is on one line, unless it is wrapped with [`set_wrap()`](Label::set_wrap()).
+/// Text that doesn't fit in the label is truncated, and ends with an ellipsis.
+///
+/// `T` is the type of [`Widget::Context`]: a label doesn't use its context.
+///
+/// # Example
+/// ```
+/// use embedded_graphics::{prelude::*, primitives::Rectangle};
+/// use nw_gui::gui::widgets::{HorizontalAlign, Label, VerticalAlign};
+/// use heapless::String;
+/// let mut label: Label<(), 16> = Label::new(
+///     String::from("Mods:"),
+///     Rectangle::new(Point::new(0, 0), Size::new(320, 21)),
+/// );
+/// label.set_alignment(HorizontalAlign::Right, VerticalAlign::Middle);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Label<T, const N: usize> {
+    text: String<N>,
+    bounding_box: Rectangle,
+    horizontal_align: HorizontalAlign,
+    vertical_align: VerticalAlign,
+    wrap: bool,
+    dirty: bool,
+    _context: PhantomData<T>,
+}
+impl<T, const N: usize> Label<T, N> {
+    /// Create a new label on one line, aligned to the left and in the middle.
+    pub fn new(text: String<N>, bounding_box: Rectangle) -> Self {
+        Self {
+            text,
+            bounding_box,
+            horizontal_align: HorizontalAlign::Left,
+            vertical_align: VerticalAlign::Middle,
+            wrap: false,
+            dirty: true,
+            _context: PhantomData,
+        }
+    }
+
+    /// Text shown.
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+    /// Modify the text shown.
+    pub fn set_text(&mut self, text: String<N>) {
+        if text != self.text {
+            self.text = text;
+            self.dirty = true;
+        }
+    }
+    /// Change the alignment of the text inside the label.
+    pub fn set_alignment(&mut self, horizontal: HorizontalAlign, vertical: VerticalAlign) {
+        self.horizontal_align = horizontal;
+        self.vertical_align = vertical;
+        self.dirty = true;
+    }
+    /// Whether the text is wrapped on several lines.
+    pub fn is_wrapped(&self) -> bool {
+        self.wrap
+    }
+    /// Wrap the text on several lines, or show it on one line.
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+        self.dirty = true;
+    }
+
+    /// Area in which the text is drawn.
+    fn text_area(&self) -> Rectangle {
+        let padding = Size::new(TEXT_PADDING, TEXT_PADDING);
+        Rectangle::new(
+            self.bounding_box.top_left + padding,
+            self.bounding_box.size.saturating_sub(padding * 2),
+        )
+    }
+    /// Draw a line of text, ending it with an ellipsis if it is truncated or if `ellipsis` is true.
+    fn draw_line<D>(&self, target: &mut D, theme: &Theme, line: &str, y: i32, ellipsis: bool) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let area = self.text_area();
+        let columns = text_columns(area.size.width);
+        let characters = line.chars().count();
+        let (shown, ellipsis) = if ellipsis || characters > columns {
+            let kept = columns.saturating_sub(ELLIPSIS.len()).min(characters);
+            let end = line
+                .char_indices()
+                .nth(kept)
+                .map_or(line.len(), |(position, _)| position);
+            (&line[..end], ELLIPSIS)
+        } else {
+            (line, "")
+        };
+        let width = text_size(shown).width + text_size(ellipsis).width;
+        let x = match self.horizontal_align {
+            HorizontalAlign::Left => area.top_left.x,
+            HorizontalAlign::Center => area.center().x - (width / 2) as i32,
+            HorizontalAlign::Right => area.top_left.x + area.size.width as i32 - width as i32,
+        };
+        let style = MonoTextStyle::new(&NORMAL_FONT, theme.foreground);
+        // the ellipsis follows the text
+        Text::with_baseline(shown, Point::new(x, y), style, Baseline::Top)
+            .draw(target)
+            .and_then(|next| Text::with_baseline(ellipsis, next, style, Baseline::Top).draw(target))
+            .map(|_| ())
+            .map_err(|_| RenderError::DrawTarget)
+    }
+}
+impl<T, const N: usize> Widget for Label<T, N> {
+    type Context = T;
+
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let mut result = self
+            .bounding_box
+            .into_styled(PrimitiveStyle::with_fill(theme.background))
+            .draw(target)
+            .map_err(|_| RenderError::DrawTarget);
+        let area = self.text_area();
+        let line_height = NORMAL_FONT.character_size.height;
+        let visible_lines = ((area.size.height / line_height) as usize).max(1);
+        let lines = if self.wrap {
+            Lines::new(&self.text, text_columns(area.size.width)).count()
+        } else {
+            1
+        };
+        let shown_lines = lines.min(visible_lines);
+        let height = (shown_lines as u32 * line_height) as i32;
+        let top = match self.vertical_align {
+            VerticalAlign::Top => area.top_left.y,
+            VerticalAlign::Middle => area.center().y - height / 2,
+            VerticalAlign::Bottom => area.top_left.y + area.size.height as i32 - height,
+        };
+        let mut target = target.clipped(&self.bounding_box);
+        if self.wrap {
+            let wrapped = Lines::new(&self.text, text_columns(area.size.width)).take(shown_lines);
+            for (n, (start, end)) in wrapped.enumerate() {
+                // the last line shown ends with an ellipsis if some lines are hidden
+                result = result.and(self.draw_line(
+                    &mut target,
+                    theme,
+                    &self.text[start..end],
+                    top + (n as u32 * line_height) as i32,
+                    n + 1 == shown_lines && shown_lines < lines,
+                ));
+            }
+        } else {
+            // a label on one line shows its first line
+            let line = self.text.split('\n').next().unwrap_or_default();
+            result = result.and(self.draw_line(&mut target, theme, line, top, line.len() < self.text.len()));
+        }
+        result
+    }
+    fn on_event(&mut self, e: Event, _context: &mut Self::Context) -> Option<Event> {
+        Some(e)
+    }
+    fn set_bounding_box(&mut self, bounding_box: Rectangle) {
+        self.bounding_box = bounding_box;
+        self.dirty = true;
+    }
+    fn get_bounding_box(&self) -> Rectangle {
+        self.bounding_box
+    }
+    /// A label prefers to show all its text, on the available width if it is wrapped.
+    fn measure(&self, available: Size) -> SizeHint {
+        let padding = Size::new(2 * TEXT_PADDING, 2 * TEXT_PADDING);
+        let text = if self.wrap {
+            let columns = text_columns(available.width.saturating_sub(padding.width));
+            let lines = Lines::new(&self.text, columns).count() as u32;
+            Size::new(
+                available.width.saturating_sub(padding.width),
+                lines * NORMAL_FONT.character_size.height,
+            )
+        } else {
+            text_size(self.text.split('\n').next().unwrap_or_default())
+        };
+        SizeHint::new(
+            Size::new(text_size(ELLIPSIS).width, NORMAL_FONT.character_size.height) + padding,
+            text + padding,
+            available,
+        )
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        self.dirty.then_some(self.bounding_box)
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+    fn get_focus(&self) -> Option<bool> {
+        None
+    }
+    fn set_focus(&mut self, _: Option<FocusFrom>) -> Result<(), ()> {
+        Err(())
+    }
+}
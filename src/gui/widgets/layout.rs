@@ -1,6 +1,6 @@
-use crate::calculator::{Event, KeyCode};
+use crate::calculator::{Color, Event, KeyCode};
 use crate::gui::theme::Theme;
-use crate::gui::{FocusFrom, Widget};
+use crate::gui::{union_area, FocusFrom, RenderError, SizeHint, Widget};
 
 use embedded_graphics::{prelude::*, primitives::Rectangle};
 
@@ -12,9 +12,10 @@ pub struct SplitLayout<T: Widget, U: Widget> {
     second: Option<U>,
     focused: Option<Side>,
     bounding_box: Rectangle,
-    split_factor: f32, // 0 = the second widget has all the bounding box, 1 = the first widget has all the bounding box
+    split: SplitMode,
     first_margins: (u32, u32), // horizontal, vertical
     second_margins: (u32, u32),
+    dirty: bool, // the whole layout must be rendered again
 }
 impl<S, T: Widget<Context = S>, U: Widget<Context = S>> Widget for SplitLayout<T, U> {
     type Context = S;
@@ -24,8 +25,34 @@ impl<S, T: Widget<Context = S>, U: Widget<Context = S>> Widget for SplitLayout<T
     }
     fn set_bounding_box(&mut self, bounding_box: Rectangle) {
         self.bounding_box = bounding_box;
+        self.dirty = true;
         self.set_children_bounding_box();
     }
+    /// The children are measured side by side, including their margins.
+    fn measure(&self, available: Size) -> SizeHint {
+        let first = self
+            .first
+            .as_ref()
+            .map(|first| measure_with_margins(first, available, self.first_margins));
+        let second = self
+            .second
+            .as_ref()
+            .map(|second| measure_with_margins(second, available, self.second_margins));
+        let zero = SizeHint {
+            min: Size::zero(),
+            preferred: Size::zero(),
+        };
+        let (first, second) = (first.unwrap_or(zero), second.unwrap_or(zero));
+        let combine = |a: Size, b: Size| match self.direction {
+            SplitDirection::Horizontal => Size::new(a.width + b.width, a.height.max(b.height)),
+            SplitDirection::Vertical => Size::new(a.width.max(b.width), a.height + b.height),
+        };
+        SizeHint::new(
+            combine(first.min, second.min),
+            combine(first.preferred, second.preferred),
+            available,
+        )
+    }
     fn on_event(&mut self, e: Event, context: &mut Self::Context) -> Option<Event> {
         let remaining_event = match self.focused {
             Some(Side::First) => {
@@ -46,7 +73,7 @@ impl<S, T: Widget<Context = S>, U: Widget<Context = S>> Widget for SplitLayout<T
         };
         match remaining_event {
             None => None,
-            Some(Event::KeyDown(key)) => {
+            Some(e @ (Event::KeyDown(key) | Event::KeyRepeat(key))) => {
                 let mut to_focus: Option<(Side, FocusFrom)> = None;
                 match (key, self.direction, self.focused) {
                     (KeyCode::Left, SplitDirection::Horizontal, Some(Side::Second) | None) => {
@@ -67,52 +94,106 @@ impl<S, T: Widget<Context = S>, U: Widget<Context = S>> Widget for SplitLayout<T
                     let should_attempt_other_side = self.focused.is_none();
                     match self.focus_child(next_focus, from_dir, should_attempt_other_side) {
                         Ok(()) => None,
-                        Err(()) => Some(Event::KeyDown(key)),
+                        Err(()) => Some(e),
                     }
                 } else {
-                    Some(Event::KeyDown(key))
+                    Some(e)
                 }
             }
             Some(ev) => Some(ev),
         }
     }
-    fn render(&self, target: &mut crate::calculator::DeviceDislay, theme: &Theme) {
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let render_first = |target: &mut D| self.first.as_ref().map_or(Ok(()), |first| first.render(target, theme));
+        let render_second = |target: &mut D| {
+            self.second
+                .as_ref()
+                .map_or(Ok(()), |second| second.render(target, theme))
+        };
+        // a failing child doesn't prevent the other one from being rendered
         match self.focused {
             Some(Side::First) => {
-                if let Some(ref second) = self.second {
-                    second.render(target, theme);
-                }
-                if let Some(ref first) = self.first {
-                    first.render(target, theme);
-                }
+                let second = render_second(target);
+                render_first(target).and(second)
             }
             Some(Side::Second) | None => {
-                if let Some(ref first) = self.first {
-                    first.render(target, theme);
-                }
-                if let Some(ref second) = self.second {
-                    second.render(target, theme);
-                }
+                let first = render_first(target);
+                render_second(target).and(first)
+            }
+        }
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        if self.dirty {
+            return Some(self.bounding_box);
+        }
+        union_area(
+            self.first.as_ref().and_then(|first| first.get_dirty_area()),
+            self.second.as_ref().and_then(|second| second.get_dirty_area()),
+        )
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+        if let Some(ref mut first) = self.first {
+            first.mark_clean();
+        }
+        if let Some(ref mut second) = self.second {
+            second.mark_clean();
+        }
+    }
+    fn render_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        if self.dirty {
+            return self.render(target, theme);
+        }
+        let render_first = |target: &mut D| {
+            self.first
+                .as_ref()
+                .map_or(Ok(()), |first| first.render_dirty(target, theme))
+        };
+        let render_second = |target: &mut D| {
+            self.second
+                .as_ref()
+                .map_or(Ok(()), |second| second.render_dirty(target, theme))
+        };
+        // same order as `render()`
+        match self.focused {
+            Some(Side::First) => {
+                let second = render_second(target);
+                render_first(target).and(second)
+            }
+            Some(Side::Second) | None => {
+                let first = render_first(target);
+                render_second(target).and(first)
             }
         }
     }
     fn get_focus(&self) -> Option<bool> {
         Some(self.focused.is_some())
     }
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        match self.focused {
+            Some(Side::First) => self.first.as_ref().and_then(|first| first.get_focused_area()),
+            Some(Side::Second) => self.second.as_ref().and_then(|second| second.get_focused_area()),
+            None => None,
+        }
+    }
     fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
         if from_dir.is_none() {
             self.focused = None;
             let mut is_ok = false; // Ok(()) as long as one of the side is Ok
             if let Some(ref mut first) = self.first {
-                match first.set_focus(None) {
-                    Ok(()) => is_ok = true,
-                    Err(()) => (),
+                if let Ok(()) = first.set_focus(None) {
+                    is_ok = true;
                 }
             }
             if let Some(ref mut second) = self.second {
-                match second.set_focus(None) {
-                    Ok(()) => is_ok = true,
-                    Err(()) => (),
+                if let Ok(()) = second.set_focus(None) {
+                    is_ok = true;
                 }
             }
             if is_ok {
@@ -157,12 +238,25 @@ impl<T: Widget, U: Widget> SplitLayout<T, U> {
             first: None,
             second: None,
             focused: None,
-            split_factor: split_factor.clamp(0.0, 1.0),
+            split: SplitMode::Factor(split_factor.clamp(0.0, 1.0)),
             first_margins: (0, 0),
             second_margins: (0, 0),
+            dirty: true,
         }
     }
 
+    /// Change how the bounding box is split between the two widgets.
+    ///
+    /// The bounding boxes of the widgets are calculated again.
+    pub fn set_split_mode(&mut self, split: SplitMode) {
+        self.split = match split {
+            SplitMode::Factor(split_factor) => SplitMode::Factor(split_factor.clamp(0.0, 1.0)),
+            split => split,
+        };
+        self.dirty = true;
+        self.set_children_bounding_box();
+    }
+
     /// Attach the first widget to the `SplitLayout`.
     ///
     /// `margins` are (horizontal, vertical). The margins are applied at each side of the widget.
@@ -170,6 +264,7 @@ impl<T: Widget, U: Widget> SplitLayout<T, U> {
     /// The bounding box of the widget is automatically set to fit according to the parametres of the split.
     pub fn attach_first(&mut self, widget: T, margins: (u32, u32)) {
         self.first = Some(widget);
+        self.dirty = true;
         self.first_margins = margins;
         self.set_children_bounding_box();
     }
@@ -180,6 +275,7 @@ impl<T: Widget, U: Widget> SplitLayout<T, U> {
     /// The bounding box of the widget is automatically set to fit according to the parametres of the split.
     pub fn attach_second(&mut self, widget: U, margins: (u32, u32)) {
         self.second = Some(widget);
+        self.dirty = true;
         self.second_margins = margins;
         self.set_children_bounding_box();
     }
@@ -249,17 +345,39 @@ impl<T: Widget, U: Widget> SplitLayout<T, U> {
         Ok(())
     }
 
+    /// Length of the first side in the split direction, margins included.
+    fn first_length(&self) -> u32 {
+        let length = match self.direction {
+            SplitDirection::Horizontal => self.bounding_box.size.width,
+            SplitDirection::Vertical => self.bounding_box.size.height,
+        };
+        let main_length = |hint: SizeHint| match self.direction {
+            SplitDirection::Horizontal => hint.preferred.width,
+            SplitDirection::Vertical => hint.preferred.height,
+        };
+        match self.split {
+            SplitMode::Factor(split_factor) => (length as f32 * split_factor) as u32,
+            SplitMode::FitFirst => self.first.as_ref().map_or(0, |first| {
+                main_length(measure_with_margins(first, self.bounding_box.size, self.first_margins)).min(length)
+            }),
+            SplitMode::FitSecond => self.second.as_ref().map_or(length, |second| {
+                length
+                    - main_length(measure_with_margins(
+                        second,
+                        self.bounding_box.size,
+                        self.second_margins,
+                    ))
+                    .min(length)
+            }),
+        }
+    }
+
     fn set_children_bounding_box(&mut self) {
+        let first_length = self.first_length();
         if let Some(ref mut first) = self.first {
             let size = match self.direction {
-                SplitDirection::Horizontal => Size::new(
-                    (self.bounding_box.size.width as f32 * self.split_factor) as u32,
-                    self.bounding_box.size.height,
-                ),
-                SplitDirection::Vertical => Size::new(
-                    self.bounding_box.size.width,
-                    (self.bounding_box.size.height as f32 * self.split_factor) as u32,
-                ),
+                SplitDirection::Horizontal => Size::new(first_length, self.bounding_box.size.height),
+                SplitDirection::Vertical => Size::new(self.bounding_box.size.width, first_length),
             };
             let mut margins = Size::new(self.first_margins.0, self.first_margins.1);
             if margins.width * 2 > size.width {
@@ -272,12 +390,8 @@ impl<T: Widget, U: Widget> SplitLayout<T, U> {
         }
         if let Some(ref mut second) = self.second {
             let other_size = match self.direction {
-                SplitDirection::Horizontal => {
-                    Size::new((self.bounding_box.size.width as f32 * self.split_factor) as u32, 0)
-                }
-                SplitDirection::Vertical => {
-                    Size::new(0, (self.bounding_box.size.height as f32 * self.split_factor) as u32)
-                }
+                SplitDirection::Horizontal => Size::new(first_length, 0),
+                SplitDirection::Vertical => Size::new(0, first_length),
             };
             let top_left = self.bounding_box.top_left + other_size;
             let size = self.bounding_box.size - other_size;
@@ -293,7 +407,29 @@ impl<T: Widget, U: Widget> SplitLayout<T, U> {
     }
 }
 
-/// Direction in which a [`SplitLayout`] is split.
+/// How the bounding box of a [`SplitLayout`] is split between the two widgets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SplitMode {
+    /// 0 = the second widget has all the bounding box, 1 = the first widget has all the bounding box.
+    Factor(f32),
+    /// The first widget takes its preferred size, and the second one the rest.
+    FitFirst,
+    /// The second widget takes its preferred size, and the first one the rest.
+    FitSecond,
+}
+
+/// Measure a widget surrounded by margins (horizontal, vertical) applied at each side.
+pub(crate) fn measure_with_margins<W: Widget>(widget: &W, available: Size, margins: (u32, u32)) -> SizeHint {
+    let margins = Size::new(margins.0 * 2, margins.1 * 2);
+    let hint = widget.measure(available.saturating_sub(margins));
+    SizeHint {
+        min: hint.min + margins,
+        preferred: hint.preferred + margins,
+    }
+}
+
+/// Direction in which a [`SplitLayout`] is split,
+/// or in which the children of a [`LinearLayout`](super::LinearLayout) are placed.
 #[derive(Debug, Clone, Copy)]
 pub enum SplitDirection {
     /// Left | Right
@@ -0,0 +1,347 @@
+use super::{layout::measure_with_margins, SplitDirection};
+use crate::calculator::{Color, Event, KeyCode};
+use crate::gui::theme::Theme;
+use crate::gui::{
+    storage::{ValueCollection, WidgetCollection},
+    FocusFrom, RenderError, SizeHint, Widget,
+};
+
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+
+/// A row or a column of widgets.
+///
+/// Its children are stored in a collection of type `C`,
+/// and are placed one after the other in the layout direction,
+/// separated by `spacing` pixels.
+/// A child takes either a fixed size, its preferred size,
+/// or a share of the space left by the other children.
+/// See [`ChildSize`].
+///
+/// The focus is moved between the children with the arrow keys matching the layout direction,
+/// skipping children that can't be focused.
+///
+/// # Example
+/// ```
+/// use embedded_graphics::{prelude::*, primitives::Rectangle};
+/// use nw_gui::gui::widgets::{Button, ChildSize, LinearLayout, SplitDirection};
+/// use nw_gui::gui::Widget;
+/// use heapless::{String, Vec};
+/// // A title bar of 20 pixels, and two buttons sharing the rest of the screen,
+/// // the second one being twice as high as the first one.
+/// let mut column: LinearLayout<Vec<Button<()>, 3>> = LinearLayout::new(
+///     Rectangle::new(Point::new(0, 0), Size::new(320, 240)),
+///     SplitDirection::Vertical,
+///     2,
+///     Vec::new(),
+/// );
+/// column.add_child(Button::new(String::from("Title"), Rectangle::default(), |_| ()), ChildSize::Fixed(20), (0, 0)).unwrap();
+/// column.add_child(Button::new(String::from("One"), Rectangle::default(), |_| ()), ChildSize::Weight(1), (0, 0)).unwrap();
+/// column.add_child(Button::new(String::from("Two"), Rectangle::default(), |_| ()), ChildSize::Weight(2), (0, 0)).unwrap();
+/// assert_eq!(column.get(2).unwrap().get_bounding_box().size, Size::new(320, 144));
+/// ```
+#[derive(Debug, Clone)]
+pub struct LinearLayout<C: WidgetCollection> {
+    direction: SplitDirection,
+    children: C,
+    layouts: C::Values<ChildLayout>,
+    spacing: u32,
+    focused: Option<usize>,
+    bounding_box: Rectangle,
+    dirty: bool, // the whole layout must be rendered again
+}
+
+/// Size of a child of a [`LinearLayout`] in the layout direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChildSize {
+    /// The child takes this number of pixels.
+    Fixed(u32),
+    /// The child takes its preferred size, see [`Widget::measure()`].
+    Fit,
+    /// The child takes a share of the space left by the other children and the spacing,
+    /// proportional to its weight.
+    Weight(u32),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ChildLayout {
+    size: ChildSize,
+    margins: (u32, u32), // horizontal, vertical
+}
+
+impl<C: WidgetCollection> LinearLayout<C> {
+    /// Create a new `LinearLayout` filling the specified bounding box.
+    ///
+    /// `children` is a the collection used to store the children of the layout.
+    /// It should be empty.
+    ///
+    /// # Panics
+    /// The function panics if `children` isn't empty.
+    pub fn new(bounding_box: Rectangle, direction: SplitDirection, spacing: u32, children: C) -> Self {
+        if !children.is_empty() {
+            panic!("Can't create a linear layout from a non-empty collection.");
+        }
+        Self {
+            direction,
+            children,
+            layouts: Default::default(),
+            spacing,
+            focused: None,
+            bounding_box,
+            dirty: true,
+        }
+    }
+
+    /// Add a child widget at the end of the layout.
+    ///
+    /// `margins` are (horizontal, vertical). The margins are applied at each side of the widget.
+    ///
+    /// If the child is successfully added, then its id is returned,
+    /// otherwise the child is returned back.
+    /// The bounding boxes of all the children are calculated again.
+    pub fn add_child(&mut self, child: C::Item, size: ChildSize, margins: (u32, u32)) -> Result<usize, C::Item> {
+        self.children.add_widget(child)?;
+        // can't fail: the layouts have the capacity of the children
+        self.layouts.push(ChildLayout { size, margins }).unwrap_or_default();
+        self.dirty = true;
+        self.set_children_bounding_box();
+        Ok(self.children.len() - 1)
+    }
+
+    /// Change the size of a child.
+    ///
+    /// The size is returned back if there is no such child.
+    pub fn set_child_size(&mut self, n: usize, size: ChildSize) -> Result<(), ChildSize> {
+        self.layouts.get_mut(n).ok_or(size)?.size = size;
+        self.dirty = true;
+        self.set_children_bounding_box();
+        Ok(())
+    }
+    /// Change the spacing between the children.
+    pub fn set_spacing(&mut self, spacing: u32) {
+        self.spacing = spacing;
+        self.dirty = true;
+        self.set_children_bounding_box();
+    }
+
+    /// Number of children.
+    pub fn len(&self) -> usize {
+        self.children.len()
+    }
+    /// Whether the layout has no children.
+    pub fn is_empty(&self) -> bool {
+        self.children.is_empty()
+    }
+    /// Read-only acces to a child.
+    pub fn get(&self, n: usize) -> Option<&C::Item> {
+        self.children.get(n)
+    }
+    /// Mutable acces to a child.
+    pub fn get_mut(&mut self, n: usize) -> Option<&mut C::Item> {
+        self.children.get_mut(n)
+    }
+    /// Id of the focused child, if any.
+    pub fn get_focused_child(&self) -> Option<usize> {
+        self.focused
+    }
+
+    /// Try to focus the given child, unfocusing the previously focused child on success.
+    fn focus_child(&mut self, n: usize, from_dir: FocusFrom) -> Result<(), ()> {
+        self.children.focus_widget(n, self.focused, from_dir)?;
+        self.focused = Some(n);
+        Ok(())
+    }
+
+    /// Length of `size` in the layout direction, and across it.
+    fn main_and_cross_length(&self, size: Size) -> (u32, u32) {
+        match self.direction {
+            SplitDirection::Horizontal => (size.width, size.height),
+            SplitDirection::Vertical => (size.height, size.width),
+        }
+    }
+
+    fn set_children_bounding_box(&mut self) {
+        let (main_length, cross_length) = self.main_and_cross_length(self.bounding_box.size);
+        let spacing = self.spacing * (self.layouts.len() as u32).saturating_sub(1);
+        let mut fixed_length = 0;
+        let mut total_weight = 0;
+        // length of the children that don't depend on the free length
+        let mut lengths = C::Values::<Option<u32>>::default();
+        for (n, layout) in self.layouts.iter().enumerate() {
+            let length = match layout.size {
+                ChildSize::Fixed(length) => Some(length),
+                ChildSize::Fit => self.children.get(n).map(|child| {
+                    let hint = measure_with_margins(child, self.bounding_box.size, layout.margins);
+                    self.main_and_cross_length(hint.preferred).0
+                }),
+                ChildSize::Weight(weight) => {
+                    total_weight += weight;
+                    None
+                }
+            };
+            fixed_length += length.unwrap_or(0);
+            // can't fail: there are as many lengths as layouts
+            lengths.push(length).unwrap_or_default();
+        }
+        let free_length = main_length.saturating_sub(fixed_length + spacing);
+
+        let mut offset = 0;
+        let mut distributed_weight = 0;
+        let mut distributed_length = 0;
+        for (n, layout) in self.layouts.iter().enumerate() {
+            let length = match (layout.size, lengths[n]) {
+                (_, Some(length)) => length,
+                (ChildSize::Weight(weight), None) => {
+                    // computed from the cumulated weight, so that rounding errors don't add up
+                    distributed_weight += weight;
+                    let end = if total_weight == 0 {
+                        0
+                    } else {
+                        (free_length as u64 * distributed_weight as u64 / total_weight as u64) as u32
+                    };
+                    let length = end - distributed_length;
+                    distributed_length = end;
+                    length
+                }
+                (_, None) => 0,
+            };
+            // children that don't fit are shrinked
+            let length = length.min(main_length.saturating_sub(offset));
+
+            let (top_left, size) = match self.direction {
+                SplitDirection::Horizontal => (Point::new(offset as i32, 0), Size::new(length, cross_length)),
+                SplitDirection::Vertical => (Point::new(0, offset as i32), Size::new(cross_length, length)),
+            };
+            let mut margins = Size::new(layout.margins.0, layout.margins.1);
+            if margins.width * 2 > size.width {
+                margins.width = size.width / 2;
+            }
+            if margins.height * 2 > size.height {
+                margins.height = size.height / 2;
+            }
+            if let Some(child) = self.children.get_mut(n) {
+                child.set_bounding_box(Rectangle::new(
+                    self.bounding_box.top_left + top_left + margins,
+                    size - margins * 2,
+                ));
+            }
+            offset = (offset + length + self.spacing).min(main_length);
+        }
+    }
+}
+impl<C: WidgetCollection> Widget for LinearLayout<C> {
+    type Context = <<C as WidgetCollection>::Item as Widget>::Context;
+
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        self.children.render_all(target, theme)
+    }
+    fn render_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        if self.dirty {
+            return self.render(target, theme);
+        }
+        self.children.render_all_dirty(target, theme)
+    }
+    fn on_event(&mut self, e: Event, context: &mut Self::Context) -> Option<Event> {
+        let focused = match self.focused {
+            Some(focused) => focused,
+            None => return Some(e),
+        };
+        let remaining_event = match self.children.get_mut(focused) {
+            Some(child) => child.on_event(e, context),
+            None => Some(e),
+        };
+        match remaining_event {
+            Some(Event::KeyDown(key) | Event::KeyRepeat(key)) => {
+                let (forward, from_dir) = match (key, self.direction) {
+                    (KeyCode::Left, SplitDirection::Horizontal) => (false, FocusFrom::Right),
+                    (KeyCode::Right, SplitDirection::Horizontal) => (true, FocusFrom::Left),
+                    (KeyCode::Up, SplitDirection::Vertical) => (false, FocusFrom::Down),
+                    (KeyCode::Down, SplitDirection::Vertical) => (true, FocusFrom::Up),
+                    _ => return remaining_event, // pass event to the parent
+                };
+                let mut next = focused;
+                loop {
+                    next = match (forward, next) {
+                        (false, 0) => return remaining_event,
+                        (false, n) => n - 1,
+                        (true, n) if n + 1 >= self.children.len() => return remaining_event,
+                        (true, n) => n + 1,
+                    };
+                    if self.focus_child(next, from_dir).is_ok() {
+                        return None;
+                    }
+                }
+            }
+            _ => remaining_event,
+        }
+    }
+    fn set_bounding_box(&mut self, bounding_box: Rectangle) {
+        self.bounding_box = bounding_box;
+        self.dirty = true;
+        self.set_children_bounding_box();
+    }
+    fn get_bounding_box(&self) -> Rectangle {
+        self.bounding_box
+    }
+    /// The children are measured one after the other, including their margins and the spacing.
+    fn measure(&self, available: Size) -> SizeHint {
+        let spacing = self.spacing * (self.layouts.len() as u32).saturating_sub(1);
+        let (mut min, mut preferred) = ((spacing, 0), (spacing, 0)); // (main, cross)
+        for (n, layout) in self.layouts.iter().enumerate() {
+            let hint = match self.children.get(n) {
+                Some(child) => measure_with_margins(child, available, layout.margins),
+                None => continue,
+            };
+            for (total, size) in [(&mut min, hint.min), (&mut preferred, hint.preferred)] {
+                let (main, cross) = self.main_and_cross_length(size);
+                total.0 += match layout.size {
+                    ChildSize::Fixed(length) => length,
+                    ChildSize::Fit | ChildSize::Weight(_) => main,
+                };
+                total.1 = total.1.max(cross);
+            }
+        }
+        let to_size = |(main, cross): (u32, u32)| match self.direction {
+            SplitDirection::Horizontal => Size::new(main, cross),
+            SplitDirection::Vertical => Size::new(cross, main),
+        };
+        SizeHint::new(to_size(min), to_size(preferred), available)
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        if self.dirty {
+            return Some(self.bounding_box);
+        }
+        self.children.get_dirty_area()
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+        self.children.mark_clean();
+    }
+    fn get_focus(&self) -> Option<bool> {
+        Some(self.focused.is_some())
+    }
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        self.focused
+            .and_then(|n| self.children.get(n))
+            .and_then(|child| child.get_focused_area())
+    }
+    fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
+        let from_dir = match from_dir {
+            None => return self.children.unfocus_widget(self.focused.take()),
+            Some(from_dir) => from_dir,
+        };
+        // keep the focus if we were already focused,
+        // otherwise enter the layout from the side the focus comes from
+        let from_end = matches!(
+            (from_dir, self.direction),
+            (FocusFrom::Right, SplitDirection::Horizontal) | (FocusFrom::Down, SplitDirection::Vertical)
+        );
+        self.focused = Some(self.children.focus_any(self.focused, from_dir, from_end)?);
+        Ok(())
+    }
+}
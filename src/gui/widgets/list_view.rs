@@ -0,0 +1,333 @@
+use core::fmt::{self, Write};
+
+use crate::calculator::{Color, Event, KeyCode};
+use crate::gui::theme::Theme;
+use crate::gui::{union_area, FocusFrom, IndexCallback, RenderError, SizeHint, Widget, NORMAL_FONT, TEXT_PADDING};
+
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    prelude::*,
+    primitives::{PrimitiveStyle, PrimitiveStyleBuilder, Rectangle},
+    text::{Baseline, Text},
+};
+use heapless::String;
+
+/// Default height of the rows of a [`ListView`]: one line of text and its padding.
+pub const ROW_HEIGHT: u32 = NORMAL_FONT.character_size.height + 2 * TEXT_PADDING;
+/// Maximum length of the text of a row, in bytes. Longer rows are truncated.
+pub const ROW_TEXT_CAPACITY: usize = 64;
+
+/// Rows shown by a [`ListView`].
+///
+/// The rows are only asked for when they're rendered,
+/// so they can be generated on the fly instead of being stored.
+pub trait RowProvider {
+    /// Number of rows.
+    fn len(&self) -> usize;
+    /// Whether there are no rows.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Write the text of the `n`-th row.
+    ///
+    /// `n` is always less than [`len()`](RowProvider::len()).
+    fn write_row(&self, n: usize, text: &mut dyn Write) -> fmt::Result;
+}
+/// A slice of strings can be used as rows.
+impl<S: AsRef<str>> RowProvider for &[S] {
+    fn len(&self) -> usize {
+        (**self).len()
+    }
+    fn write_row(&self, n: usize, text: &mut dyn Write) -> fmt::Result {
+        text.write_str(self[n].as_ref())
+    }
+}
+/// An array of strings can be used as rows.
+impl<S: AsRef<str>, const N: usize> RowProvider for [S; N] {
+    fn len(&self) -> usize {
+        N
+    }
+    fn write_row(&self, n: usize, text: &mut dyn Write) -> fmt::Result {
+        text.write_str(self[n].as_ref())
+    }
+}
+
+/// Text of a row, keeping what fits in [`ROW_TEXT_CAPACITY`] bytes.
+struct RowText(String<ROW_TEXT_CAPACITY>);
+impl Write for RowText {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            self.0.push(c).map_err(|_| fmt::Error)?;
+        }
+        Ok(())
+    }
+}
+
+/// A list of rows of text, one of them being selected.
+///
+/// The rows come from a [`RowProvider`], and only the visible rows are rendered,
+/// so the memory used doesn't depend on the number of rows.
+///
+/// The selection is moved with Up and Down, going around at the ends of the list
+/// unless the key is being repeated.
+/// Ok or Exe calls the callback with the index of the selected row.
+///
+/// `T` is the type of [`Widget::Context`].
+///
+/// # Example
+/// ```
+/// use embedded_graphics::{prelude::*, primitives::Rectangle};
+/// use nw_gui::gui::widgets::{ListView, RowProvider};
+/// use core::fmt;
+/// // 1000 rows, none of them being stored
+/// struct Squares;
+/// impl RowProvider for Squares {
+///     fn len(&self) -> usize {
+///         1000
+///     }
+///     fn write_row(&self, n: usize, text: &mut dyn fmt::Write) -> fmt::Result {
+///         write!(text, "{}² = {}", n, n * n)
+///     }
+/// }
+/// let list: ListView<Squares, Option<usize>> = ListView::new(
+///     Squares,
+///     Rectangle::new(Point::new(0, 0), Size::new(320, 240)),
+///     |activated, n| *activated = Some(n),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct ListView<P: RowProvider, T> {
+    provider: P,
+    bounding_box: Rectangle,
+    row_height: u32,
+    selected: usize,
+    first_visible: usize,
+    focused: bool,
+    on_activated: IndexCallback<T>,
+    dirty_area: Option<Rectangle>,
+}
+impl<P: RowProvider, T> ListView<P, T> {
+    /// Create a new list showing the rows of `provider`, the first one being selected.
+    ///
+    /// `on_activated` is called with the index of the selected row when it is activated.
+    pub fn new(provider: P, bounding_box: Rectangle, on_activated: fn(&mut T, usize)) -> Self {
+        Self {
+            provider,
+            bounding_box,
+            row_height: ROW_HEIGHT,
+            selected: 0,
+            first_visible: 0,
+            focused: false,
+            on_activated: IndexCallback(on_activated),
+            dirty_area: Some(bounding_box),
+        }
+    }
+
+    /// Read access to the rows.
+    pub fn get_provider(&self) -> &P {
+        &self.provider
+    }
+    /// Mutable access to the rows.
+    ///
+    /// [`rows_changed()`](ListView::rows_changed()) must be called once the rows are changed.
+    pub fn get_provider_mut(&mut self) -> &mut P {
+        &mut self.provider
+    }
+    /// Render the whole list again after its rows changed.
+    ///
+    /// The selection is moved to the last row if the selected row doesn't exist anymore.
+    pub fn rows_changed(&mut self) {
+        self.dirty_area = Some(self.bounding_box);
+        self.select(self.selected);
+    }
+
+    /// Index of the selected row.
+    ///
+    /// The index is 0 if the list is empty.
+    pub fn get_selected(&self) -> usize {
+        self.selected
+    }
+    /// Select a row, and scroll to show it.
+    ///
+    /// The index is clamped to the last row.
+    pub fn set_selected(&mut self, n: usize) {
+        self.select(n);
+    }
+    /// Index of the first row shown.
+    pub fn get_first_visible(&self) -> usize {
+        self.first_visible
+    }
+    /// Change the height of the rows, in pixels.
+    pub fn set_row_height(&mut self, row_height: u32) {
+        self.row_height = row_height.max(1);
+        self.dirty_area = Some(self.bounding_box);
+        self.select(self.selected);
+    }
+
+    /// Number of rows fully visible, at least one.
+    fn visible_rows(&self) -> usize {
+        ((self.bounding_box.size.height / self.row_height) as usize).max(1)
+    }
+    /// Area of the `n`-th row on the screen, if it's visible.
+    fn row_area(&self, n: usize) -> Option<Rectangle> {
+        let position = n.checked_sub(self.first_visible)? as u32 * self.row_height;
+        (position < self.bounding_box.size.height).then(|| {
+            Rectangle::new(
+                self.bounding_box.top_left + Point::new(0, position as i32),
+                Size::new(self.bounding_box.size.width, self.row_height),
+            )
+        })
+    }
+
+    /// Select the `n`-th row and scroll to show it, marking what changed as dirty.
+    fn select(&mut self, n: usize) {
+        let n = n.min(self.provider.len().saturating_sub(1));
+        let first_visible = if n < self.first_visible {
+            n
+        } else if n >= self.first_visible + self.visible_rows() {
+            n + 1 - self.visible_rows()
+        } else {
+            self.first_visible
+        };
+        // don't leave empty space after the last row if the list became shorter
+        let first_visible = first_visible.min(self.provider.len().saturating_sub(self.visible_rows()));
+        if first_visible != self.first_visible {
+            self.first_visible = first_visible;
+            self.dirty_area = Some(self.bounding_box);
+        } else if n != self.selected {
+            self.dirty_area = union_area(self.dirty_area, self.row_area(self.selected));
+            self.dirty_area = union_area(self.dirty_area, self.row_area(n));
+        }
+        self.selected = n;
+    }
+
+    /// Render the visible rows intersecting `area`.
+    fn render_rows<D>(&self, target: &mut D, theme: &Theme, area: Rectangle) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let mut target = target.clipped(&area.intersection(&self.bounding_box));
+        let mut result = Ok(());
+        let last_row = (self.first_visible + self.visible_rows() + 1).min(self.provider.len());
+        for n in self.first_visible..last_row {
+            let row_area = match self.row_area(n) {
+                Some(row_area) if row_area.intersection(&area).size != Size::zero() => row_area,
+                _ => continue,
+            };
+            let (background, foreground) = if n == self.selected {
+                (
+                    if self.focused { theme.focused } else { theme.foreground },
+                    theme.background,
+                )
+            } else {
+                (theme.background, theme.foreground)
+            };
+            result = result.and(
+                row_area
+                    .into_styled(PrimitiveStyle::with_fill(background))
+                    .draw(&mut target)
+                    .map_err(|_| RenderError::DrawTarget),
+            );
+            let mut text = RowText(String::new());
+            // a row too long is truncated
+            self.provider.write_row(n, &mut text).unwrap_or_default();
+            result = result.and(
+                Text::with_baseline(
+                    &text.0,
+                    Point::new(row_area.top_left.x + TEXT_PADDING as i32, row_area.center().y),
+                    MonoTextStyle::new(&NORMAL_FONT, foreground),
+                    Baseline::Middle,
+                )
+                .draw(&mut target)
+                .map(|_| ())
+                .map_err(|_| RenderError::DrawTarget),
+            );
+        }
+        result
+    }
+}
+impl<P: RowProvider, T> Widget for ListView<P, T> {
+    type Context = T;
+
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let style = PrimitiveStyleBuilder::new().fill_color(theme.background).build();
+        let background = self
+            .bounding_box
+            .into_styled(style)
+            .draw(target)
+            .map_err(|_| RenderError::DrawTarget);
+        self.render_rows(target, theme, self.bounding_box).and(background)
+    }
+    fn render_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        match self.dirty_area {
+            Some(area) if area == self.bounding_box => self.render(target, theme),
+            Some(area) => self.render_rows(target, theme, area),
+            None => Ok(()),
+        }
+    }
+    fn on_event(&mut self, e: Event, context: &mut T) -> Option<Event> {
+        let len = self.provider.len();
+        if len == 0 {
+            return Some(e);
+        }
+        match e {
+            Event::KeyDown(KeyCode::Up) => self.select(self.selected.checked_sub(1).unwrap_or(len - 1)),
+            Event::KeyDown(KeyCode::Down) => self.select(if self.selected + 1 < len { self.selected + 1 } else { 0 }),
+            Event::KeyRepeat(KeyCode::Up) => self.select(self.selected.saturating_sub(1)),
+            Event::KeyRepeat(KeyCode::Down) => self.select(self.selected + 1),
+            Event::KeyDown(KeyCode::Ok) | Event::KeyDown(KeyCode::Exe) => (self.on_activated.0)(context, self.selected),
+            _ => return Some(e),
+        }
+        None
+    }
+    fn set_bounding_box(&mut self, bounding_box: Rectangle) {
+        self.bounding_box = bounding_box;
+        self.dirty_area = Some(bounding_box);
+        self.select(self.selected);
+    }
+    fn get_bounding_box(&self) -> Rectangle {
+        self.bounding_box
+    }
+    /// A list prefers to show all its rows, and needs one row unless it's empty.
+    fn measure(&self, available: Size) -> SizeHint {
+        let height = (self.provider.len() as u64 * self.row_height as u64).min(u32::MAX as u64) as u32;
+        SizeHint::new(
+            Size::new(0, self.row_height.min(height)),
+            Size::new(available.width, height),
+            available,
+        )
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        self.dirty_area
+    }
+    fn mark_clean(&mut self) {
+        self.dirty_area = None;
+    }
+    fn get_focus(&self) -> Option<bool> {
+        Some(self.focused)
+    }
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        if self.focused {
+            self.row_area(self.selected)
+        } else {
+            None
+        }
+    }
+    fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
+        if from_dir.is_some() && self.provider.is_empty() {
+            return Err(());
+        }
+        let focused = from_dir.is_some();
+        if focused != self.focused {
+            self.dirty_area = union_area(self.dirty_area, self.row_area(self.selected));
+        }
+        self.focused = focused;
+        Ok(())
+    }
+}
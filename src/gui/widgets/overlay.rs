@@ -0,0 +1,220 @@
+use crate::calculator::{Color, Event};
+use crate::gui::theme::Theme;
+use crate::gui::{union_area, FocusFrom, RenderError, SizeHint, Widget};
+
+use either::Either;
+use embedded_graphics::{prelude::*, primitives::Rectangle};
+use heapless::Vec;
+
+/// A widget shown above the others by an [`OverlayStack`], until it is dismissed.
+pub trait Modal: Widget {
+    /// Whether the widget has been dismissed, and should be removed from the screen.
+    fn is_dismissed(&self) -> bool;
+}
+/// Layers of different types can be stacked with [`Either`].
+impl<T, L: Modal<Context = T>, R: Modal<Context = T>> Modal for Either<L, R> {
+    fn is_dismissed(&self) -> bool {
+        match self {
+            Either::Left(layer) => layer.is_dismissed(),
+            Either::Right(layer) => layer.is_dismissed(),
+        }
+    }
+}
+
+/// A widget with layers shown above it, e.g. [`Dialog`](super::Dialog)s.
+///
+/// The top layer captures all the events until it is dismissed,
+/// then it is removed and the events go to the layer below, or to the base widget.
+/// What was below the layer gets [`Event::LayerDismissed`] right away,
+/// f.e. so that a [`TextBox`](super::TextBox) inserts the text chosen in a [`Toolbox`](super::Toolbox).
+/// The base widget keeps its focus while it is covered.
+///
+/// Layers are centered above the base widget, with their preferred size.
+///
+/// `N` is the maximum number of layers.
+///
+/// # Example
+/// ```
+/// use embedded_graphics::{prelude::*, primitives::Rectangle};
+/// use nw_gui::calculator::{Event, KeyCode};
+/// use nw_gui::gui::widgets::{ColorRect, Dialog, OverlayStack};
+/// use nw_gui::gui::Widget;
+/// use heapless::String;
+/// let mut screen: OverlayStack<ColorRect<bool>, Dialog<bool>> =
+///     OverlayStack::new(ColorRect::new(Rectangle::new(Point::new(0, 0), Size::new(320, 240))));
+/// let mut dialog = Dialog::new(String::from("Delete"), String::from("Delete this file?"));
+/// dialog.add_button(String::from("Yes"), |delete| *delete = true).unwrap();
+/// dialog.add_button(String::from("No"), |_| ()).unwrap();
+/// screen.push(dialog).unwrap();
+///
+/// let mut delete = false;
+/// screen.on_event(Event::KeyDown(KeyCode::Ok), &mut delete);
+/// assert!(delete);
+/// assert!(screen.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct OverlayStack<W: Widget, O: Modal<Context = W::Context>, const N: usize = 4> {
+    base: W,
+    layers: Vec<O, N>,
+    dirty: bool, // the whole stack must be rendered again
+}
+impl<W: Widget, O: Modal<Context = W::Context>, const N: usize> OverlayStack<W, O, N> {
+    /// Create a new stack without any layer, taking the bounding box of `base`.
+    pub fn new(base: W) -> Self {
+        Self {
+            base,
+            layers: Vec::new(),
+            dirty: true,
+        }
+    }
+
+    /// Show a layer above the others, and focus it.
+    ///
+    /// The layer is returned back if there are too many layers.
+    pub fn push(&mut self, mut layer: O) -> Result<(), O> {
+        if self.layers.is_full() {
+            return Err(layer);
+        }
+        layer.set_bounding_box(self.layer_bounding_box(&layer));
+        layer.set_focus(Some(FocusFrom::Up)).unwrap_or_default();
+        self.layers.push(layer)
+    }
+    /// Remove the top layer, and return it.
+    ///
+    /// What was below the layer is rendered again.
+    pub fn pop(&mut self) -> Option<O> {
+        let layer = self.layers.pop()?;
+        self.dirty = true;
+        Some(layer)
+    }
+
+    /// Number of layers.
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+    /// Whether there is no layer above the base widget.
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+    /// Read access to the base widget.
+    pub fn get_base(&self) -> &W {
+        &self.base
+    }
+    /// Mutable access to the base widget.
+    pub fn get_base_mut(&mut self) -> &mut W {
+        &mut self.base
+    }
+    /// Read access to the top layer, if any.
+    pub fn get_top(&self) -> Option<&O> {
+        self.layers.last()
+    }
+    /// Mutable access to the top layer, if any.
+    pub fn get_top_mut(&mut self) -> Option<&mut O> {
+        self.layers.last_mut()
+    }
+
+    /// Bounding box of `layer` centered above the base widget, and at most as large.
+    fn layer_bounding_box(&self, layer: &O) -> Rectangle {
+        let bounding_box = self.base.get_bounding_box();
+        let size = layer
+            .measure(bounding_box.size)
+            .preferred
+            .component_min(bounding_box.size);
+        Rectangle::new(bounding_box.top_left + (bounding_box.size - size) / 2, size)
+    }
+}
+impl<W: Widget, O: Modal<Context = W::Context>, const N: usize> Widget for OverlayStack<W, O, N> {
+    type Context = W::Context;
+
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        // layers are rendered from the bottom to the top
+        let mut result = self.base.render(target, theme);
+        for layer in self.layers.iter() {
+            result = result.and(layer.render(target, theme));
+        }
+        result
+    }
+    fn render_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        if self.dirty {
+            return self.render(target, theme);
+        }
+        let mut result = self.base.render_dirty(target, theme);
+        // a layer is rendered again completely if what's below it has been drawn over it
+        let mut below = self.base.get_dirty_area();
+        for layer in self.layers.iter() {
+            let covered = below.is_some_and(|below| below.intersection(&layer.get_bounding_box()).size != Size::zero());
+            result = result.and(if covered {
+                layer.render(target, theme)
+            } else {
+                layer.render_dirty(target, theme)
+            });
+            below = union_area(below, layer.get_dirty_area());
+        }
+        result
+    }
+    /// Events are captured by the top layer, if any.
+    fn on_event(&mut self, e: Event, context: &mut Self::Context) -> Option<Event> {
+        let top = match self.layers.last_mut() {
+            Some(top) => top,
+            None => return self.base.on_event(e, context),
+        };
+        top.on_event(e, context);
+        if top.is_dismissed() {
+            self.pop();
+            self.on_event(Event::LayerDismissed, context);
+        }
+        None
+    }
+    fn set_bounding_box(&mut self, bounding_box: Rectangle) {
+        self.base.set_bounding_box(bounding_box);
+        self.dirty = true;
+        for n in 0..self.layers.len() {
+            let layer_bounding_box = self.layer_bounding_box(&self.layers[n]);
+            self.layers[n].set_bounding_box(layer_bounding_box);
+        }
+    }
+    fn get_bounding_box(&self) -> Rectangle {
+        self.base.get_bounding_box()
+    }
+    /// The layers are centered above the base widget, so only the base widget is measured.
+    fn measure(&self, available: Size) -> SizeHint {
+        self.base.measure(available)
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        if self.dirty {
+            return Some(self.base.get_bounding_box());
+        }
+        self.layers.iter().fold(self.base.get_dirty_area(), |area, layer| {
+            union_area(area, layer.get_dirty_area())
+        })
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+        self.base.mark_clean();
+        for layer in self.layers.iter_mut() {
+            layer.mark_clean();
+        }
+    }
+    fn get_focus(&self) -> Option<bool> {
+        match self.layers.last() {
+            Some(top) => top.get_focus(),
+            None => self.base.get_focus(),
+        }
+    }
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        match self.layers.last() {
+            Some(top) => top.get_focused_area(),
+            None => self.base.get_focused_area(),
+        }
+    }
+    /// The focus is given to the base widget, the layers keep their own focus.
+    fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
+        self.base.set_focus(from_dir)
+    }
+}
@@ -1,8 +1,8 @@
 use core::marker::PhantomData;
 
-use crate::calculator::{DeviceDislay, Event};
+use crate::calculator::{Color, Event};
 use crate::gui::theme::Theme;
-use crate::gui::{FocusFrom, Widget};
+use crate::gui::{FocusFrom, RenderError, Widget};
 use embedded_graphics::{
     prelude::*,
     primitives::{PrimitiveStyleBuilder, Rectangle},
@@ -14,12 +14,14 @@ use embedded_graphics::{
 #[derive(Debug, Clone, Copy)]
 pub struct ColorRect<T> {
     bounding_box: Rectangle,
+    dirty: bool,
     _context: PhantomData<T>,
 }
 impl<T> ColorRect<T> {
     pub fn new(bounding_box: Rectangle) -> Self {
         Self {
             bounding_box,
+            dirty: true,
             _context: PhantomData,
         }
     }
@@ -30,20 +32,33 @@ impl<T> Widget for ColorRect<T> {
     fn on_event(&mut self, e: Event, _context: &mut Self::Context) -> Option<Event> {
         Some(e)
     }
-    fn render(&self, target: &mut DeviceDislay, theme: &Theme) {
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
         let style = PrimitiveStyleBuilder::new()
             .fill_color(theme.background)
             .stroke_color(theme.foreground)
             .stroke_width(theme.rect_border)
             .build();
-        self.bounding_box.into_styled(style).draw(target).unwrap();
+        self.bounding_box
+            .into_styled(style)
+            .draw(target)
+            .map_err(|_| RenderError::DrawTarget)
     }
     fn set_bounding_box(&mut self, bounding_box: Rectangle) {
         self.bounding_box = bounding_box;
+        self.dirty = true;
     }
     fn get_bounding_box(&self) -> Rectangle {
         self.bounding_box
     }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        self.dirty.then_some(self.bounding_box)
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
     fn get_focus(&self) -> Option<bool> {
         None
     }
@@ -0,0 +1,347 @@
+use crate::calculator::{Color, Event, KeyCode};
+use crate::gui::theme::Theme;
+use crate::gui::{FocusFrom, RenderError, SizeHint, Widget};
+
+use embedded_graphics::{
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+};
+
+/// Width of the scrollbars, in pixels.
+pub const SCROLLBAR_WIDTH: u32 = 4;
+/// Number of pixels scrolled when an arrow key isn't used by the content.
+pub const SCROLL_STEP: u32 = 20;
+
+/// A viewport showing a part of a child larger than itself.
+///
+/// The child is laid out with the content size, and moved when scrolling.
+/// Only the part of the child inside the viewport is rendered.
+///
+/// When the focus moves inside the child, the view scrolls to keep the focused widget visible,
+/// see [`Widget::get_focused_area()`].
+/// The arrow keys which aren't used by the child scroll the view by [`SCROLL_STEP`] pixels.
+///
+/// A scrollbar is drawn on the right (bottom) when the content is higher (wider) than the view.
+///
+/// # Example
+/// ```
+/// use embedded_graphics::{prelude::*, primitives::Rectangle};
+/// use nw_gui::gui::widgets::{ColorRect, ScrollView};
+/// use nw_gui::gui::Widget;
+/// // a rectangle 3 times higher than the screen
+/// let mut view: ScrollView<ColorRect<()>> = ScrollView::new(
+///     Rectangle::new(Point::new(0, 0), Size::new(320, 240)),
+///     Size::new(316, 720),
+///     ColorRect::new(Rectangle::default()),
+/// );
+/// view.scroll_to(Point::new(0, 100));
+/// assert_eq!(
+///     view.get_child().get_bounding_box(),
+///     Rectangle::new(Point::new(0, -100), Size::new(316, 720)),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScrollView<W: Widget> {
+    child: W,
+    bounding_box: Rectangle,
+    content_size: Size,
+    offset: Point, // position of the viewport in the content
+    dirty: bool,   // the whole view must be rendered again
+}
+impl<W: Widget> ScrollView<W> {
+    /// Create a new `ScrollView` filling the specified bounding box.
+    ///
+    /// The child is laid out with `content_size`, or with the size of the viewport if it's larger.
+    pub fn new(bounding_box: Rectangle, content_size: Size, child: W) -> Self {
+        let mut view = Self {
+            child,
+            bounding_box,
+            content_size,
+            offset: Point::zero(),
+            dirty: true,
+        };
+        view.set_child_bounding_box();
+        view
+    }
+
+    /// Read access to the child.
+    pub fn get_child(&self) -> &W {
+        &self.child
+    }
+    /// Mutable access to the child.
+    pub fn get_child_mut(&mut self) -> &mut W {
+        &mut self.child
+    }
+
+    /// Change the size of the content.
+    ///
+    /// The scroll position is kept if possible.
+    pub fn set_content_size(&mut self, content_size: Size) {
+        self.content_size = content_size;
+        self.dirty = true;
+        self.set_child_bounding_box();
+    }
+    /// Position of the viewport in the content.
+    pub fn get_offset(&self) -> Point {
+        self.offset
+    }
+    /// Scroll so that the top-left corner of the viewport is at `offset` in the content.
+    ///
+    /// The offset is clamped so that the viewport stays inside the content.
+    pub fn scroll_to(&mut self, offset: Point) {
+        let max_offset = self.max_offset();
+        let offset = offset.component_max(Point::zero()).component_min(max_offset);
+        if offset != self.offset {
+            self.offset = offset;
+            self.dirty = true;
+            self.set_child_bounding_box();
+        }
+    }
+    /// Scroll as little as possible so that `area` (in screen coordinates) is visible.
+    ///
+    /// If `area` is larger than the viewport, its top-left corner is made visible.
+    pub fn scroll_to_visible(&mut self, area: Rectangle) {
+        let viewport = self.get_viewport();
+        // area in the content coordinates
+        let top_left = area.top_left - self.child.get_bounding_box().top_left;
+        let bottom_right = top_left + area.size;
+        let scroll = |offset: i32, start: i32, end: i32, length: u32| {
+            if start < offset {
+                start
+            } else if end > offset + length as i32 {
+                (end - length as i32).min(start)
+            } else {
+                offset
+            }
+        };
+        self.scroll_to(Point::new(
+            scroll(self.offset.x, top_left.x, bottom_right.x, viewport.size.width),
+            scroll(self.offset.y, top_left.y, bottom_right.y, viewport.size.height),
+        ));
+    }
+
+    /// Part of the bounding box showing the content, i.e. without the scrollbars.
+    pub fn get_viewport(&self) -> Rectangle {
+        let (vertical, horizontal) = self.scrollbars();
+        let scrollbars = Size::new(
+            if vertical { SCROLLBAR_WIDTH } else { 0 },
+            if horizontal { SCROLLBAR_WIDTH } else { 0 },
+        );
+        Rectangle::new(
+            self.bounding_box.top_left,
+            self.bounding_box.size.saturating_sub(scrollbars),
+        )
+    }
+
+    /// Whether the (vertical, horizontal) scrollbars are shown.
+    fn scrollbars(&self) -> (bool, bool) {
+        let size = self.bounding_box.size;
+        let vertical = self.content_size.height > size.height;
+        let horizontal =
+            self.content_size.width > size.width.saturating_sub(if vertical { SCROLLBAR_WIDTH } else { 0 });
+        // the horizontal scrollbar can hide the end of the content
+        let vertical =
+            vertical || (horizontal && self.content_size.height > size.height.saturating_sub(SCROLLBAR_WIDTH));
+        (vertical, horizontal)
+    }
+    /// Size of the child: the content size, or the viewport size if it's larger.
+    fn child_size(&self) -> Size {
+        self.content_size.component_max(self.get_viewport().size)
+    }
+    fn max_offset(&self) -> Point {
+        let max = self.child_size() - self.get_viewport().size;
+        Point::new(max.width as i32, max.height as i32)
+    }
+
+    fn set_child_bounding_box(&mut self) {
+        // the content may have become smaller
+        self.offset = self.offset.component_min(self.max_offset());
+        self.child.set_bounding_box(Rectangle::new(
+            self.bounding_box.top_left - self.offset,
+            self.child_size(),
+        ));
+    }
+
+    /// Draw the scrollbars.
+    fn render_scrollbars<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let (vertical, horizontal) = self.scrollbars();
+        let viewport = self.get_viewport();
+        let child_size = self.child_size();
+        let thumb_color = if self.child.get_focus() == Some(true) {
+            theme.focused
+        } else {
+            theme.foreground
+        };
+        let mut result = Ok(());
+        // (shown, track, viewport length, content length, offset, whether the scrollbar is vertical)
+        let scrollbars = [
+            (
+                vertical,
+                Rectangle::new(
+                    viewport.top_left + Point::new(viewport.size.width as i32, 0),
+                    Size::new(SCROLLBAR_WIDTH, viewport.size.height),
+                ),
+                viewport.size.height,
+                child_size.height,
+                self.offset.y,
+                true,
+            ),
+            (
+                horizontal,
+                Rectangle::new(
+                    viewport.top_left + Point::new(0, viewport.size.height as i32),
+                    Size::new(viewport.size.width, SCROLLBAR_WIDTH),
+                ),
+                viewport.size.width,
+                child_size.width,
+                self.offset.x,
+                false,
+            ),
+        ];
+        for (shown, track, viewport_length, content_length, offset, is_vertical) in scrollbars {
+            if !shown {
+                continue;
+            }
+            let track_length = if is_vertical {
+                track.size.height
+            } else {
+                track.size.width
+            };
+            let thumb_length = (track_length as u64 * viewport_length as u64 / content_length.max(1) as u64) as u32;
+            let thumb_length = thumb_length.clamp(SCROLLBAR_WIDTH.min(track_length), track_length);
+            let max_offset = content_length - viewport_length;
+            let thumb_offset = if max_offset == 0 {
+                0
+            } else {
+                ((track_length - thumb_length) as u64 * offset as u64 / max_offset as u64) as i32
+            };
+            let thumb = if is_vertical {
+                Rectangle::new(
+                    track.top_left + Point::new(0, thumb_offset),
+                    Size::new(SCROLLBAR_WIDTH, thumb_length),
+                )
+            } else {
+                Rectangle::new(
+                    track.top_left + Point::new(thumb_offset, 0),
+                    Size::new(thumb_length, SCROLLBAR_WIDTH),
+                )
+            };
+            result = result.and(
+                track
+                    .into_styled(PrimitiveStyle::with_fill(theme.background))
+                    .draw(target)
+                    .map_err(|_| RenderError::DrawTarget),
+            );
+            result = result.and(
+                thumb
+                    .into_styled(PrimitiveStyle::with_fill(thumb_color))
+                    .draw(target)
+                    .map_err(|_| RenderError::DrawTarget),
+            );
+        }
+        result
+    }
+
+    /// Scroll to the focused widget if it changed.
+    fn follow_focus(&mut self, previous_focused_area: Option<Rectangle>) {
+        // compared in the content coordinates, as the content may have been scrolled
+        let to_content =
+            |area: Rectangle, content: Rectangle| Rectangle::new(area.top_left - content.top_left, area.size);
+        let content = self.child.get_bounding_box();
+        if let Some(focused_area) = self.child.get_focused_area() {
+            if previous_focused_area.map(|area| to_content(area, content)) != Some(to_content(focused_area, content)) {
+                self.scroll_to_visible(focused_area);
+            }
+        }
+    }
+}
+impl<W: Widget> Widget for ScrollView<W> {
+    type Context = W::Context;
+
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let child = self.child.render(&mut target.clipped(&self.get_viewport()), theme);
+        // a failing child doesn't prevent the scrollbars from being rendered
+        let scrollbars = self.render_scrollbars(target, theme);
+        child.and(scrollbars)
+    }
+    fn render_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        if self.dirty {
+            return self.render(target, theme);
+        }
+        // the scrollbars only change when scrolling or when the focus changes, which marks the view as dirty
+        self.child
+            .render_dirty(&mut target.clipped(&self.get_viewport()), theme)
+    }
+    fn on_event(&mut self, e: Event, context: &mut Self::Context) -> Option<Event> {
+        let focused_area = self.child.get_focused_area();
+        let remaining_event = self.child.on_event(e, context);
+        self.follow_focus(focused_area);
+        match remaining_event {
+            Some(Event::KeyDown(key) | Event::KeyRepeat(key)) => {
+                let step = SCROLL_STEP as i32;
+                let offset = match key {
+                    KeyCode::Left => Point::new(-step, 0),
+                    KeyCode::Right => Point::new(step, 0),
+                    KeyCode::Up => Point::new(0, -step),
+                    KeyCode::Down => Point::new(0, step),
+                    _ => return remaining_event,
+                };
+                let previous_offset = self.offset;
+                self.scroll_to(self.offset + offset);
+                if self.offset == previous_offset {
+                    // the end of the content is reached, let the parent handle the event
+                    remaining_event
+                } else {
+                    None
+                }
+            }
+            _ => remaining_event,
+        }
+    }
+    fn set_bounding_box(&mut self, bounding_box: Rectangle) {
+        self.bounding_box = bounding_box;
+        self.dirty = true;
+        self.set_child_bounding_box();
+    }
+    fn get_bounding_box(&self) -> Rectangle {
+        self.bounding_box
+    }
+    /// A scroll view prefers to show all its content.
+    fn measure(&self, available: Size) -> SizeHint {
+        SizeHint::new(Size::zero(), self.content_size, available)
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        if self.dirty {
+            return Some(self.bounding_box);
+        }
+        let area = self.child.get_dirty_area()?.intersection(&self.get_viewport());
+        (area.size.width > 0 && area.size.height > 0).then_some(area)
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+        self.child.mark_clean();
+    }
+    fn get_focus(&self) -> Option<bool> {
+        self.child.get_focus()
+    }
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        let area = self.child.get_focused_area()?.intersection(&self.get_viewport());
+        (area.size.width > 0 && area.size.height > 0).then_some(area)
+    }
+    fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
+        let focused_area = self.child.get_focused_area();
+        self.child.set_focus(from_dir)?;
+        self.dirty = true; // the color of the scrollbars changes
+        self.follow_focus(focused_area);
+        Ok(())
+    }
+}
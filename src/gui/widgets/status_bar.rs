@@ -0,0 +1,242 @@
+use core::fmt::Write;
+use core::marker::PhantomData;
+
+use crate::calculator::{Color, Event};
+use crate::gui::text::{AlphaState, TextInputContext, TextInputState};
+use crate::gui::theme::Theme;
+use crate::gui::{text_size, FocusFrom, RenderError, SizeHint, Widget, NORMAL_FONT, TEXT_PADDING};
+
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+use heapless::String;
+
+/// Height of a [`StatusBar`]: one line of text and its padding.
+pub const STATUS_BAR_HEIGHT: u32 = NORMAL_FONT.character_size.height + TEXT_PADDING;
+/// Size of the body of the battery icon, without its tip.
+const BATTERY_SIZE: Size = Size::new(14, 7);
+
+/// A bar at the top of the screen, showing the title of the application,
+/// the state of Shift and Alpha, and optionally the time and the battery level.
+///
+/// The state of Shift and Alpha is read from the [`TextInputContext`]
+/// with [`update()`](StatusBar::update()), which should be called after each event
+/// since the status bar usually isn't focused.
+///
+/// The text is drawn with the background color of the theme on its foreground color.
+///
+/// `T` is the type of [`Widget::Context`].
+#[derive(Debug, Clone)]
+pub struct StatusBar<T> {
+    title: String<24>,
+    input_state: TextInputState,
+    clock: Option<(u8, u8)>,
+    battery: Option<u8>,
+    bounding_box: Rectangle,
+    dirty: bool,
+    _context: PhantomData<T>,
+}
+/// Draw a line of text, vertically centered on `position`.
+fn draw_text<D>(
+    target: &mut D,
+    text: &str,
+    position: Point,
+    alignment: Alignment,
+    color: Color,
+) -> Result<(), RenderError>
+where
+    D: DrawTarget<Color = Color>,
+{
+    let text_style = TextStyleBuilder::new()
+        .alignment(alignment)
+        .baseline(Baseline::Middle)
+        .build();
+    Text::with_text_style(text, position, MonoTextStyle::new(&NORMAL_FONT, color), text_style)
+        .draw(target)
+        .map(|_| ())
+        .map_err(|_| RenderError::DrawTarget)
+}
+
+impl<T> StatusBar<T> {
+    /// Create a new status bar, without clock nor battery level.
+    pub fn new(title: String<24>, bounding_box: Rectangle) -> Self {
+        Self {
+            title,
+            input_state: TextInputState::new(),
+            clock: None,
+            battery: None,
+            bounding_box,
+            dirty: true,
+            _context: PhantomData,
+        }
+    }
+
+    /// Modify the title shown in the middle of the bar.
+    pub fn set_title(&mut self, title: String<24>) {
+        self.title = title;
+        self.dirty = true;
+    }
+    /// State of Shift and Alpha shown.
+    pub fn get_input_state(&self) -> TextInputState {
+        self.input_state
+    }
+    /// Show the time as hours and minutes, or hide it.
+    pub fn set_clock(&mut self, clock: Option<(u8, u8)>) {
+        if clock != self.clock {
+            self.clock = clock;
+            self.dirty = true;
+        }
+    }
+    /// Show the battery level, in percents, or hide it.
+    ///
+    /// The level is clamped to 100.
+    pub fn set_battery(&mut self, battery: Option<u8>) {
+        let battery = battery.map(|level| level.min(100));
+        if battery != self.battery {
+            self.battery = battery;
+            self.dirty = true;
+        }
+    }
+
+    /// Text showing the state of Shift and Alpha.
+    fn modifiers(&self) -> String<16> {
+        let mut modifiers = String::new();
+        // can't fail: "shift ALPHA lock" is 16 bytes long
+        if self.input_state.get_shift() {
+            modifiers.push_str("shift ").unwrap_or_default();
+        }
+        let (alpha, lock) = match self.input_state.get_alpha() {
+            AlphaState::No => ("", false),
+            AlphaState::Small(lock) => ("alpha", lock),
+            AlphaState::Big(lock) => ("ALPHA", lock),
+        };
+        modifiers.push_str(alpha).unwrap_or_default();
+        if lock {
+            modifiers.push_str(" lock").unwrap_or_default();
+        }
+        modifiers
+    }
+}
+impl<T: TextInputContext> StatusBar<T> {
+    /// Read the state of Shift and Alpha from the context.
+    pub fn update(&mut self, context: &mut T) {
+        let input_state = *context.get_context();
+        if input_state != self.input_state {
+            self.input_state = input_state;
+            self.dirty = true;
+        }
+    }
+}
+impl<T: TextInputContext> Widget for StatusBar<T> {
+    type Context = T;
+
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let mut target = target.clipped(&self.bounding_box);
+        let mut result = self
+            .bounding_box
+            .into_styled(PrimitiveStyle::with_fill(theme.foreground))
+            .draw(&mut target)
+            .map_err(|_| RenderError::DrawTarget);
+        let center_y = self.bounding_box.center().y;
+        let left = self.bounding_box.top_left.x + TEXT_PADDING as i32;
+        let mut right = self.bounding_box.top_left.x + self.bounding_box.size.width as i32 - TEXT_PADDING as i32;
+        result = result.and(draw_text(
+            &mut target,
+            &self.modifiers(),
+            Point::new(left, center_y),
+            Alignment::Left,
+            theme.background,
+        ));
+        result = result.and(draw_text(
+            &mut target,
+            &self.title,
+            self.bounding_box.center(),
+            Alignment::Center,
+            theme.background,
+        ));
+        if let Some(level) = self.battery {
+            // a battery with a tip on its right, filled according to its level
+            let body = Rectangle::new(
+                Point::new(
+                    right - BATTERY_SIZE.width as i32 - 2,
+                    center_y - (BATTERY_SIZE.height / 2) as i32,
+                ),
+                BATTERY_SIZE,
+            );
+            let tip = Rectangle::new(
+                body.top_left + Point::new(BATTERY_SIZE.width as i32, 2),
+                Size::new(2, 3),
+            );
+            let charge = Rectangle::new(
+                body.top_left + Point::new(2, 2),
+                Size::new((BATTERY_SIZE.width - 4) * level as u32 / 100, BATTERY_SIZE.height - 4),
+            );
+            result = result.and(
+                body.into_styled(PrimitiveStyle::with_stroke(theme.background, 1))
+                    .draw(&mut target)
+                    .and_then(|_| {
+                        tip.into_styled(PrimitiveStyle::with_fill(theme.background))
+                            .draw(&mut target)
+                    })
+                    .and_then(|_| {
+                        charge
+                            .into_styled(PrimitiveStyle::with_fill(theme.background))
+                            .draw(&mut target)
+                    })
+                    .map_err(|_| RenderError::DrawTarget),
+            );
+            right = body.top_left.x - TEXT_PADDING as i32;
+        }
+        if let Some((hours, minutes)) = self.clock {
+            let mut clock: String<8> = String::new();
+            // can't fail: the text is short enough
+            write!(clock, "{:02}:{:02}", hours, minutes).unwrap_or_default();
+            result = result.and(draw_text(
+                &mut target,
+                &clock,
+                Point::new(right, center_y),
+                Alignment::Right,
+                theme.background,
+            ));
+        }
+        result
+    }
+    /// The state of Shift and Alpha is read from the context if the status bar gets events.
+    fn on_event(&mut self, e: Event, context: &mut T) -> Option<Event> {
+        self.update(context);
+        Some(e)
+    }
+    fn set_bounding_box(&mut self, bounding_box: Rectangle) {
+        self.bounding_box = bounding_box;
+        self.dirty = true;
+    }
+    fn get_bounding_box(&self) -> Rectangle {
+        self.bounding_box
+    }
+    /// A status bar prefers to take the whole width.
+    fn measure(&self, available: Size) -> SizeHint {
+        SizeHint::new(
+            Size::new(text_size(&self.title).width, STATUS_BAR_HEIGHT),
+            Size::new(available.width, STATUS_BAR_HEIGHT),
+            available,
+        )
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        self.dirty.then_some(self.bounding_box)
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+    fn get_focus(&self) -> Option<bool> {
+        None
+    }
+    fn set_focus(&mut self, _: Option<FocusFrom>) -> Result<(), ()> {
+        Err(())
+    }
+}
@@ -0,0 +1,387 @@
+use crate::calculator::{Color, Event, KeyCode};
+use crate::gui::theme::Theme;
+use crate::gui::{
+    storage::{ValueCollection, WidgetCollection},
+    text_size, union_area, FocusFrom, RenderError, SizeHint, Widget, NORMAL_FONT, TEXT_PADDING,
+};
+
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::{Alignment, Baseline, Text, TextStyleBuilder},
+};
+use heapless::String;
+
+/// Height of the tab strip of a [`TabLayout`]: one line of text and its padding.
+pub const TAB_STRIP_HEIGHT: u32 = NORMAL_FONT.character_size.height + 2 * TEXT_PADDING;
+
+/// Pages of widgets, one of them being shown below a strip of tabs.
+///
+/// Its pages are stored in a collection of type `C`, and all take the space below the tab strip.
+/// The tabs share the width of the layout.
+///
+/// When the tab strip is focused, Left and Right select the previous or next tab,
+/// and Down, Ok or Exe focus the page.
+/// When the page is focused, Back or an Up that the page doesn't use focus the tab strip again.
+///
+/// # Example
+/// ```
+/// use embedded_graphics::{prelude::*, primitives::Rectangle};
+/// use nw_gui::gui::widgets::{ColorRect, TabLayout};
+/// use nw_gui::gui::Widget;
+/// use heapless::{String, Vec};
+/// let mut tabs: TabLayout<Vec<ColorRect<()>, 3>> =
+///     TabLayout::new(Rectangle::new(Point::new(0, 0), Size::new(320, 240)), Vec::new());
+/// tabs.add_tab(String::from("Expressions"), ColorRect::new(Rectangle::default())).unwrap();
+/// tabs.add_tab(String::from("Graph"), ColorRect::new(Rectangle::default())).unwrap();
+/// tabs.add_tab(String::from("Table"), ColorRect::new(Rectangle::default())).unwrap();
+/// assert_eq!(tabs.get(1).unwrap().get_bounding_box(), tabs.get_page_area());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TabLayout<C: WidgetCollection> {
+    pages: C,
+    titles: C::Values<String<16>>,
+    selected: usize,
+    focused: Option<TabFocus>,
+    bounding_box: Rectangle,
+    dirty: bool,       // the whole layout must be rendered again
+    strip_dirty: bool, // the tab strip must be rendered again
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TabFocus {
+    Strip,
+    Page,
+}
+
+impl<C: WidgetCollection> TabLayout<C> {
+    /// Create a new `TabLayout` filling the specified bounding box.
+    ///
+    /// `pages` is a the collection used to store the pages of the layout.
+    /// It should be empty.
+    ///
+    /// # Panics
+    /// The function panics if `pages` isn't empty.
+    pub fn new(bounding_box: Rectangle, pages: C) -> Self {
+        if !pages.is_empty() {
+            panic!("Can't create a tab layout from a non-empty collection.");
+        }
+        Self {
+            pages,
+            titles: Default::default(),
+            selected: 0,
+            focused: None,
+            bounding_box,
+            dirty: true,
+            strip_dirty: false,
+        }
+    }
+
+    /// Add a page after the last tab.
+    ///
+    /// If the page is successfully added, then its id is returned,
+    /// otherwise the page is returned back.
+    pub fn add_tab(&mut self, title: String<16>, mut page: C::Item) -> Result<usize, C::Item> {
+        page.set_bounding_box(self.get_page_area());
+        self.pages.add_widget(page)?;
+        // can't fail: the titles have the capacity of the pages
+        self.titles.push(title).unwrap_or_default();
+        self.dirty = true;
+        Ok(self.pages.len() - 1)
+    }
+    /// Change the title of a tab.
+    ///
+    /// The title is returned back if there is no such tab.
+    pub fn set_title(&mut self, n: usize, title: String<16>) -> Result<(), String<16>> {
+        match self.titles.get_mut(n) {
+            Some(previous) => *previous = title,
+            None => return Err(title),
+        }
+        self.strip_dirty = true;
+        Ok(())
+    }
+
+    /// Number of pages.
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+    /// Whether the layout has no pages.
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+    /// Read-only acces to a page.
+    pub fn get(&self, n: usize) -> Option<&C::Item> {
+        self.pages.get(n)
+    }
+    /// Mutable acces to a page.
+    pub fn get_mut(&mut self, n: usize) -> Option<&mut C::Item> {
+        self.pages.get_mut(n)
+    }
+    /// Id of the page shown.
+    pub fn get_selected_tab(&self) -> usize {
+        self.selected
+    }
+    /// Show another page.
+    ///
+    /// The id is clamped to the last page.
+    /// If the page shown was focused, the tab strip is focused instead.
+    pub fn select_tab(&mut self, n: usize) {
+        let n = n.min(self.pages.len().saturating_sub(1));
+        if n == self.selected {
+            return;
+        }
+        if self.focused == Some(TabFocus::Page) {
+            self.focus_strip();
+        }
+        self.selected = n;
+        self.dirty = true;
+    }
+    /// Whether the tab strip is focused, rather than the page.
+    pub fn is_strip_focused(&self) -> bool {
+        self.focused == Some(TabFocus::Strip)
+    }
+    /// Area taken by the pages, below the tab strip.
+    pub fn get_page_area(&self) -> Rectangle {
+        let strip_height = TAB_STRIP_HEIGHT.min(self.bounding_box.size.height);
+        Rectangle::new(
+            self.bounding_box.top_left + Size::new(0, strip_height),
+            self.bounding_box.size - Size::new(0, strip_height),
+        )
+    }
+
+    /// Area taken by the tab strip.
+    fn strip_area(&self) -> Rectangle {
+        Rectangle::new(
+            self.bounding_box.top_left,
+            Size::new(
+                self.bounding_box.size.width,
+                TAB_STRIP_HEIGHT.min(self.bounding_box.size.height),
+            ),
+        )
+    }
+    /// Area of the `n`-th tab in the tab strip.
+    fn tab_area(&self, n: usize) -> Rectangle {
+        let strip = self.strip_area();
+        let count = self.titles.len().max(1) as u64;
+        // computed from the start of the strip, so that rounding errors don't add up
+        let start = (strip.size.width as u64 * n as u64 / count) as u32;
+        let end = (strip.size.width as u64 * (n as u64 + 1) / count) as u32;
+        Rectangle::new(
+            strip.top_left + Size::new(start, 0),
+            Size::new(end - start, strip.size.height),
+        )
+    }
+
+    /// Focus the tab strip, unfocusing the page.
+    fn focus_strip(&mut self) {
+        if let Some(page) = self.pages.get_mut(self.selected) {
+            page.set_focus(None).unwrap_or_default();
+        }
+        self.focused = Some(TabFocus::Strip);
+        self.strip_dirty = true;
+    }
+    /// Try to focus the page shown.
+    fn focus_page(&mut self, from_dir: FocusFrom) -> Result<(), ()> {
+        self.pages.get_mut(self.selected).ok_or(())?.set_focus(Some(from_dir))?;
+        self.focused = Some(TabFocus::Page);
+        self.strip_dirty = true;
+        Ok(())
+    }
+
+    fn render_strip<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let mut result = self
+            .strip_area()
+            .into_styled(PrimitiveStyle::with_fill(theme.background))
+            .draw(target)
+            .map_err(|_| RenderError::DrawTarget);
+        let text_style = TextStyleBuilder::new()
+            .alignment(Alignment::Center)
+            .baseline(Baseline::Middle)
+            .build();
+        for (n, title) in self.titles.iter().enumerate() {
+            let area = self.tab_area(n);
+            let text_color = if n == self.selected {
+                // the selected tab is highlighted, with the focus color if the strip is focused
+                let highlight = if self.is_strip_focused() {
+                    theme.focused
+                } else {
+                    theme.foreground
+                };
+                result = result.and(
+                    area.into_styled(PrimitiveStyle::with_fill(highlight))
+                        .draw(target)
+                        .map_err(|_| RenderError::DrawTarget),
+                );
+                theme.background
+            } else {
+                theme.foreground
+            };
+            result = result.and(
+                Text::with_text_style(
+                    title,
+                    area.center(),
+                    MonoTextStyle::new(&NORMAL_FONT, text_color),
+                    text_style,
+                )
+                .draw(&mut target.clipped(&area))
+                .map(|_| ())
+                .map_err(|_| RenderError::DrawTarget),
+            );
+        }
+        result
+    }
+}
+impl<C: WidgetCollection> Widget for TabLayout<C> {
+    type Context = <<C as WidgetCollection>::Item as Widget>::Context;
+
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let strip = self.render_strip(target, theme);
+        match self.pages.get(self.selected) {
+            Some(page) => page.render(target, theme).and(strip),
+            None => strip,
+        }
+    }
+    fn render_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        if self.dirty {
+            return self.render(target, theme);
+        }
+        let strip = if self.strip_dirty {
+            self.render_strip(target, theme)
+        } else {
+            Ok(())
+        };
+        match self.pages.get(self.selected) {
+            Some(page) => page.render_dirty(target, theme).and(strip),
+            None => strip,
+        }
+    }
+    fn on_event(&mut self, e: Event, context: &mut Self::Context) -> Option<Event> {
+        match self.focused {
+            None => Some(e),
+            Some(TabFocus::Strip) => match e {
+                Event::KeyDown(KeyCode::Left) | Event::KeyRepeat(KeyCode::Left) if self.selected > 0 => {
+                    self.select_tab(self.selected - 1);
+                    None
+                }
+                Event::KeyDown(KeyCode::Right) | Event::KeyRepeat(KeyCode::Right)
+                    if self.selected + 1 < self.pages.len() =>
+                {
+                    self.select_tab(self.selected + 1);
+                    None
+                }
+                Event::KeyDown(KeyCode::Down | KeyCode::Ok | KeyCode::Exe) => match self.focus_page(FocusFrom::Up) {
+                    Ok(()) => None,
+                    Err(()) => Some(e),
+                },
+                _ => Some(e), // pass event to the parent
+            },
+            Some(TabFocus::Page) => {
+                let remaining_event = match self.pages.get_mut(self.selected) {
+                    Some(page) => page.on_event(e, context),
+                    None => Some(e),
+                };
+                match remaining_event {
+                    Some(Event::KeyDown(KeyCode::Back | KeyCode::Up) | Event::KeyRepeat(KeyCode::Up)) => {
+                        self.focus_strip();
+                        None
+                    }
+                    _ => remaining_event,
+                }
+            }
+        }
+    }
+    fn set_bounding_box(&mut self, bounding_box: Rectangle) {
+        self.bounding_box = bounding_box;
+        self.dirty = true;
+        let page_area = self.get_page_area();
+        for n in 0..self.pages.len() {
+            self.pages.get_mut(n).unwrap().set_bounding_box(page_area);
+        }
+    }
+    fn get_bounding_box(&self) -> Rectangle {
+        self.bounding_box
+    }
+    /// The largest page is measured below the tab strip, which fits all the titles.
+    fn measure(&self, available: Size) -> SizeHint {
+        let strip = Size::new(0, TAB_STRIP_HEIGHT);
+        let mut min = Size::zero();
+        let mut preferred = Size::zero();
+        for n in 0..self.pages.len() {
+            let hint = self.pages.get(n).unwrap().measure(available.saturating_sub(strip));
+            min = min.component_max(hint.min);
+            preferred = preferred.component_max(hint.preferred);
+        }
+        let titles_width = self
+            .titles
+            .iter()
+            .map(|title| text_size(title).width + 2 * TEXT_PADDING)
+            .sum();
+        preferred.width = preferred.width.max(titles_width);
+        SizeHint::new(min + strip, preferred + strip, available)
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        if self.dirty {
+            return Some(self.bounding_box);
+        }
+        let strip = if self.strip_dirty {
+            Some(self.strip_area())
+        } else {
+            None
+        };
+        union_area(
+            strip,
+            self.pages.get(self.selected).and_then(|page| page.get_dirty_area()),
+        )
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+        self.strip_dirty = false;
+        self.pages.mark_clean();
+    }
+    fn get_focus(&self) -> Option<bool> {
+        Some(self.focused.is_some())
+    }
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        match self.focused {
+            Some(TabFocus::Strip) => Some(self.tab_area(self.selected)),
+            Some(TabFocus::Page) => self.pages.get(self.selected).and_then(|page| page.get_focused_area()),
+            None => None,
+        }
+    }
+    fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
+        let from_dir = match from_dir {
+            Some(from_dir) => from_dir,
+            None => {
+                if self.focused.is_some() {
+                    if let Some(page) = self.pages.get_mut(self.selected) {
+                        page.set_focus(None).unwrap_or_default();
+                    }
+                    self.focused = None;
+                    self.strip_dirty = true;
+                }
+                return Ok(());
+            }
+        };
+        if self.pages.is_empty() {
+            return Err(());
+        }
+        // if we were already focused, keep that focus
+        if self.focused.is_none() {
+            // coming from below, the page is entered first
+            if from_dir != FocusFrom::Down || self.focus_page(from_dir).is_err() {
+                self.focus_strip();
+            }
+        }
+        Ok(())
+    }
+}
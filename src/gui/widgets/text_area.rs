@@ -0,0 +1,418 @@
+use core::marker::PhantomData;
+
+use crate::calculator::{Color, Event};
+use crate::gui::text::{Action, EditHistory, Lines, TextInput, TextInputContext, CLIPBOARD_CAPACITY, HISTORY_SIZE};
+use crate::gui::theme::Theme;
+use crate::gui::{text_columns, FocusFrom, RenderError, SizeHint, Widget, NORMAL_FONT, TEXT_PADDING};
+
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    prelude::*,
+    primitives::{PrimitiveStyle, PrimitiveStyleBuilder, Rectangle},
+    text::{Baseline, Text},
+};
+use heapless::String;
+
+/// Width of a character of [`NORMAL_FONT`], with the space after it.
+const COLUMN_WIDTH: u32 = NORMAL_FONT.character_size.width + NORMAL_FONT.character_spacing;
+/// Height of a line of text.
+const LINE_HEIGHT: u32 = NORMAL_FONT.character_size.height;
+
+/// A text on several lines, wrapped to the width of the text area.
+///
+/// The text is scrolled to show the cursor.
+/// When it is editable, the text is edited at the cursor as soon as the text area is focused:
+/// the arrows move the cursor, Exe starts a new line,
+/// and Shift + Minus and Shift + Plus undo and redo the changes.
+/// The arrows move the focus once the cursor is at the start or at the end of the text.
+///
+/// A read-only text area, f.e. for help screens or the output of a script,
+/// is scrolled with Up and Down.
+///
+/// Text that doesn't fit in the `N` bytes of the text area isn't inserted,
+/// and the event inserting it is returned.
+/// The changes are kept in `H` bytes, and a read-only text area can have `H` set to 0.
+///
+/// `T` is the type of [`Widget::Context`].
+#[derive(Debug, Clone)]
+pub struct TextArea<T, const N: usize, const H: usize = HISTORY_SIZE> {
+    text: String<N>,
+    bounding_box: Rectangle,
+    read_only: bool,
+    focused: bool,
+    cursor: usize,     // position of the cursor in `text`, in bytes
+    first_line: usize, // first line shown
+    history: EditHistory<H>,
+    dirty: bool,
+    _context: PhantomData<T>,
+}
+impl<T, const N: usize, const H: usize> TextArea<T, N, H> {
+    /// Create a new empty text area.
+    pub fn new(bounding_box: Rectangle, read_only: bool) -> Self {
+        Self {
+            text: String::new(),
+            bounding_box,
+            read_only,
+            focused: false,
+            cursor: 0,
+            first_line: 0,
+            history: EditHistory::new(),
+            dirty: true,
+            _context: PhantomData,
+        }
+    }
+
+    /// Text shown.
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+    /// Replace the text shown, putting the cursor at its start.
+    ///
+    /// The changes made before can't be undone anymore.
+    pub fn set_text(&mut self, text: String<N>) {
+        self.text = text;
+        self.cursor = 0;
+        self.first_line = 0;
+        self.history.clear();
+        self.dirty = true;
+    }
+    /// Add text at the end, f.e. the output of a script, and scroll to show it.
+    ///
+    /// The start of the text is removed to make room for `text` if needed.
+    /// The changes made before can't be undone anymore.
+    pub fn append(&mut self, text: &str) {
+        // the end of `text` is kept if it is too long
+        let text = &text[Self::ceil_boundary(text, text.len().saturating_sub(N))..];
+        let removed = Self::ceil_boundary(&self.text, (self.text.len() + text.len()).saturating_sub(N));
+        if removed > 0 {
+            let kept: String<N> = String::from(&self.text[removed..]);
+            self.text = kept;
+        }
+        self.history.clear();
+        // can't fail: enough text has been removed
+        self.text.push_str(text).unwrap_or_default();
+        self.cursor = self.text.len();
+        self.show_cursor();
+        self.dirty = true;
+    }
+    /// Remove all the text.
+    pub fn clear(&mut self) {
+        self.set_text(String::new());
+    }
+    /// Whether the text can't be edited.
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+    /// Allow or forbid editing the text.
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+        self.dirty = true;
+    }
+    /// Position of the cursor in the text, in bytes.
+    pub fn get_cursor(&self) -> usize {
+        self.cursor
+    }
+    /// Move the cursor, and scroll to show it.
+    ///
+    /// The position is clamped to the text.
+    pub fn set_cursor(&mut self, position: usize) {
+        self.cursor = Self::floor_boundary(&self.text, position);
+        self.show_cursor();
+        self.dirty = true;
+    }
+    /// Index of the first line shown.
+    pub fn get_first_line(&self) -> usize {
+        self.first_line
+    }
+    /// Number of lines of the text, once wrapped.
+    pub fn line_count(&self) -> usize {
+        self.lines().count()
+    }
+
+    /// Position in `text` at most `position`, between two characters.
+    fn floor_boundary(text: &str, position: usize) -> usize {
+        let mut position = position.min(text.len());
+        while !text.is_char_boundary(position) {
+            position -= 1;
+        }
+        position
+    }
+    /// Position in `text` at least `position`, between two characters.
+    fn ceil_boundary(text: &str, position: usize) -> usize {
+        let mut position = position.min(text.len());
+        while !text.is_char_boundary(position) {
+            position += 1;
+        }
+        position
+    }
+    /// Area in which the text is drawn.
+    fn text_area(&self) -> Rectangle {
+        let padding = Size::new(TEXT_PADDING, TEXT_PADDING);
+        Rectangle::new(
+            self.bounding_box.top_left + padding,
+            self.bounding_box.size.saturating_sub(padding * 2),
+        )
+    }
+    /// Number of characters fitting on a line.
+    fn columns(&self) -> usize {
+        text_columns(self.text_area().size.width)
+    }
+    /// Number of lines fully visible, at least one.
+    fn visible_lines(&self) -> usize {
+        ((self.text_area().size.height / LINE_HEIGHT) as usize).max(1)
+    }
+    /// Lines of the text wrapped to the text area.
+    fn lines(&self) -> Lines<'_> {
+        Lines::new(&self.text, self.columns())
+    }
+    /// Line of the cursor, and its start and end.
+    fn cursor_line(&self) -> (usize, (usize, usize)) {
+        // at the end of a line broken in a word, the cursor is at the start of the next line
+        let mut result = (0, (0, 0));
+        for (n, line) in self.lines().enumerate() {
+            if line.0 > self.cursor {
+                break;
+            }
+            result = (n, line);
+        }
+        result
+    }
+    /// Scroll to show the line of the cursor.
+    fn show_cursor(&mut self) {
+        let (line, _) = self.cursor_line();
+        let visible_lines = self.visible_lines();
+        if line < self.first_line {
+            self.first_line = line;
+        } else if line >= self.first_line + visible_lines {
+            self.first_line = line + 1 - visible_lines;
+        }
+    }
+    /// Move the cursor to the same column of the `n`-th line, if it exists.
+    fn move_to_line(&mut self, n: usize) -> bool {
+        let (_, (start, _)) = self.cursor_line();
+        let column = self.text[start..self.cursor].chars().count();
+        match self.lines().nth(n) {
+            Some((start, end)) => {
+                self.cursor = self.text[start..end]
+                    .char_indices()
+                    .nth(column)
+                    .map_or(end, |(position, _)| start + position);
+                true
+            }
+            None => false,
+        }
+    }
+    /// Insert text at the cursor, if it fits.
+    fn insert(&mut self, text: &str) -> bool {
+        if self.text.len() + text.len() > N {
+            return false;
+        }
+        let tail: String<N> = String::from(&self.text[self.cursor..]);
+        self.text.truncate(self.cursor);
+        // can't fail: we already checked the length
+        self.text.push_str(text).unwrap_or_default();
+        self.text.push_str(&tail).unwrap_or_default();
+        self.cursor += text.len();
+        true
+    }
+    /// Delete the character before the cursor, if any.
+    fn delete_before_cursor(&mut self) -> bool {
+        match self.text[..self.cursor].char_indices().next_back() {
+            Some((previous, _)) => {
+                let tail: String<N> = String::from(&self.text[self.cursor..]);
+                self.text.truncate(previous);
+                // can't fail: the text is shorter
+                self.text.push_str(&tail).unwrap_or_default();
+                self.cursor = previous;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Handle an action in a read-only text area.
+    fn scroll(&mut self, action: Action) -> bool {
+        let max_first_line = self.line_count().saturating_sub(self.visible_lines());
+        match action {
+            Action::Up(_) if self.first_line > 0 => self.first_line -= 1,
+            Action::Down(_) if self.first_line < max_first_line => self.first_line += 1,
+            _ => return false,
+        }
+        true
+    }
+}
+impl<T: TextInputContext, const N: usize, const H: usize> Widget for TextArea<T, N, H> {
+    type Context = T;
+
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let style = PrimitiveStyleBuilder::new()
+            .fill_color(theme.background)
+            .stroke_color(if self.focused { theme.focused } else { theme.foreground })
+            .stroke_width(theme.rect_border)
+            .build();
+        let mut result = self
+            .bounding_box
+            .into_styled(style)
+            .draw(target)
+            .map_err(|_| RenderError::DrawTarget);
+        let area = self.text_area();
+        let mut target = target.clipped(&area);
+        let (cursor_line, (cursor_start, _)) = self.cursor_line();
+        let lines = self
+            .lines()
+            .enumerate()
+            .skip(self.first_line)
+            .take(self.visible_lines() + 1);
+        for (n, (start, end)) in lines {
+            let position = area.top_left + Point::new(0, ((n - self.first_line) as u32 * LINE_HEIGHT) as i32);
+            result = result.and(
+                Text::with_baseline(
+                    &self.text[start..end],
+                    position,
+                    MonoTextStyle::new(&NORMAL_FONT, theme.foreground),
+                    Baseline::Top,
+                )
+                .draw(&mut target)
+                .map(|_| ())
+                .map_err(|_| RenderError::DrawTarget),
+            );
+            if n == cursor_line && self.focused && !self.read_only {
+                let column = self.text[cursor_start..self.cursor].chars().count() as u32;
+                result = result.and(
+                    Rectangle::new(
+                        position + Point::new((column * COLUMN_WIDTH) as i32, 0),
+                        Size::new(1, LINE_HEIGHT),
+                    )
+                    .into_styled(PrimitiveStyle::with_fill(theme.active))
+                    .draw(&mut target)
+                    .map_err(|_| RenderError::DrawTarget),
+                );
+            }
+        }
+        result
+    }
+    fn on_event(&mut self, e: Event, context: &mut T) -> Option<Event> {
+        let input = context.get_context().text_from_event(&e);
+        // the text is kept to record the change only if the event may edit it
+        let edits = !self.read_only
+            && matches!(
+                input,
+                TextInput::Text(_)
+                    | TextInput::Backspace
+                    | TextInput::Action(Action::Exe | Action::Clear | Action::Paste)
+            );
+        let previous = edits.then(|| (self.text.clone(), self.cursor));
+        let handled = match input {
+            // without selection, the whole text is copied
+            TextInput::Action(Action::Copy) => context
+                .get_clipboard()
+                .map(|clipboard| clipboard.set(&self.text))
+                .is_some(),
+            // a read-only text is only scrolled and copied
+            TextInput::Action(action) if self.read_only => self.scroll(action),
+            _ if self.read_only => false,
+            TextInput::Text(text) => self.insert(text),
+            TextInput::Backspace => self.delete_before_cursor(),
+            TextInput::Action(action) => match action {
+                Action::Exe => self.insert("\n"),
+                Action::Clear => {
+                    self.text.clear();
+                    self.cursor = 0;
+                    true
+                }
+                Action::Left(_) => match self.text[..self.cursor].char_indices().next_back() {
+                    Some((previous, _)) => {
+                        self.cursor = previous;
+                        true
+                    }
+                    None => false,
+                },
+                Action::Right(_) => match self.text[self.cursor..].chars().next() {
+                    Some(c) => {
+                        self.cursor += c.len_utf8();
+                        true
+                    }
+                    None => false,
+                },
+                Action::Up(_) => {
+                    let (line, _) = self.cursor_line();
+                    line > 0 && self.move_to_line(line - 1)
+                }
+                Action::Down(_) => {
+                    let (line, _) = self.cursor_line();
+                    self.move_to_line(line + 1)
+                }
+                Action::Paste => match context.get_clipboard() {
+                    Some(clipboard) => {
+                        let text: String<CLIPBOARD_CAPACITY> = String::from(clipboard.get());
+                        self.insert(&text)
+                    }
+                    None => false,
+                },
+                Action::Undo => self.history.undo(&mut self.text, &mut self.cursor),
+                Action::Redo => self.history.redo(&mut self.text, &mut self.cursor),
+                _ => false,
+            },
+            TextInput::None => false,
+        };
+        if let Some((previous_text, previous_cursor)) = previous {
+            self.history.record(&previous_text, &self.text, previous_cursor);
+        }
+        if !handled {
+            return Some(e);
+        }
+        if !self.read_only {
+            self.show_cursor();
+        }
+        self.dirty = true;
+        None
+    }
+    fn set_bounding_box(&mut self, bounding_box: Rectangle) {
+        self.bounding_box = bounding_box;
+        if !self.read_only {
+            self.show_cursor();
+        }
+        self.dirty = true;
+    }
+    fn get_bounding_box(&self) -> Rectangle {
+        self.bounding_box
+    }
+    /// A text area prefers to show all its lines on the available width.
+    fn measure(&self, available: Size) -> SizeHint {
+        let columns = text_columns(available.width.saturating_sub(2 * TEXT_PADDING));
+        let lines = Lines::new(&self.text, columns).count() as u32;
+        let padding = 2 * TEXT_PADDING;
+        SizeHint::new(
+            Size::new(COLUMN_WIDTH + padding, LINE_HEIGHT + padding),
+            Size::new(available.width, lines * LINE_HEIGHT + padding),
+            available,
+        )
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        if self.dirty {
+            Some(self.bounding_box)
+        } else {
+            None
+        }
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+    fn get_focus(&self) -> Option<bool> {
+        Some(self.focused)
+    }
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        if self.focused {
+            Some(self.bounding_box)
+        } else {
+            None
+        }
+    }
+    fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
+        self.focused = from_dir.is_some();
+        self.dirty = true;
+        Ok(())
+    }
+}
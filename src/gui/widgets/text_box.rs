@@ -2,31 +2,54 @@ use core::marker::PhantomData;
 use embedded_graphics::{
     mono_font::MonoTextStyleBuilder,
     prelude::*,
-    primitives::Rectangle,
+    primitives::{PrimitiveStyle, Rectangle},
     text::{Alignment, Baseline, Text, TextStyleBuilder},
 };
 use heapless::String;
 
 use super::ColorRect;
-use crate::gui::text::{Action, TextInput, TextInputContext};
+use crate::gui::text::{
+    Action, EditHistory, Popup, Popups, TextInput, TextInputContext, CLIPBOARD_CAPACITY, HISTORY_SIZE,
+};
 use crate::{
-    calculator::Event,
-    gui::{Widget, NORMAL_FONT},
+    calculator::{Color, Event},
+    gui::{text_size, FocusFrom, RenderError, SizeHint, Widget, NORMAL_FONT, TEXT_PADDING},
 };
 
+/// Time during which the cursor of a [`TextBox`] is shown, then hidden, in milliseconds.
+pub const CURSOR_BLINK_PERIOD: u32 = 500;
+
 #[derive(Debug, Clone)]
 struct EditionInfo {
     focused: bool,
     in_edition: bool, // whether the text is being edited
 }
 
-pub struct TextBox<T, const N: usize> {
+/// A single line of text which, if it is editable, is edited after Ok or replaced by typing.
+///
+/// The text holds at most `N` bytes.
+/// The changes are undone with Shift + Minus and redone with Shift + Plus.
+/// They are kept inside the text box in an [`EditHistory`] of `H` bytes,
+/// so by default each text box takes [`HISTORY_SIZE`] more bytes:
+/// text boxes which can't be edited, or many small ones in a form, should set `H` to 0.
+///
+/// `T` is the type of [`Widget::Context`].
+pub struct TextBox<T, const N: usize, const H: usize = HISTORY_SIZE> {
     background: ColorRect<T>,
     pub value: String<N>,
     edition: Option<EditionInfo>,
+    cursor: usize,         // position of the cursor in `value`, in bytes
+    anchor: Option<usize>, // other end of the selection, the cursor being at one end
+    cursor_visible: bool,
+    blink_timer: Option<u32>,
+    history: EditHistory<H>,
+    // `value` can be modified from outside, so it is compared with what has been rendered
+    rendered_value: String<N>,
+    popup_requested: bool, // whether the text chosen in a popup is awaited
+    dirty: bool,
     _context: PhantomData<T>,
 }
-impl<T, const N: usize> TextBox<T, N> {
+impl<T, const N: usize, const H: usize> TextBox<T, N, H> {
     pub fn new(bounding_box: Rectangle, editable: bool) -> Self {
         Self {
             _context: PhantomData,
@@ -40,10 +63,139 @@ impl<T, const N: usize> TextBox<T, N> {
             } else {
                 None
             },
+            cursor: 0,
+            anchor: None,
+            cursor_visible: true,
+            blink_timer: None,
+            rendered_value: String::new(),
+            popup_requested: false,
+            history: EditHistory::new(),
+            dirty: true,
+        }
+    }
+
+    /// Position of the cursor in `value`, in bytes.
+    pub fn get_cursor(&self) -> usize {
+        self.clamp_position(self.cursor)
+    }
+    /// Start and end of the selected text in `value`, in bytes, if any.
+    ///
+    /// The text is selected with Shift and the arrows while it is edited.
+    pub fn get_selection(&self) -> Option<(usize, usize)> {
+        let anchor = self.clamp_position(self.anchor?);
+        let cursor = self.get_cursor();
+        (anchor != cursor).then(|| (anchor.min(cursor), anchor.max(cursor)))
+    }
+    /// Move the cursor, clamped to the end of `value`.
+    pub fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = cursor;
+        self.cursor = self.get_cursor();
+        self.dirty = true;
+    }
+    /// Make the cursor blink while the text is edited, using the timer `id`.
+    ///
+    /// The timers are given by [`TextInputContext::get_timers()`],
+    /// and the [`Event::Timer`] events must be sent to the text box.
+    /// With `None`, the cursor is always shown.
+    pub fn set_blink_timer(&mut self, id: Option<u32>) {
+        self.blink_timer = id;
+        self.cursor_visible = true;
+    }
+    /// Read access to the changes of the text, undone with Shift + Minus and redone with Shift + Plus.
+    ///
+    /// The history takes `H` bytes, and a text box which isn't editable can have `H` set to 0.
+    pub fn get_history(&self) -> &EditHistory<H> {
+        &self.history
+    }
+    /// Mutable access to the changes of the text, f.e. to forget them once `value` is replaced.
+    pub fn get_history_mut(&mut self) -> &mut EditHistory<H> {
+        &mut self.history
+    }
+
+    /// Insert text chosen in a popup, starting the edition if needed.
+    fn insert(&mut self, text: &str) {
+        let previous = (self.value.clone(), self.get_cursor());
+        if let Some(ref mut edition) = self.edition {
+            if !edition.in_edition {
+                edition.in_edition = true;
+                self.value.clear();
+            }
+            self.insert_at_cursor(text);
+            self.dirty = true;
+        }
+        self.record_change(previous);
+    }
+    /// Record the change from the previous state of the text in the history.
+    fn record_change(&mut self, (previous, cursor): (String<N>, usize)) {
+        self.history.record(&previous, &self.value, cursor);
+    }
+    /// Position in `value` at most `position`, between two characters.
+    fn clamp_position(&self, position: usize) -> usize {
+        let mut position = position.min(self.value.len());
+        // `value` may have been modified from outside
+        while !self.value.is_char_boundary(position) {
+            position -= 1;
+        }
+        position
+    }
+    /// Move the cursor to `position`, extending the selection.
+    fn select_to(&mut self, position: usize) {
+        if self.anchor.is_none() {
+            self.anchor = Some(self.get_cursor());
+        }
+        self.cursor = position;
+    }
+    /// Remove the selected text, if any.
+    fn delete_selection(&mut self) -> bool {
+        let selection = self.get_selection();
+        self.anchor = None;
+        match selection {
+            Some((start, end)) => {
+                let tail: String<N> = String::from(&self.value[end..]);
+                self.value.truncate(start);
+                self.value.push_str(&tail).unwrap_or_default();
+                self.cursor = start;
+                true
+            }
+            None => false,
         }
     }
+    /// Insert text before the cursor in place of the selected text, if it fits.
+    fn insert_at_cursor(&mut self, text: &str) {
+        let selected = self.get_selection().map_or(0, |(start, end)| end - start);
+        if self.value.len() - selected + text.len() > N {
+            return;
+        }
+        self.delete_selection();
+        let cursor = self.get_cursor();
+        let tail: String<N> = String::from(&self.value[cursor..]);
+        self.value.truncate(cursor);
+        // can't fail: we already checked that there is enough space
+        self.value.push_str(text).unwrap_or_default();
+        self.value.push_str(&tail).unwrap_or_default();
+        self.cursor = cursor + text.len();
+    }
+    /// Remove the character before the cursor, if any.
+    fn delete_before_cursor(&mut self) {
+        if let Some(previous) = self.previous_position() {
+            let tail: String<N> = String::from(&self.value[self.get_cursor()..]);
+            self.value.truncate(previous);
+            self.value.push_str(&tail).unwrap_or_default();
+            self.cursor = previous;
+        }
+    }
+    /// Position of the character before the cursor.
+    fn previous_position(&self) -> Option<usize> {
+        let cursor = self.get_cursor();
+        self.value[..cursor].chars().next_back().map(|c| cursor - c.len_utf8())
+    }
+    /// Position of the character after the cursor.
+    fn next_position(&self) -> Option<usize> {
+        let cursor = self.get_cursor();
+        self.value[cursor..].chars().next().map(|c| cursor + c.len_utf8())
+    }
 }
-impl<T: TextInputContext, const N: usize> Widget for TextBox<T, N> {
+impl<T: TextInputContext, const N: usize, const H: usize> Widget for TextBox<T, N, H> {
     type Context = T;
 
     fn get_bounding_box(&self) -> embedded_graphics::primitives::Rectangle {
@@ -52,10 +204,35 @@ impl<T: TextInputContext, const N: usize> Widget for TextBox<T, N> {
     fn set_bounding_box(&mut self, bounding_box: embedded_graphics::primitives::Rectangle) {
         self.background.set_bounding_box(bounding_box);
     }
+    /// A text box prefers to take all the available width, and to be one line high.
+    fn measure(&self, available: Size) -> SizeHint {
+        let text = text_size(&self.value);
+        SizeHint::new(
+            text,
+            Size::new(available.width, text.height + 2 * TEXT_PADDING),
+            available,
+        )
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        if self.dirty || self.value != self.rendered_value {
+            Some(self.get_bounding_box())
+        } else {
+            self.background.get_dirty_area()
+        }
+    }
+    fn mark_clean(&mut self) {
+        self.rendered_value = self.value.clone();
+        self.dirty = false;
+        self.background.mark_clean();
+    }
     fn get_focus(&self) -> Option<bool> {
         self.edition.as_ref().map(|edition| edition.focused)
     }
-    fn set_focus(&mut self, from_dir: Option<crate::gui::FocusFrom>) -> Result<(), ()> {
+    fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
+        self.dirty = true;
+        if from_dir.is_none() {
+            self.anchor = None;
+        }
         if let Some(ref mut edition) = self.edition {
             if from_dir.is_some() {
                 edition.focused = true;
@@ -69,49 +246,188 @@ impl<T: TextInputContext, const N: usize> Widget for TextBox<T, N> {
         }
     }
     fn on_event(&mut self, e: crate::calculator::Event, context: &mut Self::Context) -> Option<Event> {
-        if let Some(ref mut edition) = self.edition {
-            let input = context.get_context().text_from_event(&e);
-            let remaining_event: Option<Event> = match input {
-                TextInput::Text(text) => {
-                    if !edition.in_edition {
-                        edition.in_edition = true;
-                        self.value.clear();
+        if self.edition.is_none() {
+            return Some(e);
+        }
+        // the text chosen in the popup asked for, if any, is given back once the popup is dismissed
+        if e == Event::LayerDismissed && self.popup_requested {
+            self.popup_requested = false;
+            if let Some(text) = context.get_popups().and_then(Popups::take_chosen) {
+                self.insert(&text);
+            }
+            return None;
+        }
+        let mut in_edition = match self.edition {
+            Some(ref edition) => edition.in_edition,
+            None => return Some(e),
+        };
+        if let (Event::Timer(id), Some(blink_timer)) = (e, self.blink_timer) {
+            if id == blink_timer {
+                // the timer isn't scheduled again once the edition is over
+                if in_edition {
+                    self.cursor_visible = !self.cursor_visible;
+                    self.dirty = true;
+                    if let Some(timers) = context.get_timers() {
+                        timers.schedule(blink_timer, CURSOR_BLINK_PERIOD).unwrap_or_default();
                     }
-                    self.value.push_str(text).unwrap_or_default();
-                    None
                 }
-                TextInput::Backspace => {
-                    if edition.in_edition {
-                        self.value.pop();
-                    } else {
-                        edition.in_edition = true;
-                        self.value.clear();
+                return None;
+            }
+        }
+        let input = context.get_context().text_from_event(&e);
+        // state of the text before the event, recorded if the text changes
+        let mut previous = Some((self.value.clone(), self.get_cursor()));
+        let remaining_event: Option<Event> = match input {
+            TextInput::Text(text) => {
+                if !in_edition {
+                    in_edition = true;
+                    self.value.clear();
+                }
+                self.insert_at_cursor(text);
+                None
+            }
+            TextInput::Backspace => {
+                if in_edition {
+                    if !self.delete_selection() {
+                        self.delete_before_cursor();
                     }
+                } else {
+                    in_edition = true;
+                    self.value.clear();
+                }
+                None
+            }
+            TextInput::Action(action) => match action {
+                Action::Back => {
+                    in_edition = false;
+                    None
+                }
+                Action::Clear => {
+                    self.value.clear();
+                    None
+                }
+                Action::Ok | Action::Exe => {
+                    in_edition = !in_edition;
+                    self.cursor = self.value.len();
                     None
                 }
-                TextInput::Action(action) => match action {
-                    Action::Back => {
-                        edition.in_edition = false;
+                // the cursor moves inside the text, and the focus moves at its ends
+                Action::Left(false) if in_edition => self.previous_position().map_or(Some(e), |previous| {
+                    self.anchor = None;
+                    self.cursor = previous;
+                    None
+                }),
+                Action::Right(false) if in_edition => self.next_position().map_or(Some(e), |next| {
+                    self.anchor = None;
+                    self.cursor = next;
+                    None
+                }),
+                Action::Up(false) if in_edition && self.get_cursor() > 0 => {
+                    self.anchor = None;
+                    self.cursor = 0;
+                    None
+                }
+                Action::Down(false) if in_edition && self.get_cursor() < self.value.len() => {
+                    self.anchor = None;
+                    self.cursor = self.value.len();
+                    None
+                }
+                // with Shift, the selection is extended
+                Action::Left(true) if in_edition => self.previous_position().map_or(Some(e), |previous| {
+                    self.select_to(previous);
+                    None
+                }),
+                Action::Right(true) if in_edition => self.next_position().map_or(Some(e), |next| {
+                    self.select_to(next);
+                    None
+                }),
+                Action::Up(true) if in_edition => {
+                    self.select_to(0);
+                    None
+                }
+                Action::Down(true) if in_edition => {
+                    self.select_to(self.value.len());
+                    None
+                }
+                // without selection, the whole text is copied
+                Action::Copy | Action::Cut => match context.get_clipboard() {
+                    Some(clipboard) => {
+                        let (start, end) = self.get_selection().unwrap_or((0, self.value.len()));
+                        clipboard.set(&self.value[start..end]);
+                        if action == Action::Cut && !self.delete_selection() {
+                            self.value.clear();
+                        }
                         None
                     }
-                    Action::Clear => {
-                        self.value.clear();
+                    None => Some(e),
+                },
+                Action::Paste => match context.get_clipboard() {
+                    Some(clipboard) => {
+                        let text: String<CLIPBOARD_CAPACITY> = String::from(clipboard.get());
+                        if !in_edition {
+                            in_edition = true;
+                            self.value.clear();
+                        }
+                        self.insert_at_cursor(&text);
                         None
                     }
-                    Action::Ok | Action::Exe => {
-                        edition.in_edition = !edition.in_edition;
+                    None => Some(e),
+                },
+                Action::Undo | Action::Redo => {
+                    let changed = if action == Action::Undo {
+                        self.history.undo(&mut self.value, &mut self.cursor)
+                    } else {
+                        self.history.redo(&mut self.value, &mut self.cursor)
+                    };
+                    // the history isn't recorded again
+                    previous = None;
+                    if changed {
+                        in_edition = true;
+                        self.anchor = None;
                         None
+                    } else {
+                        Some(e)
                     }
-                    _ => Some(e),
+                }
+                Action::Toolbox | Action::Var => match context.get_popups() {
+                    Some(popups) => {
+                        popups.request(if action == Action::Toolbox {
+                            Popup::Toolbox
+                        } else {
+                            Popup::Var
+                        });
+                        self.popup_requested = true;
+                        None
+                    }
+                    None => Some(e),
                 },
-                TextInput::None => Some(e),
-            };
-            remaining_event
-        } else {
-            Some(e)
+                _ => Some(e),
+            },
+            TextInput::None => Some(e),
+        };
+        if let Some(previous) = previous {
+            self.record_change(previous);
         }
+        if let Some(ref mut edition) = self.edition {
+            edition.in_edition = in_edition;
+        }
+        if !in_edition {
+            self.anchor = None;
+        }
+        if remaining_event.is_none() {
+            self.dirty = true;
+            // the cursor is shown while typing
+            self.cursor_visible = true;
+            if let (true, Some(blink_timer), Some(timers)) = (in_edition, self.blink_timer, context.get_timers()) {
+                timers.schedule(blink_timer, CURSOR_BLINK_PERIOD).unwrap_or_default();
+            }
+        }
+        remaining_event
     }
-    fn render(&self, target: &mut crate::calculator::DeviceDislay, theme: &crate::gui::theme::Theme) {
+    fn render<D>(&self, target: &mut D, theme: &crate::gui::theme::Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
         let (editable, focused, in_edition) = if let Some(ref edition) = self.edition {
             (true, edition.focused, edition.in_edition)
         } else {
@@ -128,7 +444,7 @@ impl<T: TextInputContext, const N: usize> Widget for TextBox<T, N> {
         if !editable {
             background_theme.rect_border = 0;
         }
-        self.background.render(target, &background_theme);
+        let background = self.background.render(target, &background_theme);
         let character_style = MonoTextStyleBuilder::new()
             .font(&NORMAL_FONT)
             .text_color(theme.foreground);
@@ -142,13 +458,41 @@ impl<T: TextInputContext, const N: usize> Widget for TextBox<T, N> {
             .baseline(Baseline::Middle)
             .build();
 
-        Text::with_text_style(
+        let text = Text::with_text_style(
             &self.value,
             self.background.get_bounding_box().center(),
             character_style,
             text_style,
-        )
-        .draw(target)
-        .unwrap();
+        );
+        let text_left = text.bounding_box().top_left.x;
+        let text_top = self.background.get_bounding_box().center().y - (NORMAL_FONT.character_size.height / 2) as i32;
+        // the selected text is drawn above the selection
+        let selection = match self.get_selection() {
+            Some((start, end)) if in_edition => Rectangle::new(
+                Point::new(text_left + text_size(&self.value[..start]).width as i32, text_top),
+                Size::new(
+                    text_size(&self.value[start..end]).width,
+                    NORMAL_FONT.character_size.height,
+                ),
+            )
+            .into_styled(PrimitiveStyle::with_fill(theme.active))
+            .draw(target)
+            .map_err(|_| RenderError::DrawTarget),
+            _ => Ok(()),
+        };
+        let cursor = if in_edition && self.cursor_visible {
+            let position = Point::new(
+                text_left + text_size(&self.value[..self.get_cursor()]).width as i32,
+                text_top,
+            );
+            Rectangle::new(position, Size::new(1, NORMAL_FONT.character_size.height))
+                .into_styled(PrimitiveStyle::with_fill(theme.active))
+                .draw(target)
+                .map_err(|_| RenderError::DrawTarget)
+        } else {
+            Ok(())
+        };
+        let text = text.draw(target).map(|_| ()).map_err(|_| RenderError::DrawTarget);
+        text.and(background).and(selection).and(cursor)
     }
 }
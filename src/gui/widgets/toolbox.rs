@@ -0,0 +1,346 @@
+use core::fmt::{self, Write};
+
+use super::{ListView, Modal, RowProvider, ROW_HEIGHT};
+use crate::calculator::{Color, Event, KeyCode};
+use crate::gui::text::{TextInputContext, POPUP_TEXT_CAPACITY};
+use crate::gui::theme::Theme;
+use crate::gui::{FocusFrom, RenderError, SizeHint, Widget, NORMAL_FONT, TEXT_PADDING};
+
+use embedded_graphics::{
+    mono_font::MonoTextStyle,
+    prelude::*,
+    primitives::{PrimitiveStyleBuilder, Rectangle},
+    text::{Baseline, Text},
+};
+use heapless::{String, Vec};
+
+/// Maximum number of nested submenus in a [`Toolbox`].
+pub const TOOLBOX_DEPTH: usize = 4;
+
+/// An entry of a [`Toolbox`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToolboxEntry {
+    /// Text inserted when the entry is chosen.
+    Snippet {
+        /// Text shown in the menu.
+        label: &'static str,
+        /// Text inserted, truncated to [`POPUP_TEXT_CAPACITY`] bytes.
+        text: &'static str,
+    },
+    /// A submenu.
+    Menu {
+        /// Text shown in the menu, and as the title of the submenu.
+        label: &'static str,
+        /// Entries of the submenu.
+        entries: &'static [ToolboxEntry],
+    },
+}
+impl ToolboxEntry {
+    /// Text shown in the menu.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Snippet { label, .. } | Self::Menu { label, .. } => label,
+        }
+    }
+}
+
+const fn snippet(label: &'static str, text: &'static str) -> ToolboxEntry {
+    ToolboxEntry::Snippet { label, text }
+}
+const fn menu(label: &'static str, entries: &'static [ToolboxEntry]) -> ToolboxEntry {
+    ToolboxEntry::Menu { label, entries }
+}
+
+/// Entries of the toolbox of the calculator.
+pub const DEFAULT_TOOLBOX: &[ToolboxEntry] = &[
+    snippet("abs(x)", "abs("),
+    snippet("root(x,n)", "root("),
+    snippet("log(x,a)", "log("),
+    menu(
+        "Calculation",
+        &[
+            snippet("diff(f(x),x,a)", "diff("),
+            snippet("int(f(x),x,a,b)", "int("),
+            snippet("sum(f(k),k,m,n)", "sum("),
+            snippet("product(f(k),k,m,n)", "product("),
+        ],
+    ),
+    menu(
+        "Complex numbers",
+        &[
+            snippet("abs(z)", "abs("),
+            snippet("arg(z)", "arg("),
+            snippet("re(z)", "re("),
+            snippet("im(z)", "im("),
+            snippet("conj(z)", "conj("),
+        ],
+    ),
+    menu(
+        "Probability",
+        &[
+            menu(
+                "Combinatorics",
+                &[
+                    snippet("binomial(n,k)", "binomial("),
+                    snippet("permute(n,k)", "permute("),
+                    snippet("n!", "!"),
+                ],
+            ),
+            snippet("random()", "random()"),
+            snippet("randint(a,b)", "randint("),
+        ],
+    ),
+    menu(
+        "Arithmetic",
+        &[
+            snippet("gcd(p,q)", "gcd("),
+            snippet("lcm(p,q)", "lcm("),
+            snippet("rem(p,q)", "rem("),
+            snippet("quo(p,q)", "quo("),
+            snippet("factor(n)", "factor("),
+        ],
+    ),
+    menu(
+        "Approximation",
+        &[
+            snippet("floor(x)", "floor("),
+            snippet("ceil(x)", "ceil("),
+            snippet("frac(x)", "frac("),
+            snippet("round(x,n)", "round("),
+        ],
+    ),
+    menu(
+        "Hyperbolic trigonometry",
+        &[
+            snippet("sinh(x)", "sinh("),
+            snippet("cosh(x)", "cosh("),
+            snippet("tanh(x)", "tanh("),
+            snippet("asinh(x)", "asinh("),
+            snippet("acosh(x)", "acosh("),
+            snippet("atanh(x)", "atanh("),
+        ],
+    ),
+];
+
+/// Rows of the menu being shown.
+#[derive(Debug, Clone, Copy)]
+struct Entries(&'static [ToolboxEntry]);
+impl RowProvider for Entries {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+    fn write_row(&self, n: usize, text: &mut dyn Write) -> fmt::Result {
+        match self.0[n] {
+            ToolboxEntry::Snippet { label, .. } => text.write_str(label),
+            ToolboxEntry::Menu { label, .. } => write!(text, "{} >", label),
+        }
+    }
+}
+
+/// A popup menu of snippets of text, organized in submenus.
+///
+/// Up and Down select an entry, Ok, Exe or Right open the selected submenu,
+/// and Back or Left go back to the parent menu.
+/// Choosing a snippet with Ok or Exe gives it to the text field which asked for the toolbox,
+/// through [`Popups::choose()`](crate::gui::text::Popups::choose()), and dismisses the toolbox.
+/// The text field inserts it once the toolbox has been removed from the [`OverlayStack`](super::OverlayStack).
+/// Back in the top menu dismisses the toolbox without choosing anything.
+///
+/// The toolbox starts again from the top menu when it is focused.
+///
+/// A [`TextBox`](super::TextBox) asks for a toolbox when Toolbox is pressed,
+/// and the application shows it, f.e. by pushing it onto an [`OverlayStack`](super::OverlayStack).
+///
+/// `T` is the type of [`Widget::Context`].
+#[derive(Debug, Clone)]
+pub struct Toolbox<T> {
+    root: &'static [ToolboxEntry],
+    path: Vec<usize, TOOLBOX_DEPTH>, // entry selected in each parent menu
+    list: ListView<Entries, T>,
+    dismissed: bool,
+    bounding_box: Rectangle,
+    dirty: bool, // the whole toolbox must be rendered again
+}
+impl<T> Toolbox<T> {
+    /// Create a new toolbox showing the given entries, e.g. [`DEFAULT_TOOLBOX`].
+    ///
+    /// Its bounding box is set when it is shown, f.e. by [`OverlayStack::push()`](super::OverlayStack::push()).
+    pub fn new(entries: &'static [ToolboxEntry]) -> Self {
+        Self {
+            root: entries,
+            path: Vec::new(),
+            list: ListView::new(Entries(entries), Rectangle::default(), |_, _| ()),
+            dismissed: false,
+            bounding_box: Rectangle::default(),
+            dirty: true,
+        }
+    }
+
+    /// Entries of the menu being shown.
+    pub fn get_entries(&self) -> &'static [ToolboxEntry] {
+        self.list.get_provider().0
+    }
+    /// Id of the selected entry in the menu being shown.
+    pub fn get_selected(&self) -> usize {
+        self.list.get_selected()
+    }
+    /// Number of submenus opened.
+    pub fn get_depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Title and entries of the menu at the end of `path`.
+    fn current_menu(&self) -> (&'static str, &'static [ToolboxEntry]) {
+        let mut menu = ("Toolbox", self.root);
+        for n in self.path.iter() {
+            if let ToolboxEntry::Menu { label, entries } = menu.1[*n] {
+                menu = (label, entries);
+            }
+        }
+        menu
+    }
+    /// Show other entries, selecting the `selected`-th one.
+    fn show(&mut self, entries: &'static [ToolboxEntry], selected: usize) {
+        self.list.get_provider_mut().0 = entries;
+        self.list.rows_changed();
+        self.list.set_selected(selected);
+        self.dirty = true;
+    }
+    /// Go back to the parent menu, or dismiss the toolbox from the top menu.
+    fn back(&mut self) {
+        match self.path.pop() {
+            Some(selected) => self.show(self.current_menu().1, selected),
+            None => self.dismissed = true,
+        }
+    }
+}
+impl<T: TextInputContext> Toolbox<T> {
+    /// Open the selected submenu, or choose the selected snippet.
+    fn activate(&mut self, context: &mut T) {
+        let selected = self.list.get_selected();
+        match self.get_entries().get(selected) {
+            Some(ToolboxEntry::Menu { entries, .. }) if !self.path.is_full() => {
+                // can't fail: we already checked that there is some space left
+                self.path.push(selected).unwrap_or_default();
+                self.show(entries, 0);
+            }
+            Some(ToolboxEntry::Snippet { text, .. }) => {
+                if let Some(popups) = context.get_popups() {
+                    let mut chosen = String::<POPUP_TEXT_CAPACITY>::new();
+                    for c in text.chars() {
+                        if chosen.push(c).is_err() {
+                            break;
+                        }
+                    }
+                    popups.choose(chosen);
+                }
+                self.dismissed = true;
+            }
+            _ => (), // the submenus are too deep
+        }
+    }
+}
+impl<T: TextInputContext> Modal for Toolbox<T> {
+    fn is_dismissed(&self) -> bool {
+        self.dismissed
+    }
+}
+impl<T: TextInputContext> Widget for Toolbox<T> {
+    type Context = T;
+
+    fn render<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        let style = PrimitiveStyleBuilder::new()
+            .fill_color(theme.background)
+            .stroke_color(theme.foreground)
+            .stroke_width(theme.rect_border)
+            .build();
+        let frame = self
+            .bounding_box
+            .into_styled(style)
+            .draw(target)
+            .map_err(|_| RenderError::DrawTarget);
+        let title = Text::with_baseline(
+            self.current_menu().0,
+            Point::new(
+                self.list.get_bounding_box().top_left.x + TEXT_PADDING as i32,
+                self.bounding_box.top_left.y + (ROW_HEIGHT / 2) as i32,
+            ),
+            MonoTextStyle::new(&NORMAL_FONT, theme.focused),
+            Baseline::Middle,
+        )
+        .draw(&mut target.clipped(&self.bounding_box))
+        .map(|_| ())
+        .map_err(|_| RenderError::DrawTarget);
+        frame.and(title).and(self.list.render(target, theme))
+    }
+    fn render_dirty<D>(&self, target: &mut D, theme: &Theme) -> Result<(), RenderError>
+    where
+        D: DrawTarget<Color = Color>,
+    {
+        if self.dirty {
+            return self.render(target, theme);
+        }
+        self.list.render_dirty(target, theme)
+    }
+    fn on_event(&mut self, e: Event, context: &mut T) -> Option<Event> {
+        match e {
+            Event::KeyDown(KeyCode::Ok | KeyCode::Exe) => self.activate(context),
+            Event::KeyDown(KeyCode::Right) => {
+                if let Some(ToolboxEntry::Menu { .. }) = self.get_entries().get(self.list.get_selected()) {
+                    self.activate(context);
+                }
+            }
+            Event::KeyDown(KeyCode::Back) => self.back(),
+            Event::KeyDown(KeyCode::Left) if !self.path.is_empty() => self.back(),
+            _ => return self.list.on_event(e, context),
+        }
+        None
+    }
+    /// The title is shown above the entries, inside a border.
+    fn set_bounding_box(&mut self, bounding_box: Rectangle) {
+        self.bounding_box = bounding_box;
+        self.dirty = true;
+        // room for the border of the default theme
+        let border = Size::new(TEXT_PADDING / 2, TEXT_PADDING / 2);
+        let title = Size::new(0, ROW_HEIGHT);
+        self.list.set_bounding_box(Rectangle::new(
+            bounding_box.top_left + border + title,
+            bounding_box.size.saturating_sub(border * 2 + title),
+        ));
+    }
+    fn get_bounding_box(&self) -> Rectangle {
+        self.bounding_box
+    }
+    fn measure(&self, available: Size) -> SizeHint {
+        SizeHint::new(Size::new(0, 2 * ROW_HEIGHT), available, available)
+    }
+    fn get_dirty_area(&self) -> Option<Rectangle> {
+        if self.dirty {
+            Some(self.bounding_box)
+        } else {
+            self.list.get_dirty_area()
+        }
+    }
+    fn mark_clean(&mut self) {
+        self.dirty = false;
+        self.list.mark_clean();
+    }
+    fn get_focus(&self) -> Option<bool> {
+        self.list.get_focus()
+    }
+    fn get_focused_area(&self) -> Option<Rectangle> {
+        self.list.get_focused_area()
+    }
+    /// The toolbox is reset to its top menu when it is focused.
+    fn set_focus(&mut self, from_dir: Option<FocusFrom>) -> Result<(), ()> {
+        if from_dir.is_some() {
+            self.path.clear();
+            self.dismissed = false;
+            self.show(self.root, 0);
+        }
+        self.list.set_focus(from_dir)
+    }
+}
@@ -1,5 +1,10 @@
 #![no_std]
 
+#[cfg(any(feature = "simulator", feature = "snapshot"))]
+extern crate std;
+
 pub mod app;
 pub mod calculator;
 pub mod gui;
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
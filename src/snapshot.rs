@@ -0,0 +1,254 @@
+//! Golden-image testing of the rendered UI.
+//!
+//! A [`Snapshot`] is a copy of an area of a [`Framebuffer`](crate::calculator::Framebuffer).
+//! It can be compared with a reference image stored as a binary PPM file.
+//!
+//! A missing reference image is an error, so that a forgotten reference doesn't make the test pass.
+//! Setting the `NW_GUI_UPDATE_SNAPSHOTS` environment variable creates the missing references
+//! and overwrites the existing ones.
+//! On mismatch, the rendered image and a diff image are written next to the reference,
+//! as `<name>.actual.ppm` and `<name>.diff.ppm`.
+//!
+//! This module requires the `snapshot` feature.
+//!
+//! # Example
+//! ```no_run
+//! use embedded_graphics::{prelude::*, primitives::Rectangle};
+//! use nw_gui::calculator::{Color, Framebuffer, PIXEL_COUNT};
+//! use nw_gui::gui::{theme::Theme, widgets::ColorRect, Widget};
+//! use nw_gui::snapshot::assert_snapshot;
+//!
+//! let area = Rectangle::new(Point::new(0, 0), Size::new(40, 20));
+//! let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+//! let mut display = Framebuffer::new(&mut pixels);
+//! ColorRect::<()>::new(area).render(&mut display, &Theme::default()).unwrap();
+//! assert_snapshot(&display, area, "tests/snapshots/color_rect.ppm");
+//! ```
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::vec::Vec;
+
+use embedded_graphics::{pixelcolor::RgbColor, prelude::*, primitives::Rectangle};
+
+use crate::calculator::{Color, Framebuffer};
+
+/// Environment variable forcing references to be created or overwritten.
+pub const UPDATE_ENV_VAR: &str = "NW_GUI_UPDATE_SNAPSHOTS";
+
+/// An image captured from the display.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    size: Size,
+    pixels: Vec<Color>,
+}
+impl Snapshot {
+    /// Capture the given area of the display.
+    ///
+    /// Pixels outside of the display are black.
+    pub fn capture(display: &Framebuffer<'_>, area: Rectangle) -> Self {
+        let pixels = area
+            .points()
+            .map(|point| display.get_pixel(point).unwrap_or(Color::BLACK))
+            .collect();
+        Self {
+            size: area.size,
+            pixels,
+        }
+    }
+
+    /// Size of the image.
+    pub fn size(&self) -> Size {
+        self.size
+    }
+    /// Color of the pixel at the given point, relative to the top-left corner of the image.
+    pub fn get_pixel(&self, point: Point) -> Option<Color> {
+        if point.x < 0 || point.y < 0 || point.x as u32 >= self.size.width || point.y as u32 >= self.size.height {
+            return None;
+        }
+        self.pixels
+            .get(point.y as usize * self.size.width as usize + point.x as usize)
+            .copied()
+    }
+
+    /// Compare two snapshots.
+    ///
+    /// Return `None` if they are identical, otherwise return the number of different pixels
+    /// and a diff image: identical pixels are dimmed, different pixels are red.
+    pub fn diff(&self, other: &Snapshot) -> Option<(usize, Snapshot)> {
+        if self.size != other.size {
+            let pixels = (0..self.pixels.len()).map(|_| Color::RED).collect();
+            return Some((
+                self.pixels.len(),
+                Self {
+                    size: self.size,
+                    pixels,
+                },
+            ));
+        }
+        let mut differences = 0;
+        let pixels = self
+            .pixels
+            .iter()
+            .zip(other.pixels.iter())
+            .map(|(a, b)| {
+                if a == b {
+                    Color::new(a.r() / 4, a.g() / 4, a.b() / 4)
+                } else {
+                    differences += 1;
+                    Color::RED
+                }
+            })
+            .collect();
+        if differences == 0 {
+            None
+        } else {
+            Some((
+                differences,
+                Self {
+                    size: self.size,
+                    pixels,
+                },
+            ))
+        }
+    }
+
+    /// Encode the image as a binary PPM (P6) file.
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut data = std::format!("P6\n{} {}\n255\n", self.size.width, self.size.height).into_bytes();
+        for color in &self.pixels {
+            data.extend_from_slice(&[color.r(), color.g(), color.b()]);
+        }
+        data
+    }
+    /// Decode a binary PPM (P6) file with a maximum value of 255.
+    pub fn from_ppm(data: &[u8]) -> Result<Self, SnapshotError> {
+        let mut fields: Vec<u32> = Vec::new();
+        let mut position = 2;
+        if !data.starts_with(b"P6") {
+            return Err(SnapshotError::InvalidReference);
+        }
+        while fields.len() < 3 {
+            match data.get(position) {
+                Some(b'#') => {
+                    while !matches!(data.get(position), Some(b'\n') | None) {
+                        position += 1;
+                    }
+                }
+                Some(c) if c.is_ascii_whitespace() => position += 1,
+                Some(c) if c.is_ascii_digit() => {
+                    let start = position;
+                    while data.get(position).is_some_and(u8::is_ascii_digit) {
+                        position += 1;
+                    }
+                    let field = std::str::from_utf8(&data[start..position])
+                        .ok()
+                        .and_then(|field| field.parse().ok())
+                        .ok_or(SnapshotError::InvalidReference)?;
+                    fields.push(field);
+                }
+                _ => return Err(SnapshotError::InvalidReference),
+            }
+        }
+        // a single whitespace separates the header from the pixels
+        position += 1;
+        let size = Size::new(fields[0], fields[1]);
+        let pixel_count = (size.width * size.height) as usize;
+        if fields[2] != 255 || data.len() < position + pixel_count * 3 {
+            return Err(SnapshotError::InvalidReference);
+        }
+        let pixels = data[position..position + pixel_count * 3]
+            .chunks_exact(3)
+            .map(|rgb| Color::new(rgb[0], rgb[1], rgb[2]))
+            .collect();
+        Ok(Self { size, pixels })
+    }
+
+    /// Compare the snapshot with the reference image stored at `path`.
+    ///
+    /// See the [module documentation](self) for the files written by this function.
+    pub fn compare_with_reference(&self, path: impl AsRef<Path>) -> Result<(), SnapshotError> {
+        let path = path.as_ref();
+        if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, self.to_ppm())?;
+            return Ok(());
+        }
+        if !path.exists() {
+            return Err(SnapshotError::MissingReference(path.to_path_buf()));
+        }
+        let reference = Self::from_ppm(&fs::read(path)?)?;
+        match self.diff(&reference) {
+            None => Ok(()),
+            Some((different_pixels, diff)) => {
+                let diff_path = path.with_extension("diff.ppm");
+                fs::write(path.with_extension("actual.ppm"), self.to_ppm())?;
+                fs::write(&diff_path, diff.to_ppm())?;
+                Err(SnapshotError::Mismatch {
+                    different_pixels,
+                    diff_path,
+                })
+            }
+        }
+    }
+}
+
+/// Error returned when comparing a [`Snapshot`] with a reference.
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The snapshot differs from the reference.
+    Mismatch {
+        /// Number of pixels that differ.
+        different_pixels: usize,
+        /// Where the diff image was written.
+        diff_path: PathBuf,
+    },
+    /// The reference doesn't exist, and [`UPDATE_ENV_VAR`] isn't set to create it.
+    MissingReference(PathBuf),
+    /// The reference isn't a valid binary PPM file.
+    InvalidReference,
+    /// Reading or writing a file failed.
+    Io(io::Error),
+}
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mismatch {
+                different_pixels,
+                diff_path,
+            } => write!(
+                f,
+                "{} pixels differ from the reference, see {}",
+                different_pixels,
+                diff_path.display()
+            ),
+            Self::MissingReference(path) => write!(
+                f,
+                "the reference {} doesn't exist, set {} to create it",
+                path.display(),
+                UPDATE_ENV_VAR
+            ),
+            Self::InvalidReference => write!(f, "the reference isn't a valid binary PPM file"),
+            Self::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// Capture the given area of the display and compare it with the reference stored at `path`.
+///
+/// # Panics
+/// Panics if the snapshot doesn't match the reference, or if the reference can't be read or written.
+pub fn assert_snapshot(display: &Framebuffer<'_>, area: Rectangle, path: impl AsRef<Path>) {
+    if let Err(e) = Snapshot::capture(display, area).compare_with_reference(path.as_ref()) {
+        panic!("snapshot {} failed: {}", path.as_ref().display(), e);
+    }
+}
@@ -0,0 +1,103 @@
+use embedded_graphics::prelude::*;
+use nw_gui::{
+    calculator::{Color, Framebuffer, KeyCode, PIXEL_COUNT},
+    gui::{theme::Theme, widgets::TextBox, FocusFrom, Widget},
+};
+
+mod common;
+use common::{press, state, text_box, State, LINE};
+
+#[test]
+fn selection_is_copied_to_another_text_box() {
+    let mut state = state();
+    let mut first = text_box();
+    let mut second: TextBox<State, 16> = TextBox::new(LINE, true);
+    press(&mut first, &mut state, &[KeyCode::Num1, KeyCode::Num2, KeyCode::Num3]);
+    press(
+        &mut first,
+        &mut state,
+        &[KeyCode::Shift, KeyCode::Left, KeyCode::Shift, KeyCode::Left],
+    );
+    assert_eq!(first.get_selection(), Some((1, 3)));
+
+    // Shift + Var
+    press(&mut first, &mut state, &[KeyCode::Shift, KeyCode::Var]);
+    assert_eq!(state.clipboard.get(), "23");
+    assert_eq!(first.value, "123");
+
+    first.set_focus(None).unwrap();
+    assert_eq!(first.get_selection(), None);
+    second.set_focus(Some(FocusFrom::Up)).unwrap();
+    // Shift + Toolbox
+    press(&mut second, &mut state, &[KeyCode::Shift, KeyCode::Toolbox]);
+    press(&mut second, &mut state, &[KeyCode::Shift, KeyCode::Toolbox]);
+    assert_eq!(second.value, "2323");
+    assert_eq!(second.get_cursor(), 4);
+}
+
+#[test]
+fn selection_is_cut() {
+    let mut state = state();
+    let mut text_box = text_box();
+    press(
+        &mut text_box,
+        &mut state,
+        &[KeyCode::Num1, KeyCode::Num2, KeyCode::Num3, KeyCode::Left],
+    );
+    // Shift + Up selects up to the start
+    press(&mut text_box, &mut state, &[KeyCode::Shift, KeyCode::Up]);
+    assert_eq!(text_box.get_selection(), Some((0, 2)));
+
+    // Shift + Xnt
+    press(&mut text_box, &mut state, &[KeyCode::Shift, KeyCode::Xnt]);
+    assert_eq!(state.clipboard.get(), "12");
+    assert_eq!(text_box.value, "3");
+    assert_eq!(text_box.get_cursor(), 0);
+    assert_eq!(text_box.get_selection(), None);
+}
+
+#[test]
+fn selection_is_replaced() {
+    let mut state = state();
+    let mut text_box = text_box();
+    press(
+        &mut text_box,
+        &mut state,
+        &[KeyCode::Num1, KeyCode::Num2, KeyCode::Num3, KeyCode::Up],
+    );
+    press(&mut text_box, &mut state, &[KeyCode::Shift, KeyCode::Right]);
+    press(&mut text_box, &mut state, &[KeyCode::Num4]);
+    assert_eq!(text_box.value, "423");
+
+    press(
+        &mut text_box,
+        &mut state,
+        &[KeyCode::Shift, KeyCode::Down, KeyCode::Backspace],
+    );
+    assert_eq!(text_box.value, "4");
+
+    // moving the cursor drops the selection
+    press(
+        &mut text_box,
+        &mut state,
+        &[KeyCode::Shift, KeyCode::Left, KeyCode::Right],
+    );
+    assert_eq!(text_box.get_selection(), None);
+    assert_eq!(text_box.get_cursor(), 1);
+}
+
+#[test]
+fn selection_is_highlighted() {
+    let theme = Theme::default();
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    let mut state = state();
+    let mut text_box = text_box();
+    press(&mut text_box, &mut state, &[KeyCode::Num1, KeyCode::Num2]);
+    press(&mut text_box, &mut state, &[KeyCode::Shift, KeyCode::Left]);
+
+    // "12" is centered from x = 153 to 167, "2" is selected
+    text_box.render(&mut display, &theme).unwrap();
+    assert_eq!(display.get_pixel(Point::new(161, 5)), Some(theme.active));
+    assert_eq!(display.get_pixel(Point::new(159, 5)), Some(theme.background));
+}
@@ -0,0 +1,63 @@
+//! Context and helpers shared by the tests of the text widgets.
+#![allow(dead_code)]
+
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use nw_gui::{
+    calculator::{Event, KeyCode, Timers},
+    gui::{
+        text::{Clipboard, Popups, TextInputContext, TextInputState},
+        widgets::TextBox,
+        FocusFrom, Widget,
+    },
+};
+
+/// One line of text across the screen.
+pub const LINE: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(320, 21));
+
+/// Context providing every service to the text widgets.
+pub struct State {
+    pub input: TextInputState,
+    pub clipboard: Clipboard,
+    pub timers: Timers,
+    pub popups: Popups,
+}
+impl TextInputContext for State {
+    fn get_context(&mut self) -> &mut TextInputState {
+        &mut self.input
+    }
+    fn get_clipboard(&mut self) -> Option<&mut Clipboard> {
+        Some(&mut self.clipboard)
+    }
+    fn get_timers(&mut self) -> Option<&mut Timers> {
+        Some(&mut self.timers)
+    }
+    fn get_popups(&mut self) -> Option<&mut Popups> {
+        Some(&mut self.popups)
+    }
+}
+
+pub fn state() -> State {
+    State {
+        input: TextInputState::new(),
+        clipboard: Clipboard::new(),
+        timers: Timers::new(),
+        popups: Popups::new(),
+    }
+}
+
+/// Editable text box filling [`LINE`], focused.
+pub fn text_box() -> TextBox<State, 16> {
+    let mut text_box = TextBox::new(LINE, true);
+    text_box.set_focus(Some(FocusFrom::Up)).unwrap();
+    text_box
+}
+
+/// Press `keys` one after the other, and return the event left by the last one.
+pub fn press<W: Widget<Context = State>>(widget: &mut W, state: &mut State, keys: &[KeyCode]) -> Option<Event> {
+    let mut remaining_event = None;
+    for key in keys {
+        remaining_event = widget.on_event(Event::KeyDown(*key), state);
+    }
+    remaining_event
+}
@@ -0,0 +1,118 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use nw_gui::{
+    calculator::{Calculator, Color, Event, Framebuffer, HeadlessBackend, KeyCode, PIXEL_COUNT},
+    gui::{
+        theme::Theme,
+        union_area,
+        widgets::{Button, SplitDirection, SplitLayout, TextBox},
+        FocusFrom, Widget,
+    },
+};
+
+use heapless::String;
+
+mod common;
+use common::State;
+
+const SCREEN: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(320, 240));
+const LEFT: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(160, 240));
+const RIGHT: Rectangle = Rectangle::new(Point::new(160, 0), Size::new(160, 240));
+
+fn buttons() -> SplitLayout<Button<()>, Button<()>> {
+    let mut layout = SplitLayout::new(SCREEN, SplitDirection::Horizontal, 0.5);
+    layout.attach_first(Button::new(String::from("First"), Rectangle::default(), |_| ()), (0, 0));
+    layout.attach_second(Button::new(String::from("Second"), Rectangle::default(), |_| ()), (0, 0));
+    layout
+}
+
+#[test]
+fn containers_report_changed_children() {
+    let mut layout = buttons();
+    assert_eq!(layout.get_dirty_area(), Some(SCREEN));
+    layout.mark_clean();
+    assert_eq!(layout.get_dirty_area(), None);
+
+    layout.set_focus(Some(FocusFrom::Left)).unwrap();
+    assert_eq!(layout.get_dirty_area(), Some(LEFT));
+    layout.mark_clean();
+
+    assert_eq!(layout.on_event(Event::KeyDown(KeyCode::Right), &mut ()), None);
+    assert_eq!(layout.get_dirty_area(), Some(SCREEN));
+    layout.mark_clean();
+
+    assert_eq!(layout.on_event(Event::KeyDown(KeyCode::Ok), &mut ()), None);
+    assert_eq!(layout.get_dirty_area(), Some(RIGHT));
+    layout.mark_clean();
+
+    // unused events don't change anything
+    assert_eq!(
+        layout.on_event(Event::KeyDown(KeyCode::Right), &mut ()),
+        Some(Event::KeyDown(KeyCode::Right))
+    );
+    assert_eq!(layout.get_dirty_area(), None);
+}
+
+#[test]
+fn partial_render_matches_full_render() {
+    let theme = Theme::default();
+    let mut partial_pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut partial = Framebuffer::new(&mut partial_pixels);
+    let mut layout = buttons();
+    layout.render_dirty(&mut partial, &theme).unwrap();
+    layout.mark_clean();
+    for e in [
+        Event::KeyDown(KeyCode::Left),
+        Event::KeyDown(KeyCode::Right),
+        Event::KeyDown(KeyCode::Ok),
+    ] {
+        layout.on_event(e, &mut ());
+        layout.render_dirty(&mut partial, &theme).unwrap();
+        layout.mark_clean();
+    }
+
+    let mut full_pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut full = Framebuffer::new(&mut full_pixels);
+    layout.render(&mut full, &theme).unwrap();
+    assert!(partial.iter_pixels().eq(full.iter_pixels()));
+}
+
+#[test]
+fn only_changed_area_is_flushed() {
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut calc = Calculator::new(HeadlessBackend::<4>::new(&mut pixels)).unwrap();
+    calc.get_backend_mut().push_event(Event::KeyDown(KeyCode::Right)).unwrap();
+    let mut layout = buttons();
+    let mut flushed = [None; 3];
+    for flushed in flushed.iter_mut() {
+        if let Some(area) = layout.get_dirty_area() {
+            layout.render_dirty(calc.get_draw_target(), &Theme::default()).unwrap();
+            layout.mark_clean();
+            calc.render_area(area);
+            *flushed = calc.get_backend().get_last_flushed_area();
+        }
+        for e in calc.events() {
+            layout.on_event(e, &mut ());
+        }
+    }
+    assert_eq!(flushed, [Some(SCREEN), Some(RIGHT), None]);
+}
+
+#[test]
+fn text_box_tracks_its_value() {
+    let mut text_box: TextBox<State, 8> = TextBox::new(LEFT, false);
+    text_box.mark_clean();
+    assert_eq!(text_box.get_dirty_area(), None);
+    text_box.value = String::from("status");
+    assert_eq!(text_box.get_dirty_area(), Some(LEFT));
+    text_box.mark_clean();
+    assert_eq!(text_box.get_dirty_area(), None);
+}
+
+#[test]
+fn union_of_areas() {
+    assert_eq!(union_area(Some(LEFT), Some(RIGHT)), Some(SCREEN));
+    assert_eq!(union_area(None, Some(RIGHT)), Some(RIGHT));
+    assert_eq!(union_area(Some(Rectangle::default()), Some(RIGHT)), Some(RIGHT));
+    assert_eq!(union_area(None, None), None);
+}
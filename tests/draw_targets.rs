@@ -0,0 +1,102 @@
+use embedded_graphics::draw_target::DrawTargetExt;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use nw_gui::{
+    calculator::{Color, Framebuffer, PIXEL_COUNT},
+    gui::{
+        theme::Theme,
+        widgets::{Button, SplitDirection, SplitLayout},
+        RenderError, Widget,
+    },
+};
+
+use heapless::String;
+
+const AREA: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(100, 40));
+
+#[test]
+fn translated_target() {
+    let offset = Point::new(50, 60);
+    let theme = Theme::default();
+
+    let mut translated_pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut translated = Framebuffer::new(&mut translated_pixels);
+    let button: Button<()> = Button::new(String::from("Ok"), AREA, |_| ());
+    button.render(&mut translated.translated(offset), &theme).unwrap();
+
+    let mut moved_pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut moved = Framebuffer::new(&mut moved_pixels);
+    let button: Button<()> = Button::new(String::from("Ok"), AREA.translate(offset), |_| ());
+    button.render(&mut moved, &theme).unwrap();
+
+    assert!(translated.iter_pixels().eq(moved.iter_pixels()));
+}
+
+#[test]
+fn clipped_target() {
+    let mut layout: SplitLayout<Button<()>, Button<()>> = SplitLayout::new(
+        Rectangle::new(Point::new(0, 0), Size::new(320, 240)),
+        SplitDirection::Horizontal,
+        0.5,
+    );
+    layout.attach_first(Button::new(String::from("First"), Rectangle::default(), |_| ()), (0, 0));
+    layout.attach_second(Button::new(String::from("Second"), Rectangle::default(), |_| ()), (0, 0));
+
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    let left = Rectangle::new(Point::new(0, 0), Size::new(160, 240));
+    layout.render(&mut display.clipped(&left), &Theme::default()).unwrap();
+
+    assert_eq!(display.get_pixel(Point::new(80, 30)), Some(Theme::default().background));
+    assert_eq!(display.get_pixel(Point::new(240, 30)), Some(Color::BLACK));
+}
+
+/// Target refusing to draw in the left half of the screen.
+struct FailingTarget<'a, 'b>(&'a mut Framebuffer<'b>);
+impl OriginDimensions for FailingTarget<'_, '_> {
+    fn size(&self) -> Size {
+        self.0.size()
+    }
+}
+impl DrawTarget for FailingTarget<'_, '_> {
+    type Color = Color;
+    type Error = ();
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for pixel in pixels {
+            if pixel.0.x < 160 {
+                return Err(());
+            }
+            self.0.draw_iter([pixel]).unwrap();
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn failing_target_doesnt_stop_rendering() {
+    let mut layout: SplitLayout<Button<()>, Button<()>> = SplitLayout::new(
+        Rectangle::new(Point::new(0, 0), Size::new(320, 240)),
+        SplitDirection::Horizontal,
+        0.5,
+    );
+    layout.attach_first(Button::new(String::from("First"), Rectangle::default(), |_| ()), (0, 0));
+    layout.attach_second(
+        Button::new(String::from("Second"), Rectangle::default(), |_| ()),
+        (0, 0),
+    );
+
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    assert_eq!(
+        layout.render(&mut FailingTarget(&mut display), &Theme::default()),
+        Err(RenderError::DrawTarget)
+    );
+    assert_eq!(
+        display.get_pixel(Point::new(240, 30)),
+        Some(Theme::default().background)
+    );
+}
@@ -0,0 +1,197 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use heapless::{String, Vec};
+use nw_gui::{
+    calculator::{Event, KeyCode},
+    gui::{
+        widgets::{Button, ColorRect, FlexAlign, FlexItem, FlexLayout, SplitDirection},
+        FocusFrom, Widget,
+    },
+};
+
+fn button(text: &str) -> Button<()> {
+    Button::new(String::from(text), Rectangle::default(), |_| ())
+}
+
+fn bounding_boxes<const N: usize>(layout: &FlexLayout<Vec<impl Widget, N>>) -> [Rectangle; N] {
+    core::array::from_fn(|n| layout.get(n).unwrap().get_bounding_box())
+}
+
+#[test]
+fn children_grow_up_to_their_maximum_size() {
+    let mut row: FlexLayout<Vec<ColorRect<()>, 3>> = FlexLayout::new(
+        Rectangle::new(Point::zero(), Size::new(300, 20)),
+        SplitDirection::Horizontal,
+        0,
+        Vec::new(),
+    );
+    let item = FlexItem {
+        basis: Some(50),
+        grow: 1,
+        ..FlexItem::default()
+    };
+    row.add_child(ColorRect::new(Rectangle::default()), FlexItem { max: 60, ..item })
+        .unwrap();
+    row.add_child(ColorRect::new(Rectangle::default()), item).unwrap();
+    row.add_child(ColorRect::new(Rectangle::default()), FlexItem { grow: 2, ..item })
+        .unwrap();
+    // the space A can't take is shared between B and C
+    assert_eq!(
+        bounding_boxes(&row),
+        [
+            Rectangle::new(Point::new(0, 0), Size::new(60, 20)),
+            Rectangle::new(Point::new(60, 0), Size::new(96, 20)),
+            Rectangle::new(Point::new(156, 0), Size::new(144, 20)),
+        ]
+    );
+}
+
+#[test]
+fn children_shrink_down_to_their_minimum_size() {
+    let mut row: FlexLayout<Vec<ColorRect<()>, 2>> = FlexLayout::new(
+        Rectangle::new(Point::new(10, 10), Size::new(100, 20)),
+        SplitDirection::Horizontal,
+        0,
+        Vec::new(),
+    );
+    let item = FlexItem {
+        basis: Some(80),
+        ..FlexItem::default()
+    };
+    row.add_child(ColorRect::new(Rectangle::default()), item).unwrap();
+    row.add_child(ColorRect::new(Rectangle::default()), FlexItem { min: 70, ..item })
+        .unwrap();
+    assert_eq!(
+        bounding_boxes(&row),
+        [
+            Rectangle::new(Point::new(10, 10), Size::new(30, 20)),
+            Rectangle::new(Point::new(40, 10), Size::new(70, 20)),
+        ]
+    );
+
+    // children that can't shrink are cut at the end of the layout
+    row.set_item(
+        0,
+        FlexItem {
+            min: 80,
+            ..FlexItem::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        bounding_boxes(&row)[1],
+        Rectangle::new(Point::new(90, 10), Size::new(20, 20))
+    );
+}
+
+#[test]
+fn children_are_aligned_across_the_layout() {
+    let mut row: FlexLayout<Vec<Button<()>, 4>> = FlexLayout::new(
+        Rectangle::new(Point::zero(), Size::new(200, 50)),
+        SplitDirection::Horizontal,
+        0,
+        Vec::new(),
+    );
+    for align in [
+        Some(FlexAlign::Start),
+        Some(FlexAlign::Center),
+        Some(FlexAlign::End),
+        None,
+    ] {
+        let item = FlexItem {
+            basis: Some(50),
+            align,
+            ..FlexItem::default()
+        };
+        row.add_child(button("A"), item).unwrap();
+    }
+    // buttons are 21 pixels high
+    assert_eq!(
+        bounding_boxes(&row),
+        [
+            Rectangle::new(Point::new(0, 0), Size::new(50, 21)),
+            Rectangle::new(Point::new(50, 14), Size::new(50, 21)),
+            Rectangle::new(Point::new(100, 29), Size::new(50, 21)),
+            Rectangle::new(Point::new(150, 0), Size::new(50, 50)),
+        ]
+    );
+
+    row.set_align(FlexAlign::End);
+    assert_eq!(
+        bounding_boxes(&row)[3],
+        Rectangle::new(Point::new(150, 29), Size::new(50, 21))
+    );
+}
+
+fn wrapped() -> FlexLayout<Vec<Button<()>, 5>> {
+    // +----+----+
+    // | 0  | 1  |
+    // +----+----+
+    // | 2  | 3  |
+    // +----+----+
+    // | 4  |
+    // +----+
+    let mut layout = FlexLayout::new(
+        Rectangle::new(Point::zero(), Size::new(100, 240)),
+        SplitDirection::Horizontal,
+        2,
+        Vec::new(),
+    );
+    layout.set_wrap(true);
+    for _ in 0..5 {
+        let item = FlexItem {
+            basis: Some(40),
+            ..FlexItem::default()
+        };
+        layout.add_child(button("A"), item).unwrap();
+    }
+    layout
+}
+
+#[test]
+fn children_wrap_on_several_lines() {
+    let layout = wrapped();
+    assert_eq!(
+        bounding_boxes(&layout),
+        [
+            Rectangle::new(Point::new(0, 0), Size::new(40, 21)),
+            Rectangle::new(Point::new(42, 0), Size::new(40, 21)),
+            Rectangle::new(Point::new(0, 23), Size::new(40, 21)),
+            Rectangle::new(Point::new(42, 23), Size::new(40, 21)),
+            Rectangle::new(Point::new(0, 46), Size::new(40, 21)),
+        ]
+    );
+    assert_eq!(layout.measure(Size::new(100, 240)).preferred, Size::new(82, 67));
+}
+
+#[test]
+fn arrows_move_focus_to_the_nearest_child() {
+    let mut layout = wrapped();
+    assert_eq!(
+        layout.on_event(Event::KeyDown(KeyCode::Down), &mut ()),
+        Some(Event::KeyDown(KeyCode::Down))
+    );
+    layout.set_focus(Some(FocusFrom::Up)).unwrap();
+    assert_eq!(layout.get_focused_child(), Some(0));
+
+    assert_eq!(layout.on_event(Event::KeyDown(KeyCode::Right), &mut ()), None);
+    assert_eq!(layout.get_focused_child(), Some(1));
+    assert_eq!(layout.get(0).unwrap().get_focus(), Some(false));
+
+    assert_eq!(layout.on_event(Event::KeyDown(KeyCode::Down), &mut ()), None);
+    assert_eq!(layout.get_focused_child(), Some(3));
+    assert_eq!(layout.on_event(Event::KeyRepeat(KeyCode::Down), &mut ()), None);
+    assert_eq!(layout.get_focused_child(), Some(4));
+
+    assert_eq!(
+        layout.on_event(Event::KeyDown(KeyCode::Left), &mut ()),
+        Some(Event::KeyDown(KeyCode::Left))
+    );
+    assert_eq!(layout.on_event(Event::KeyDown(KeyCode::Up), &mut ()), None);
+    assert_eq!(layout.get_focused_child(), Some(2));
+
+    layout.set_focus(None).unwrap();
+    assert_eq!(layout.get(2).unwrap().get_focus(), Some(false));
+    layout.set_focus(Some(FocusFrom::Down)).unwrap();
+    assert_eq!(layout.get_focused_child(), Some(4));
+}
@@ -0,0 +1,219 @@
+use either::Either;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use heapless::{String, Vec};
+use nw_gui::{
+    calculator::{Event, KeyCode},
+    gui::{
+        union_area,
+        widgets::{Button, ColorRect, Grid},
+        FocusFrom, Widget,
+    },
+};
+
+type Cell = Either<Button<()>, ColorRect<()>>;
+
+const GRID: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(300, 200));
+
+fn button(text: &str) -> Cell {
+    Either::Left(Button::new(String::from(text), Rectangle::default(), |_| ()))
+}
+
+// +----+----+----+
+// |    A    | B  |
+// +----+----+----+
+// | C  |    | D  |
+// +----+----+----+
+// C can't be focused
+fn grid() -> Grid<3, 2, Vec<Cell, 4>> {
+    let mut grid = Grid::new(GRID, Vec::new());
+    assert_eq!(grid.add_child_at(button("A"), (0, 0), (2, 1), 0).ok(), Some(0));
+    assert_eq!(grid.add_child_at(button("B"), (2, 0), (1, 1), 0).ok(), Some(1));
+    assert_eq!(
+        grid.add_child_at(Either::Right(ColorRect::new(Rectangle::default())), (0, 1), (1, 1), 0)
+            .ok(),
+        Some(2)
+    );
+    assert_eq!(grid.add_child_at(button("D"), (2, 1), (1, 1), 0).ok(), Some(3));
+    grid
+}
+
+#[test]
+fn focus_enters_from_the_given_side() {
+    let mut grid = grid();
+    assert_eq!(grid.get_focus(), Some(false));
+    assert_eq!(grid.get_focused_child(), None);
+
+    grid.set_focus(Some(FocusFrom::Left)).unwrap();
+    assert_eq!(grid.get_focused_child(), Some(0));
+    assert_eq!(grid.get(0).unwrap().get_focus(), Some(true));
+
+    let mut grid = self::grid();
+    grid.set_focus(Some(FocusFrom::Right)).unwrap();
+    assert_eq!(grid.get_focused_child(), Some(1));
+
+    // C can't be focused, so it's skipped
+    let mut grid = self::grid();
+    grid.set_focus(Some(FocusFrom::Down)).unwrap();
+    assert_eq!(grid.get_focused_child(), Some(3));
+
+    grid.set_focus(None).unwrap();
+    assert_eq!(grid.get_focus(), Some(false));
+    assert_eq!(grid.get(3).unwrap().get_focus(), Some(false));
+}
+
+#[test]
+fn grid_without_focusable_children_refuses_focus() {
+    let mut grid: Grid<2, 1, Vec<ColorRect<()>, 2>> = Grid::new(GRID, Vec::new());
+    grid.add_child_at(ColorRect::new(Rectangle::default()), (0, 0), (1, 1), 0)
+        .unwrap();
+    assert_eq!(grid.set_focus(Some(FocusFrom::Left)), Err(()));
+    assert_eq!(grid.get_focus(), Some(false));
+}
+
+#[test]
+fn arrows_move_focus_between_children() {
+    let mut grid = grid();
+    grid.set_focus(Some(FocusFrom::Left)).unwrap();
+
+    // A spans two cells, which are skipped
+    assert_eq!(grid.on_event(Event::KeyDown(KeyCode::Right), &mut ()), None);
+    assert_eq!(grid.get_focused_child(), Some(1));
+    assert_eq!(grid.get(0).unwrap().get_focus(), Some(false));
+    assert_eq!(grid.get(1).unwrap().get_focus(), Some(true));
+
+    assert_eq!(grid.on_event(Event::KeyDown(KeyCode::Down), &mut ()), None);
+    assert_eq!(grid.get_focused_child(), Some(3));
+
+    // only an empty cell and C are on the left of D,
+    // so the event is given back to the parent
+    assert_eq!(
+        grid.on_event(Event::KeyDown(KeyCode::Left), &mut ()),
+        Some(Event::KeyDown(KeyCode::Left))
+    );
+    assert_eq!(grid.get_focused_child(), Some(3));
+
+    assert_eq!(grid.on_event(Event::KeyDown(KeyCode::Up), &mut ()), None);
+    assert_eq!(grid.on_event(Event::KeyRepeat(KeyCode::Left), &mut ()), None);
+    assert_eq!(grid.get_focused_child(), Some(0));
+
+    // C, below the left cell of A, can't be focused, and the cell below its right cell is empty
+    assert_eq!(
+        grid.on_event(Event::KeyDown(KeyCode::Down), &mut ()),
+        Some(Event::KeyDown(KeyCode::Down))
+    );
+    assert_eq!(grid.get_focused_child(), Some(0));
+}
+
+#[test]
+fn focus_leaves_a_spanning_child_from_any_of_its_cells() {
+    // +----+----+
+    // | A  | B  |
+    // +----+----+
+    // |    W    |
+    // +----+----+
+    // |    | Z  |
+    // +----+----+
+    let mut grid: Grid<2, 3, Vec<Cell, 4>> = Grid::new(GRID, Vec::new());
+    grid.add_child_at(button("A"), (0, 0), (1, 1), 0).unwrap();
+    grid.add_child_at(button("B"), (1, 0), (1, 1), 0).unwrap();
+    grid.add_child_at(button("W"), (0, 1), (2, 1), 0).unwrap();
+    grid.add_child_at(button("Z"), (1, 2), (1, 1), 0).unwrap();
+    grid.set_focus(Some(FocusFrom::Left)).unwrap();
+    assert_eq!(grid.get_focused_child(), Some(0));
+
+    for (above, key) in [(0, KeyCode::Left), (1, KeyCode::Right)] {
+        // W is entered through the cell below A, then through the cell below B
+        grid.on_event(Event::KeyDown(key), &mut ());
+        assert_eq!(grid.get_focused_child(), Some(above));
+        assert_eq!(grid.on_event(Event::KeyDown(KeyCode::Down), &mut ()), None);
+        assert_eq!(grid.get_focused_child(), Some(2));
+        // the cell below is searched across the whole width of W
+        assert_eq!(grid.on_event(Event::KeyDown(KeyCode::Down), &mut ()), None);
+        assert_eq!(grid.get_focused_child(), Some(3));
+
+        // going back up, the cell above Z is preferred
+        assert_eq!(grid.on_event(Event::KeyDown(KeyCode::Up), &mut ()), None);
+        assert_eq!(grid.on_event(Event::KeyDown(KeyCode::Up), &mut ()), None);
+        assert_eq!(grid.get_focused_child(), Some(1));
+    }
+}
+
+#[test]
+fn unfocused_grid_ignores_events() {
+    let mut grid = grid();
+    assert_eq!(
+        grid.on_event(Event::KeyDown(KeyCode::Right), &mut ()),
+        Some(Event::KeyDown(KeyCode::Right))
+    );
+    assert_eq!(grid.get_focused_child(), None);
+}
+
+#[test]
+fn children_cant_overlap_or_overflow() {
+    let mut grid = grid();
+    assert!(grid.add_child_at(button("E"), (1, 1), (2, 1), 0).is_err());
+    assert!(grid.add_child_at(button("E"), (1, 1), (1, 2), 0).is_err());
+    assert!(grid.add_child_at(button("E"), (3, 0), (1, 1), 0).is_err());
+
+    let mut grid: Grid<2, 1, Vec<Cell, 2>> = Grid::new(GRID, Vec::new());
+    grid.add_child_at(button("A"), (0, 0), (1, 1), 0).unwrap();
+    grid.add_child_at(button("B"), (1, 0), (1, 1), 0).unwrap();
+}
+
+#[test]
+fn children_are_laid_out_again_on_resize() {
+    let mut grid: Grid<3, 2, Vec<Cell, 2>> = Grid::new(GRID, Vec::new());
+    grid.add_child_at(button("A"), (0, 0), (2, 1), 10).unwrap();
+    grid.add_child_at(button("B"), (2, 1), (1, 1), 0).unwrap();
+    assert_eq!(
+        grid.get(0).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(10, 10), Size::new(180, 80))
+    );
+    assert_eq!(
+        grid.get(1).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(200, 100), Size::new(100, 100))
+    );
+
+    grid.set_bounding_box(Rectangle::new(Point::new(20, 40), Size::new(150, 60)));
+    assert_eq!(
+        grid.get(0).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(30, 50), Size::new(80, 10))
+    );
+    assert_eq!(
+        grid.get(1).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(120, 70), Size::new(50, 30))
+    );
+}
+
+#[test]
+fn padding_is_clamped_in_both_directions() {
+    let mut grid: Grid<1, 1, Vec<Cell, 1>> =
+        Grid::new(Rectangle::new(Point::new(0, 0), Size::new(100, 40)), Vec::new());
+    grid.add_child_at(button("A"), (0, 0), (1, 1), 30).unwrap();
+    assert_eq!(
+        grid.get(0).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(30, 20), Size::new(40, 0))
+    );
+}
+
+#[test]
+fn focus_changes_are_reported_as_dirty() {
+    let mut grid = grid();
+    assert_eq!(grid.get_dirty_area(), Some(GRID));
+    grid.mark_clean();
+    assert_eq!(grid.get_dirty_area(), None);
+
+    grid.set_focus(Some(FocusFrom::Left)).unwrap();
+    let a = grid.get(0).unwrap().get_bounding_box();
+    assert_eq!(grid.get_dirty_area(), Some(a));
+    grid.mark_clean();
+
+    grid.on_event(Event::KeyDown(KeyCode::Right), &mut ());
+    let b = grid.get(1).unwrap().get_bounding_box();
+    assert_eq!(grid.get_dirty_area(), union_area(Some(a), Some(b)));
+    grid.mark_clean();
+
+    grid.set_bounding_box(GRID);
+    assert_eq!(grid.get_dirty_area(), Some(GRID));
+}
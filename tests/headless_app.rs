@@ -0,0 +1,146 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use nw_gui::{
+    app::App,
+    calculator::{Backend, Calculator, Color, Event, HeadlessBackend, KeyCode, PIXEL_COUNT},
+    gui::{
+        theme::Theme,
+        widgets::{Button, SplitDirection, SplitLayout},
+        Widget,
+    },
+};
+
+use heapless::String;
+
+#[test]
+fn press_button() {
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut calc = Calculator::new(HeadlessBackend::<8>::new(&mut pixels)).unwrap();
+    let mut app = ButtonsApp::new();
+
+    let backend = calc.get_backend_mut();
+    backend.push_event(Event::KeyDown(KeyCode::Right)).unwrap();
+    backend.push_event_at(1, Event::KeyDown(KeyCode::Ok)).unwrap();
+    backend.push_event_at(1, Event::KeyUp(KeyCode::Ok)).unwrap();
+    backend.set_frame_limit(3);
+    app.run(&mut calc);
+
+    let backend = calc.get_backend();
+    assert_eq!(backend.get_frame(), 3);
+    assert_eq!(backend.pending_events(), 0);
+    assert_eq!(app.state, Some(Color::CSS_DARK_MAGENTA));
+    // inside the second button, away from its border and its text
+    assert_eq!(
+        backend.get_framebuffer().get_pixel(Point::new(250, 30)),
+        Some(Color::CSS_DARK_MAGENTA)
+    );
+    assert_eq!(
+        backend.get_framebuffer().get_pixel(Point::new(0, 0)),
+        Some(Theme::default().foreground)
+    );
+}
+
+#[test]
+fn step_frames() {
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut calc = Calculator::new(HeadlessBackend::<8>::new(&mut pixels)).unwrap();
+    let mut app = ButtonsApp::new();
+
+    calc.get_backend_mut().push_event_at(2, Event::KeyDown(KeyCode::Ok)).unwrap();
+    calc.get_backend_mut().set_frame_limit(2);
+    app.run(&mut calc);
+    assert_eq!(calc.get_backend().pending_events(), 1);
+    assert_eq!(app.state, None);
+
+    calc.get_backend_mut().set_frame_limit(2);
+    app.run(&mut calc);
+    assert_eq!(calc.get_backend().pending_events(), 0);
+    assert_eq!(app.state, Some(Color::CSS_DARK_ORCHID));
+}
+
+#[test]
+fn frame_limit_counts_flushes() {
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut calc = Calculator::new(HeadlessBackend::<1>::new(&mut pixels)).unwrap();
+    calc.get_backend_mut().set_frame_limit(3);
+    let mut flushes = 0;
+    'running: loop {
+        calc.render();
+        flushes += 1;
+        for e in calc.events() {
+            if e == Event::HardQuit {
+                break 'running;
+            }
+        }
+    }
+    assert_eq!(flushes, 3);
+    assert_eq!(calc.get_backend().get_frame(), 3);
+    // the application is asked to quit only once
+    calc.render();
+    assert_eq!(calc.events().next(), None);
+}
+
+#[test]
+fn events_are_scheduled_in_order() {
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut backend = HeadlessBackend::<4>::new(&mut pixels);
+    backend.push_event_at(2, Event::KeyDown(KeyCode::Ok)).unwrap();
+    backend.push_event_at(2, Event::KeyUp(KeyCode::Ok)).unwrap();
+    assert_eq!(
+        backend.push_event_at(1, Event::KeyDown(KeyCode::Back)),
+        Err(Event::KeyDown(KeyCode::Back))
+    );
+    assert_eq!(
+        backend.push_event(Event::KeyDown(KeyCode::Back)),
+        Err(Event::KeyDown(KeyCode::Back))
+    );
+    assert_eq!(backend.pending_events(), 2);
+}
+
+struct ButtonsApp {
+    gui: SplitLayout<Button<Option<Color>>, Button<Option<Color>>>,
+    state: Option<Color>,
+    theme: Theme,
+}
+
+impl App for ButtonsApp {
+    fn new() -> Self {
+        let mut app = Self {
+            gui: SplitLayout::new(
+                Rectangle::new(Point::new(0, 0), Size::new(320, 240)),
+                SplitDirection::Horizontal,
+                0.5,
+            ),
+            state: None,
+            theme: Theme::default(),
+        };
+        app.gui.attach_first(
+            Button::new(String::from("First"), Rectangle::default(), |state| {
+                *state = Some(Color::CSS_DARK_ORCHID);
+            }),
+            (0, 0),
+        );
+        app.gui.attach_second(
+            Button::new(String::from("Second"), Rectangle::default(), |state| {
+                *state = Some(Color::CSS_DARK_MAGENTA);
+            }),
+            (0, 0),
+        );
+        app.gui.set_focus(Some(nw_gui::gui::FocusFrom::Left)).unwrap();
+        app
+    }
+    fn run<B: Backend>(&mut self, calc: &mut Calculator<B>) {
+        'running: loop {
+            self.gui.render(calc.get_draw_target(), &self.theme).unwrap();
+            calc.render();
+            for e in calc.events() {
+                if let Some(Event::HardQuit) = self.gui.on_event(e, &mut self.state) {
+                    break 'running;
+                }
+                if let Some(color) = self.state {
+                    self.theme.background = color;
+                }
+            }
+        }
+    }
+}
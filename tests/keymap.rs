@@ -0,0 +1,87 @@
+mod common;
+
+use nw_gui::calculator::{KeyCode, Keymap, KeymapError, KeymapErrorKind};
+
+fn resolve(name: &str) -> Option<char> {
+    let mut chars = name.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+#[test]
+fn rebind_and_unbind() {
+    let mut keymap: Keymap<char, 4> = Keymap::new();
+    keymap.bind('a', KeyCode::Alpha).unwrap();
+    keymap.bind('s', KeyCode::Shift).unwrap();
+    keymap.bind('a', KeyCode::Ans).unwrap();
+    keymap.bind('y', KeyCode::Ans).unwrap();
+    assert_eq!(keymap.get('a'), Some(KeyCode::Ans));
+    assert_eq!(keymap.keys_for(KeyCode::Ans).collect::<heapless::Vec<char, 4>>(), ['a', 'y']);
+
+    keymap.unbind('a');
+    assert_eq!(keymap.get('a'), None);
+    assert_eq!(keymap.get('s'), Some(KeyCode::Shift));
+}
+
+#[test]
+fn parse_errors() {
+    let mut keymap: Keymap<char, 2> = Keymap::new();
+    assert_eq!(
+        keymap.parse_bindings("\n= = Plus\n- Minus\n", resolve),
+        Err(KeymapError {
+            line: 3,
+            kind: KeymapErrorKind::Syntax
+        })
+    );
+    assert_eq!(keymap.get('='), Some(KeyCode::Plus));
+    assert_eq!(
+        keymap.parse_bindings("plus = Plus", resolve).unwrap_err().kind,
+        KeymapErrorKind::UnknownHostKey
+    );
+    assert_eq!(
+        keymap.parse_bindings("p = Add", resolve).unwrap_err().kind,
+        KeymapErrorKind::UnknownKeyCode
+    );
+    assert_eq!(
+        keymap.parse_bindings("# full\nb = Back\nc = Comma", resolve),
+        Err(KeymapError {
+            line: 3,
+            kind: KeymapErrorKind::Full
+        })
+    );
+}
+
+#[cfg(feature = "simulator")]
+#[test]
+fn sdl_bindings_cover_every_key() {
+    use nw_gui::calculator::DEFAULT_SDL_BINDINGS;
+
+    for key in KeyCode::ALL {
+        assert!(
+            DEFAULT_SDL_BINDINGS.iter().any(|(_, code)| *code == key),
+            "{:?} isn't bound",
+            key
+        );
+    }
+}
+
+#[cfg(feature = "simulator")]
+#[test]
+fn host_shift_types_symbols() {
+    use embedded_graphics_simulator::sdl2::Keycode;
+    use nw_gui::calculator::Event;
+    use nw_gui::gui::Widget;
+
+    // `+` is typed with Shift and `=` on a US layout
+    let keymap = Keymap::sdl_default();
+    let mut text_box = common::text_box();
+    let mut state = common::state();
+    for key in [Keycode::LShift, Keycode::Equals] {
+        if let Some(code) = keymap.get(key) {
+            text_box.on_event(Event::KeyDown(code), &mut state);
+        }
+    }
+    assert_eq!(text_box.value, "+");
+}
@@ -0,0 +1,65 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use heapless::String;
+use nw_gui::{
+    calculator::{Color, Framebuffer, PIXEL_COUNT},
+    gui::{
+        theme::Theme,
+        widgets::{HorizontalAlign, Label, VerticalAlign},
+        Widget,
+    },
+};
+
+// 10 columns and 2 lines
+const AREA: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(78, 34));
+
+fn label(text: &str, wrap: bool) -> Label<(), 32> {
+    let mut label = Label::new(String::from(text), AREA);
+    label.set_wrap(wrap);
+    label
+}
+
+/// Colors of the pixels of a label.
+fn pixels(label: &Label<(), 32>) -> Vec<Option<Color>> {
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    label.render(&mut display, &Theme::default()).unwrap();
+    AREA.points().map(|point| display.get_pixel(point)).collect()
+}
+
+/// Whether both labels are rendered the same way.
+fn same_pixels(first: &Label<(), 32>, second: &Label<(), 32>) -> bool {
+    pixels(first) == pixels(second)
+}
+
+#[test]
+fn long_text_ends_with_ellipsis() {
+    assert!(same_pixels(&label("0123456789ab", false), &label("0123456...", false)));
+    assert!(!same_pixels(&label("0123456789", false), &label("0123456...", false)));
+    // the second line isn't shown
+    assert!(same_pixels(&label("0\n1", false), &label("0...", false)));
+}
+
+#[test]
+fn text_is_aligned() {
+    let mut right = label("ab", false);
+    right.set_alignment(HorizontalAlign::Right, VerticalAlign::Middle);
+    assert!(same_pixels(&right, &label("        ab", false)));
+
+    let mut bottom = label("ab", true);
+    bottom.set_alignment(HorizontalAlign::Left, VerticalAlign::Bottom);
+    let mut top = label("\nab", true);
+    top.set_alignment(HorizontalAlign::Left, VerticalAlign::Top);
+    assert!(same_pixels(&bottom, &top));
+}
+
+#[test]
+fn text_is_wrapped() {
+    let mut wrapped = label("one two three", true);
+    assert!(same_pixels(&wrapped, &label("one two\nthree", true)));
+    assert_eq!(wrapped.measure(AREA.size).preferred, AREA.size);
+
+    // the last line shown ends with an ellipsis
+    wrapped.set_text(String::from("one two three four five"));
+    assert!(same_pixels(&wrapped, &label("one two\nthree f...", true)));
+}
@@ -0,0 +1,156 @@
+use either::Either;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use heapless::{String, Vec};
+use nw_gui::{
+    calculator::{Event, KeyCode},
+    gui::{
+        widgets::{Button, ChildSize, ColorRect, LinearLayout, SplitDirection},
+        FocusFrom, Widget,
+    },
+};
+
+type Child = Either<Button<()>, ColorRect<()>>;
+
+const SCREEN: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(320, 240));
+
+fn button(text: &str) -> Child {
+    Either::Left(Button::new(String::from(text), Rectangle::default(), |_| ()))
+}
+fn rect() -> Child {
+    Either::Right(ColorRect::new(Rectangle::default()))
+}
+
+#[test]
+fn children_share_the_free_space() {
+    let mut row: LinearLayout<Vec<Child, 4>> = LinearLayout::new(SCREEN, SplitDirection::Horizontal, 10, Vec::new());
+    assert_eq!(row.add_child(button("A"), ChildSize::Fixed(40), (0, 0)).ok(), Some(0));
+    assert_eq!(row.add_child(button("B"), ChildSize::Weight(1), (0, 0)).ok(), Some(1));
+    assert_eq!(row.add_child(button("C"), ChildSize::Weight(2), (0, 0)).ok(), Some(2));
+    assert_eq!(row.add_child(button("D"), ChildSize::Weight(2), (5, 5)).ok(), Some(3));
+    assert!(row.add_child(button("E"), ChildSize::Weight(2), (0, 0)).is_err());
+
+    // 320 - 40 - 3 * 10 = 250 pixels for 5 weight units
+    let boxes: [Rectangle; 4] = core::array::from_fn(|n| row.get(n).unwrap().get_bounding_box());
+    assert_eq!(
+        boxes,
+        [
+            Rectangle::new(Point::new(0, 0), Size::new(40, 240)),
+            Rectangle::new(Point::new(50, 0), Size::new(50, 240)),
+            Rectangle::new(Point::new(110, 0), Size::new(100, 240)),
+            Rectangle::new(Point::new(225, 5), Size::new(90, 230)),
+        ]
+    );
+
+    row.set_child_size(0, ChildSize::Weight(1)).unwrap();
+    row.set_spacing(0);
+    row.set_bounding_box(Rectangle::new(Point::new(10, 20), Size::new(60, 30)));
+    let boxes: [Rectangle; 4] = core::array::from_fn(|n| row.get(n).unwrap().get_bounding_box());
+    assert_eq!(
+        boxes,
+        [
+            Rectangle::new(Point::new(10, 20), Size::new(10, 30)),
+            Rectangle::new(Point::new(20, 20), Size::new(10, 30)),
+            Rectangle::new(Point::new(30, 20), Size::new(20, 30)),
+            Rectangle::new(Point::new(55, 25), Size::new(10, 20)),
+        ]
+    );
+}
+
+#[test]
+fn rounding_doesnt_leave_gaps() {
+    let mut column: LinearLayout<Vec<Child, 3>> = LinearLayout::new(SCREEN, SplitDirection::Vertical, 0, Vec::new());
+    for _ in 0..3 {
+        column.add_child(button("A"), ChildSize::Weight(1), (0, 0)).unwrap();
+    }
+    let heights: [u32; 3] = core::array::from_fn(|n| column.get(n).unwrap().get_bounding_box().size.height);
+    assert_eq!(heights, [80, 80, 80]);
+
+    column.set_bounding_box(Rectangle::new(Point::zero(), Size::new(320, 100)));
+    let boxes: [Rectangle; 3] = core::array::from_fn(|n| column.get(n).unwrap().get_bounding_box());
+    assert_eq!(boxes[0].top_left.y, 0);
+    assert_eq!(boxes[1].top_left.y, boxes[0].size.height as i32);
+    assert_eq!(boxes[2].top_left.y + boxes[2].size.height as i32, 100);
+}
+
+#[test]
+fn fixed_children_are_shrinked_to_fit() {
+    let mut column: LinearLayout<Vec<Child, 3>> = LinearLayout::new(
+        Rectangle::new(Point::zero(), Size::new(100, 50)),
+        SplitDirection::Vertical,
+        5,
+        Vec::new(),
+    );
+    column.add_child(button("A"), ChildSize::Fixed(40), (0, 0)).unwrap();
+    column.add_child(button("B"), ChildSize::Weight(1), (0, 0)).unwrap();
+    column.add_child(button("C"), ChildSize::Fixed(40), (0, 0)).unwrap();
+    let boxes: [Rectangle; 3] = core::array::from_fn(|n| column.get(n).unwrap().get_bounding_box());
+    assert_eq!(
+        boxes,
+        [
+            Rectangle::new(Point::new(0, 0), Size::new(100, 40)),
+            Rectangle::new(Point::new(0, 45), Size::new(100, 0)),
+            Rectangle::new(Point::new(0, 50), Size::new(100, 0)),
+        ]
+    );
+}
+
+#[test]
+fn arrows_move_focus_between_children() {
+    let mut column: LinearLayout<Vec<Child, 4>> = LinearLayout::new(SCREEN, SplitDirection::Vertical, 0, Vec::new());
+    column.add_child(button("A"), ChildSize::Weight(1), (0, 0)).unwrap();
+    column.add_child(rect(), ChildSize::Weight(1), (0, 0)).unwrap();
+    column.add_child(button("C"), ChildSize::Weight(1), (0, 0)).unwrap();
+    column.add_child(button("D"), ChildSize::Weight(1), (0, 0)).unwrap();
+
+    assert_eq!(
+        column.on_event(Event::KeyDown(KeyCode::Down), &mut ()),
+        Some(Event::KeyDown(KeyCode::Down))
+    );
+    column.set_focus(Some(FocusFrom::Up)).unwrap();
+    assert_eq!(column.get_focused_child(), Some(0));
+
+    // the rectangle can't be focused
+    assert_eq!(column.on_event(Event::KeyDown(KeyCode::Down), &mut ()), None);
+    assert_eq!(column.get_focused_child(), Some(2));
+    assert_eq!(column.get(0).unwrap().get_focus(), Some(false));
+    assert_eq!(column.get(2).unwrap().get_focus(), Some(true));
+
+    assert_eq!(column.on_event(Event::KeyRepeat(KeyCode::Down), &mut ()), None);
+    assert_eq!(column.get_focused_child(), Some(3));
+    assert_eq!(
+        column.on_event(Event::KeyDown(KeyCode::Down), &mut ()),
+        Some(Event::KeyDown(KeyCode::Down))
+    );
+    // arrows across the layout are given to the parent
+    assert_eq!(
+        column.on_event(Event::KeyDown(KeyCode::Left), &mut ()),
+        Some(Event::KeyDown(KeyCode::Left))
+    );
+    assert_eq!(column.get_focused_child(), Some(3));
+
+    column.set_focus(None).unwrap();
+    assert_eq!(column.get_focus(), Some(false));
+    assert_eq!(column.get(3).unwrap().get_focus(), Some(false));
+
+    column.set_focus(Some(FocusFrom::Down)).unwrap();
+    assert_eq!(column.get_focused_child(), Some(3));
+}
+
+#[test]
+fn focus_changes_are_reported_as_dirty() {
+    let mut row: LinearLayout<Vec<Child, 2>> = LinearLayout::new(SCREEN, SplitDirection::Horizontal, 0, Vec::new());
+    row.add_child(button("A"), ChildSize::Weight(1), (0, 0)).unwrap();
+    row.add_child(button("B"), ChildSize::Weight(1), (0, 0)).unwrap();
+    assert_eq!(row.get_dirty_area(), Some(SCREEN));
+    row.mark_clean();
+    assert_eq!(row.get_dirty_area(), None);
+
+    row.set_focus(Some(FocusFrom::Right)).unwrap();
+    assert_eq!(row.get_focused_child(), Some(1));
+    assert_eq!(row.get_dirty_area(), Some(row.get(1).unwrap().get_bounding_box()));
+    row.mark_clean();
+
+    row.on_event(Event::KeyDown(KeyCode::Left), &mut ());
+    assert_eq!(row.get_dirty_area(), Some(SCREEN));
+}
@@ -0,0 +1,127 @@
+use core::fmt;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use nw_gui::{
+    calculator::{Color, Event, Framebuffer, KeyCode, PIXEL_COUNT},
+    gui::{
+        theme::Theme,
+        widgets::{ListView, RowProvider},
+        FocusFrom, Widget,
+    },
+};
+
+/// Rows generated on the fly.
+struct Numbers(usize);
+impl RowProvider for Numbers {
+    fn len(&self) -> usize {
+        self.0
+    }
+    fn write_row(&self, n: usize, text: &mut dyn fmt::Write) -> fmt::Result {
+        write!(text, "Row {}", n)
+    }
+}
+
+/// Four rows are fully visible, the fifth is cut.
+fn list(rows: usize) -> ListView<Numbers, Option<usize>> {
+    ListView::new(
+        Numbers(rows),
+        Rectangle::new(Point::zero(), Size::new(320, 100)),
+        |activated, n| *activated = Some(n),
+    )
+}
+
+fn press(list: &mut ListView<Numbers, Option<usize>>, event: Event) -> Option<Event> {
+    list.on_event(event, &mut None)
+}
+
+#[test]
+fn selection_goes_around_the_list() {
+    let mut list = list(1000);
+    assert_eq!(press(&mut list, Event::KeyDown(KeyCode::Up)), None);
+    assert_eq!(list.get_selected(), 999);
+    assert_eq!(list.get_first_visible(), 996);
+    // a repeated key stops at the end
+    assert_eq!(press(&mut list, Event::KeyRepeat(KeyCode::Down)), None);
+    assert_eq!(list.get_selected(), 999);
+
+    assert_eq!(press(&mut list, Event::KeyDown(KeyCode::Down)), None);
+    assert_eq!(list.get_selected(), 0);
+    assert_eq!(list.get_first_visible(), 0);
+    assert_eq!(
+        press(&mut list, Event::KeyDown(KeyCode::Left)),
+        Some(Event::KeyDown(KeyCode::Left))
+    );
+}
+
+#[test]
+fn list_scrolls_to_the_selected_row() {
+    let mut list = list(1000);
+    for _ in 0..3 {
+        press(&mut list, Event::KeyDown(KeyCode::Down));
+    }
+    assert_eq!(list.get_first_visible(), 0);
+    press(&mut list, Event::KeyDown(KeyCode::Down));
+    assert_eq!(list.get_first_visible(), 1);
+
+    list.set_selected(500);
+    assert_eq!(list.get_first_visible(), 497);
+    list.set_selected(200);
+    assert_eq!(list.get_first_visible(), 200);
+
+    // fewer rows keep the selection in the list
+    list.get_provider_mut().0 = 100;
+    list.rows_changed();
+    assert_eq!(list.get_selected(), 99);
+    assert_eq!(list.get_first_visible(), 96);
+}
+
+#[test]
+fn activation_gives_the_selected_row() {
+    let mut list = list(10);
+    let mut activated = None;
+    list.on_event(Event::KeyDown(KeyCode::Down), &mut activated);
+    list.on_event(Event::KeyDown(KeyCode::Down), &mut activated);
+    assert_eq!(activated, None);
+    assert_eq!(list.on_event(Event::KeyDown(KeyCode::Ok), &mut activated), None);
+    assert_eq!(activated, Some(2));
+    list.on_event(Event::KeyDown(KeyCode::Up), &mut activated);
+    list.on_event(Event::KeyDown(KeyCode::Exe), &mut activated);
+    assert_eq!(activated, Some(1));
+}
+
+#[test]
+fn empty_list_cant_be_focused() {
+    let mut list = list(0);
+    assert_eq!(list.set_focus(Some(FocusFrom::Up)), Err(()));
+    assert_eq!(
+        press(&mut list, Event::KeyDown(KeyCode::Ok)),
+        Some(Event::KeyDown(KeyCode::Ok))
+    );
+    assert_eq!(list.measure(Size::new(320, 240)).preferred, Size::new(320, 0));
+}
+
+#[test]
+fn only_changed_rows_are_rendered_again() {
+    let theme = Theme::default();
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    let mut list = list(1000);
+    list.set_focus(Some(FocusFrom::Up)).unwrap();
+    list.render(&mut display, &theme).unwrap();
+    list.mark_clean();
+    assert_eq!(display.get_pixel(Point::new(1, 1)), Some(theme.focused));
+    assert_eq!(display.get_pixel(Point::new(1, 22)), Some(theme.background));
+
+    press(&mut list, Event::KeyDown(KeyCode::Down));
+    assert_eq!(
+        list.get_dirty_area(),
+        Some(Rectangle::new(Point::zero(), Size::new(320, 42)))
+    );
+    assert_eq!(
+        list.get_focused_area(),
+        Some(Rectangle::new(Point::new(0, 21), Size::new(320, 21)))
+    );
+    list.render_dirty(&mut display, &theme).unwrap();
+    assert_eq!(display.get_pixel(Point::new(1, 1)), Some(theme.background));
+    assert_eq!(display.get_pixel(Point::new(1, 22)), Some(theme.focused));
+}
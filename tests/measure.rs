@@ -0,0 +1,219 @@
+use core::fmt;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use heapless::{String, Vec};
+use nw_gui::gui::{
+    widgets::{
+        Button, ChildSize, ColorRect, Dialog, Grid, LinearLayout, ListView, RowProvider, SplitDirection, SplitLayout,
+        SplitMode, TabLayout, TextBox,
+    },
+    SizeHint, Widget,
+};
+
+mod common;
+use common::State;
+
+const SCREEN: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(320, 240));
+
+fn button<T>(text: &str) -> Button<T> {
+    Button::new(String::from(text), Rectangle::default(), |_| ())
+}
+
+#[test]
+fn widgets_measure_their_content() {
+    let rect: ColorRect<()> = ColorRect::new(SCREEN);
+    assert_eq!(
+        rect.measure(Size::new(100, 50)),
+        SizeHint {
+            min: Size::zero(),
+            preferred: Size::new(100, 50)
+        }
+    );
+
+    // 7x13 characters and 4 pixels of padding
+    let ok: Button<()> = button("OK");
+    assert_eq!(
+        ok.measure(SCREEN.size),
+        SizeHint {
+            min: Size::new(14, 13),
+            preferred: Size::new(22, 21)
+        }
+    );
+    // the preferred size is never smaller than the minimum size
+    assert_eq!(ok.measure(Size::new(10, 100)).preferred, Size::new(14, 21));
+
+    let mut text_box: TextBox<State, 16> = TextBox::new(SCREEN, true);
+    text_box.value = String::from("abc");
+    assert_eq!(
+        text_box.measure(Size::new(100, 50)),
+        SizeHint {
+            min: Size::new(21, 13),
+            preferred: Size::new(100, 21)
+        }
+    );
+}
+
+struct Rows(usize);
+impl RowProvider for Rows {
+    fn len(&self) -> usize {
+        self.0
+    }
+    fn write_row(&self, n: usize, text: &mut dyn fmt::Write) -> fmt::Result {
+        write!(text, "Row {}", n)
+    }
+}
+
+fn assert_min_fits_preferred<W: Widget>(widget: &W) {
+    for available in [Size::zero(), Size::new(10, 10), Size::new(100, 50), SCREEN.size] {
+        let hint = widget.measure(available);
+        assert_eq!(
+            hint.min.component_min(hint.preferred),
+            hint.min,
+            "{:?} in {:?}",
+            hint,
+            available
+        );
+    }
+}
+
+#[test]
+fn preferred_size_is_never_below_min() {
+    assert_min_fits_preferred(&button::<()>("Cancel"));
+    assert_min_fits_preferred(&TextBox::<State, 16>::new(SCREEN, true));
+
+    let mut dialog: Dialog<()> = Dialog::new(String::from("Error"), String::from("Disk full"));
+    dialog.add_button(String::from("OK"), |_| ()).unwrap();
+    assert_min_fits_preferred(&dialog);
+
+    for rows in [0, 3] {
+        let list: ListView<Rows, ()> = ListView::new(Rows(rows), SCREEN, |_, _| ());
+        assert_min_fits_preferred(&list);
+    }
+
+    let mut tabs: TabLayout<Vec<Button<()>, 2>> = TabLayout::new(SCREEN, Vec::new());
+    tabs.add_tab(String::from("First"), button("One")).unwrap();
+    tabs.add_tab(String::from("Second"), button("Two")).unwrap();
+    assert_min_fits_preferred(&tabs);
+}
+
+#[test]
+fn split_layout_fits_content() {
+    let mut layout: SplitLayout<Button<()>, Button<()>> = SplitLayout::new(SCREEN, SplitDirection::Vertical, 0.5);
+    layout.attach_first(button("OK"), (0, 0));
+    layout.attach_second(button("Cancel"), (0, 0));
+    assert_eq!(
+        layout.get_first().unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(0, 0), Size::new(320, 120))
+    );
+
+    layout.set_split_mode(SplitMode::FitFirst);
+    assert_eq!(
+        layout.get_first().unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(0, 0), Size::new(320, 21))
+    );
+    assert_eq!(
+        layout.get_second().unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(0, 21), Size::new(320, 219))
+    );
+    assert_eq!(
+        layout.measure(SCREEN.size),
+        SizeHint {
+            min: Size::new(42, 26),
+            preferred: Size::new(50, 42)
+        }
+    );
+
+    let mut layout: SplitLayout<Button<()>, Button<()>> = SplitLayout::new(SCREEN, SplitDirection::Horizontal, 0.5);
+    layout.set_split_mode(SplitMode::FitSecond);
+    layout.attach_first(button("OK"), (0, 0));
+    layout.attach_second(button("Cancel"), (5, 0));
+    assert_eq!(
+        layout.get_first().unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(0, 0), Size::new(260, 240))
+    );
+    assert_eq!(
+        layout.get_second().unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(265, 0), Size::new(50, 240))
+    );
+}
+
+#[test]
+fn grid_fits_content() {
+    // +---+-----------+
+    // | A | Long text |
+    // +---+-----------+
+    // |     Wide      |
+    // +---------------+
+    let mut grid: Grid<2, 2, Vec<Button<()>, 3>> =
+        Grid::new(Rectangle::new(Point::zero(), Size::new(200, 100)), Vec::new());
+    grid.add_child_at(button("A"), (0, 0), (1, 1), 0).unwrap();
+    grid.add_child_at(button("Long text"), (1, 0), (1, 1), 0).unwrap();
+    grid.add_child_at(button("Wide"), (0, 1), (2, 1), 0).unwrap();
+    assert_eq!(
+        grid.get(0).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(0, 0), Size::new(100, 50))
+    );
+    assert_eq!(
+        grid.measure(Size::new(200, 100)),
+        SizeHint {
+            min: Size::new(126, 26),
+            preferred: Size::new(142, 42)
+        }
+    );
+
+    grid.set_fit_content(true);
+    // the remaining space is shared between the columns and rows
+    assert_eq!(
+        grid.get(0).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(0, 0), Size::new(72, 50))
+    );
+    assert_eq!(
+        grid.get(1).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(72, 0), Size::new(128, 50))
+    );
+    assert_eq!(
+        grid.get(2).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(0, 50), Size::new(200, 50))
+    );
+    assert_eq!(
+        grid.measure(Size::new(200, 100)),
+        SizeHint {
+            min: Size::new(70, 26),
+            preferred: Size::new(86, 42)
+        }
+    );
+
+    // the columns are shrinked proportionally,
+    // "Long text" prefers at least its minimum width: 15 * 43 / (15 + 71) and 71 * 43 / (15 + 71)
+    grid.set_bounding_box(Rectangle::new(Point::zero(), Size::new(43, 100)));
+    assert_eq!(grid.get(0).unwrap().get_bounding_box().size.width, 8);
+    assert_eq!(grid.get(1).unwrap().get_bounding_box().size.width, 34);
+}
+
+#[test]
+fn linear_layout_fits_content() {
+    let mut row: LinearLayout<Vec<Button<()>, 3>> =
+        LinearLayout::new(SCREEN, SplitDirection::Horizontal, 2, Vec::new());
+    row.add_child(button("OK"), ChildSize::Fit, (0, 0)).unwrap();
+    row.add_child(button("B"), ChildSize::Weight(1), (0, 0)).unwrap();
+    row.add_child(button("Cancel"), ChildSize::Fit, (1, 1)).unwrap();
+    assert_eq!(
+        row.get(0).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(0, 0), Size::new(22, 240))
+    );
+    assert_eq!(
+        row.get(1).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(24, 0), Size::new(242, 240))
+    );
+    assert_eq!(
+        row.get(2).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(269, 1), Size::new(50, 238))
+    );
+    assert_eq!(
+        row.measure(SCREEN.size),
+        SizeHint {
+            min: Size::new(14 + 7 + 44 + 4, 15),
+            preferred: Size::new(22 + 15 + 52 + 4, 23)
+        }
+    );
+}
@@ -0,0 +1,116 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use heapless::{String, Vec};
+use nw_gui::{
+    calculator::{Color, Event, Framebuffer, KeyCode, PIXEL_COUNT},
+    gui::{
+        theme::Theme,
+        widgets::{Button, ChildSize, Dialog, LinearLayout, OverlayStack, SplitDirection},
+        FocusFrom, Widget,
+    },
+};
+
+const SCREEN: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(320, 240));
+
+type Buttons = LinearLayout<Vec<Button<u32>, 2>>;
+
+/// Two buttons adding 1 and 10 to the context.
+fn screen() -> OverlayStack<Buttons, Dialog<u32>> {
+    let mut buttons: Buttons = LinearLayout::new(SCREEN, SplitDirection::Vertical, 0, Vec::new());
+    let one = Button::new(String::from("+1"), Rectangle::default(), |n| *n += 1);
+    let ten = Button::new(String::from("+10"), Rectangle::default(), |n| *n += 10);
+    buttons.add_child(one, ChildSize::Weight(1), (0, 0)).unwrap();
+    buttons.add_child(ten, ChildSize::Weight(1), (0, 0)).unwrap();
+    buttons.set_focus(Some(FocusFrom::Up)).unwrap();
+    OverlayStack::new(buttons)
+}
+
+/// A dialog adding 100 or 1000 to the context.
+fn dialog() -> Dialog<u32> {
+    let mut dialog = Dialog::new(String::from("Add"), String::from("How much?"));
+    dialog.add_button(String::from("100"), |n| *n += 100).unwrap();
+    dialog.add_button(String::from("1000"), |n| *n += 1000).unwrap();
+    dialog
+}
+
+fn press(screen: &mut OverlayStack<Buttons, Dialog<u32>>, key: KeyCode, count: &mut u32) -> Option<Event> {
+    screen.on_event(Event::KeyDown(key), count)
+}
+
+#[test]
+fn dialog_is_centered_with_its_preferred_size() {
+    let mut screen = screen();
+    let mut dialog = Dialog::new(String::from("Error"), String::from("Disk full"));
+    dialog.add_button(String::from("OK"), |_| ()).unwrap();
+    screen.push(dialog).unwrap();
+    // the message is 63 pixels wide, and the dialog has 2 lines of text and a row of buttons
+    assert_eq!(
+        screen.get_top().unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(120, 84), Size::new(79, 71))
+    );
+    assert_eq!(
+        screen.get_focused_area(),
+        Some(Rectangle::new(Point::new(128, 126), Size::new(63, 21)))
+    );
+}
+
+#[test]
+fn dialog_captures_events_until_dismissed() {
+    let mut screen = screen();
+    let mut count = 0;
+    screen.push(dialog()).unwrap();
+    assert_eq!(press(&mut screen, KeyCode::Down, &mut count), None);
+    assert_eq!(press(&mut screen, KeyCode::Home, &mut count), None);
+    assert_eq!(screen.get_base().get_focused_child(), Some(0));
+
+    assert_eq!(press(&mut screen, KeyCode::Back, &mut count), None);
+    assert!(screen.is_empty());
+    assert_eq!(count, 0);
+    assert_eq!(screen.get_dirty_area(), Some(SCREEN));
+
+    // the base widget gets the events again, with the focus it had
+    assert_eq!(press(&mut screen, KeyCode::Down, &mut count), None);
+    assert_eq!(press(&mut screen, KeyCode::Ok, &mut count), None);
+    assert_eq!(count, 10);
+}
+
+#[test]
+fn dialog_buttons_call_back_and_dismiss() {
+    let mut screen = screen();
+    let mut count = 0;
+    screen.push(dialog()).unwrap();
+    screen.push(dialog()).unwrap();
+    assert_eq!(screen.len(), 2);
+
+    assert_eq!(press(&mut screen, KeyCode::Right, &mut count), None);
+    assert_eq!(screen.get_top().unwrap().get_focused_button(), Some(1));
+    assert_eq!(press(&mut screen, KeyCode::Exe, &mut count), None);
+    assert_eq!(count, 1000);
+    assert_eq!(screen.len(), 1);
+
+    assert_eq!(press(&mut screen, KeyCode::Ok, &mut count), None);
+    assert_eq!(count, 1100);
+    assert!(screen.is_empty());
+}
+
+#[test]
+fn dialog_is_rendered_above_the_base() {
+    let theme = Theme::default();
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    let mut screen = screen();
+    let mut count = 0;
+    screen.render(&mut display, &theme).unwrap();
+    screen.mark_clean();
+
+    screen.push(dialog()).unwrap();
+    let corner = screen.get_top().unwrap().get_bounding_box().top_left;
+    assert_eq!(display.get_pixel(corner), Some(theme.background));
+    screen.render_dirty(&mut display, &theme).unwrap();
+    screen.mark_clean();
+    assert_eq!(display.get_pixel(corner), Some(theme.foreground));
+
+    press(&mut screen, KeyCode::Back, &mut count);
+    screen.render_dirty(&mut display, &theme).unwrap();
+    assert_eq!(display.get_pixel(corner), Some(theme.background));
+}
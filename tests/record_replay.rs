@@ -0,0 +1,242 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use nw_gui::{
+    app::App,
+    calculator::{
+        Backend, Calculator, Color, Event, Framebuffer, HeadlessBackend, KeyCode, KeyRepeat, RecordingBackend,
+        ReplayBackend, ReplayError, ReplayErrorKind, Timers, PIXEL_COUNT,
+    },
+    gui::{
+        text::{TextInputContext, TextInputState},
+        theme::Theme,
+        widgets::TextBox,
+        FocusFrom, Widget,
+    },
+};
+
+use heapless::{String, Vec};
+
+#[test]
+fn record_then_replay() {
+    let mut recorded_pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let log = record(&mut recorded_pixels);
+    assert_eq!(
+        log,
+        "0 0\n1 20\n1 20 KeyDown Num4\n2 40\n3 60\n3 60 KeyDown Num2\n3 60 KeyUp Num2\n4 80\n5 100\n5 100 HardQuit\n"
+    );
+
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let headless = HeadlessBackend::<1>::new(&mut pixels);
+    let mut calc = Calculator::new(ReplayBackend::new(headless, &log)).unwrap();
+    let mut app = InputApp::new();
+    app.run(&mut calc);
+
+    let replay = calc.get_backend();
+    assert!(replay.is_finished());
+    assert_eq!(app.text_box.value, "42");
+    assert_eq!(replay.get_inner().get_frame(), 5);
+    assert!(replay
+        .get_inner()
+        .get_framebuffer()
+        .iter_pixels()
+        .eq(Framebuffer::new(&mut recorded_pixels).iter_pixels()));
+}
+
+fn record(pixels: &mut [Color]) -> String<256> {
+    let mut backend = HeadlessBackend::<8>::new(pixels);
+    backend.push_event(Event::KeyDown(KeyCode::Num4)).unwrap();
+    backend.push_event_at(2, Event::KeyDown(KeyCode::Num2)).unwrap();
+    backend.push_event_at(2, Event::KeyUp(KeyCode::Num2)).unwrap();
+    backend.set_frame_limit(5);
+    let mut calc = Calculator::new(RecordingBackend::new(backend, String::<256>::new())).unwrap();
+    let mut app = InputApp::new();
+    app.run(&mut calc);
+
+    let recorder = calc.get_backend();
+    assert!(!recorder.has_write_failed());
+    assert_eq!(app.text_box.value, "42");
+    assert_eq!(recorder.get_inner().get_frame(), 5);
+    recorder.get_log().clone()
+}
+
+#[test]
+fn key_repeats_are_replayed() {
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut backend = HeadlessBackend::<4>::new(&mut pixels);
+    backend.push_event_at(0, Event::KeyDown(KeyCode::Left)).unwrap();
+    backend.push_event_at(5, Event::KeyUp(KeyCode::Left)).unwrap();
+    backend.set_frame_limit(8);
+    let mut calc = Calculator::new(RecordingBackend::new(backend, String::<256>::new())).unwrap();
+    calc.set_key_repeat(Some(KeyRepeat {
+        delay: 40,
+        interval: 40,
+    }));
+    let recorded = collect_events(&mut calc);
+    let log = calc.get_backend().get_log().clone();
+    assert_eq!(
+        log,
+        "0 0\n1 20\n1 20 KeyDown Left\n2 40\n3 60\n3 60 KeyRepeat Left\n4 80\n5 100\n5 100 KeyRepeat Left\n6 120\n\
+         6 120 KeyUp Left\n7 140\n8 160\n8 160 HardQuit\n"
+    );
+
+    // the repetitions come from the log only, whatever the settings of the replaying calculator
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let headless = HeadlessBackend::<1>::new(&mut pixels);
+    let mut calc = Calculator::new(ReplayBackend::new(headless, &log)).unwrap();
+    calc.set_key_repeat(Some(KeyRepeat {
+        delay: 20,
+        interval: 20,
+    }));
+    assert_eq!(collect_events(&mut calc), recorded);
+}
+
+#[test]
+fn timers_expire_at_the_recorded_frames() {
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut backend = HeadlessBackend::<4>::new(&mut pixels);
+    backend.push_event_at(1, Event::KeyDown(KeyCode::Ok)).unwrap();
+    backend.set_frame_limit(9);
+    let mut calc = Calculator::new(RecordingBackend::new(backend, String::<256>::new())).unwrap();
+    let recorded = collect_timers(&mut calc);
+    assert_eq!(
+        recorded,
+        [
+            (1, Event::KeyDown(KeyCode::Ok)),
+            (4, Event::Timer(1)),
+            (8, Event::HardQuit)
+        ]
+    );
+    let log = calc.get_backend().get_log().clone();
+
+    // the time comes from the log, whatever the frame duration of the replaying backend
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut headless = HeadlessBackend::<1>::new(&mut pixels);
+    headless.set_frame_duration(5);
+    let mut calc = Calculator::new(ReplayBackend::new(headless, &log)).unwrap();
+    assert_eq!(collect_timers(&mut calc), recorded);
+    assert_eq!(calc.now(), 180);
+}
+
+/// Frame and event received until HardQuit, with a timer expiring 60 ms after each key press.
+fn collect_timers<B: Backend>(calc: &mut Calculator<B>) -> Vec<(u32, Event), 16> {
+    let mut timers: Timers = Timers::new();
+    let mut received = Vec::new();
+    for frame in 0.. {
+        calc.render();
+        timers.set_now(calc.now());
+        for e in calc.events() {
+            received.push((frame, e)).unwrap();
+            match e {
+                Event::KeyDown(_) => timers.schedule(1, 60).unwrap(),
+                Event::HardQuit => return received,
+                _ => (),
+            }
+        }
+        while let Some(e) = timers.poll_event() {
+            received.push((frame, e)).unwrap();
+        }
+    }
+    received
+}
+
+/// Frame and event received until HardQuit.
+fn collect_events<B: Backend>(calc: &mut Calculator<B>) -> Vec<(u32, Event), 16> {
+    let mut received = Vec::new();
+    for frame in 0.. {
+        calc.render();
+        for e in calc.events() {
+            received.push((frame, e)).unwrap();
+            if e == Event::HardQuit {
+                return received;
+            }
+        }
+    }
+    received
+}
+
+#[test]
+fn events_before_the_first_frame_are_replayed_before_it() {
+    let log = "0 0\n0 0 KeyDown Ok\n1 20\n1 20 KeyUp Ok\n1 20 HardQuit\n";
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let replay = ReplayBackend::new(HeadlessBackend::<1>::new(&mut pixels), log);
+    let mut recorder = RecordingBackend::new(replay, String::<64>::new());
+    assert_eq!(recorder.poll_event(), Some(Event::KeyDown(KeyCode::Ok)));
+    assert_eq!(recorder.poll_event(), None);
+    recorder.flush();
+    assert_eq!(recorder.poll_event(), Some(Event::KeyUp(KeyCode::Ok)));
+    assert_eq!(recorder.poll_event(), Some(Event::HardQuit));
+    assert_eq!(recorder.get_log(), log);
+}
+
+#[test]
+fn invalid_line_stops_the_replay() {
+    let log = "0 0 KeyDown Ok\n\n# Enter isn't a key of the calculator\n0 0 KeyDown Enter\n0 0 KeyUp Ok\n";
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut replay = ReplayBackend::new(HeadlessBackend::<1>::new(&mut pixels), log);
+    assert_eq!(replay.poll_event(), Some(Event::KeyDown(KeyCode::Ok)));
+    assert_eq!(replay.poll_event(), Some(Event::HardQuit));
+    assert!(!replay.is_finished());
+    assert_eq!(
+        replay.get_error(),
+        Some(ReplayError {
+            line: 4,
+            kind: ReplayErrorKind::InvalidEvent
+        })
+    );
+
+    let replay = ReplayBackend::new(HeadlessBackend::<1>::new(&mut pixels), "KeyDown Ok\n");
+    assert_eq!(replay.get_error().map(|e| e.kind), Some(ReplayErrorKind::InvalidFrame));
+    let replay = ReplayBackend::new(HeadlessBackend::<1>::new(&mut pixels), "0 KeyDown Ok\n");
+    assert_eq!(replay.get_error().map(|e| e.kind), Some(ReplayErrorKind::InvalidTime));
+    let replay = ReplayBackend::new(HeadlessBackend::<1>::new(&mut pixels), "0\n");
+    assert_eq!(replay.get_error().map(|e| e.kind), Some(ReplayErrorKind::Syntax));
+}
+
+#[test]
+fn parse_events() {
+    for key in KeyCode::ALL {
+        let mut text: String<32> = String::new();
+        core::fmt::write(&mut text, format_args!("{}", Event::KeyUp(key))).unwrap();
+        assert_eq!(text.parse(), Ok(Event::KeyUp(key)));
+    }
+    assert_eq!("HardQuit".parse(), Ok(Event::HardQuit));
+    assert_eq!("LayerDismissed".parse(), Ok(Event::LayerDismissed));
+    assert_eq!("Timer 12".parse(), Ok(Event::Timer(12)));
+    assert_eq!("KeyDown".parse::<Event>(), Err(()));
+    assert_eq!("KeyDown Ok Ok".parse::<Event>(), Err(()));
+    assert_eq!("KeyDown Enter".parse::<Event>(), Err(()));
+}
+
+struct InputApp {
+    text_box: TextBox<InputState, 8>,
+    state: InputState,
+}
+
+impl App for InputApp {
+    fn new() -> Self {
+        let mut text_box = TextBox::new(Rectangle::new(Point::new(10, 10), Size::new(100, 30)), true);
+        text_box.set_focus(Some(FocusFrom::Left)).unwrap();
+        Self {
+            text_box,
+            state: InputState(TextInputState::new()),
+        }
+    }
+    fn run<B: Backend>(&mut self, calc: &mut Calculator<B>) {
+        loop {
+            self.text_box.render(calc.get_draw_target(), &Theme::default()).unwrap();
+            calc.render();
+            for e in calc.events() {
+                if let Some(Event::HardQuit) = self.text_box.on_event(e, &mut self.state) {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+struct InputState(TextInputState);
+impl TextInputContext for InputState {
+    fn get_context(&mut self) -> &mut TextInputState {
+        &mut self.0
+    }
+}
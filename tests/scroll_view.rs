@@ -0,0 +1,119 @@
+use embedded_graphics::pixelcolor::RgbColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use heapless::{String, Vec};
+use nw_gui::{
+    calculator::{Color, Event, Framebuffer, KeyCode, PIXEL_COUNT},
+    gui::{
+        theme::Theme,
+        widgets::{Button, ChildSize, ColorRect, LinearLayout, ScrollView, SplitDirection},
+        FocusFrom, Widget,
+    },
+};
+
+const SCREEN: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(320, 240));
+const SMALL: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(100, 100));
+
+type List = LinearLayout<Vec<Button<()>, 10>>;
+
+/// Ten buttons 40 pixels high.
+fn list() -> ScrollView<List> {
+    let mut list: List = LinearLayout::new(Rectangle::default(), SplitDirection::Vertical, 0, Vec::new());
+    for _ in 0..10 {
+        let button = Button::new(String::from("Button"), Rectangle::default(), |_| ());
+        list.add_child(button, ChildSize::Weight(1), (0, 0)).unwrap();
+    }
+    ScrollView::new(SCREEN, Size::new(316, 400), list)
+}
+
+fn press(view: &mut impl Widget<Context = ()>, key: KeyCode) -> Option<Event> {
+    view.on_event(Event::KeyDown(key), &mut ())
+}
+
+#[test]
+fn view_follows_the_focus() {
+    let mut view = list();
+    assert_eq!(view.get_viewport(), Rectangle::new(Point::zero(), Size::new(316, 240)));
+    assert_eq!(
+        view.get_child().get_bounding_box(),
+        Rectangle::new(Point::zero(), Size::new(316, 400))
+    );
+
+    view.set_focus(Some(FocusFrom::Up)).unwrap();
+    for _ in 0..5 {
+        assert_eq!(press(&mut view, KeyCode::Down), None);
+    }
+    assert_eq!(view.get_child().get_focused_child(), Some(5));
+    assert_eq!(view.get_offset(), Point::zero());
+
+    assert_eq!(press(&mut view, KeyCode::Down), None);
+    assert_eq!(view.get_offset(), Point::new(0, 40));
+    assert_eq!(
+        view.get_focused_area(),
+        Some(Rectangle::new(Point::new(0, 200), Size::new(316, 40)))
+    );
+
+    // going back doesn't scroll while the focused button is visible
+    assert_eq!(press(&mut view, KeyCode::Up), None);
+    assert_eq!(view.get_offset(), Point::new(0, 40));
+    for _ in 0..5 {
+        assert_eq!(press(&mut view, KeyCode::Up), None);
+    }
+    assert_eq!(view.get_child().get_focused_child(), Some(0));
+    assert_eq!(view.get_offset(), Point::zero());
+
+    // the end of the list is given back to the parent
+    for _ in 0..9 {
+        assert_eq!(press(&mut view, KeyCode::Down), None);
+    }
+    assert_eq!(view.get_offset(), Point::new(0, 160));
+    assert_eq!(press(&mut view, KeyCode::Down), Some(Event::KeyDown(KeyCode::Down)));
+}
+
+#[test]
+fn unused_arrows_scroll_the_content() {
+    let mut view: ScrollView<ColorRect<()>> =
+        ScrollView::new(SMALL, Size::new(96, 300), ColorRect::new(Rectangle::default()));
+    assert_eq!(press(&mut view, KeyCode::Up), Some(Event::KeyDown(KeyCode::Up)));
+    assert_eq!(press(&mut view, KeyCode::Down), None);
+    assert_eq!(view.on_event(Event::KeyRepeat(KeyCode::Down), &mut ()), None);
+    assert_eq!(view.get_offset(), Point::new(0, 40));
+    assert_eq!(
+        view.get_child().get_bounding_box(),
+        Rectangle::new(Point::new(0, -40), Size::new(96, 300))
+    );
+    // the content isn't wider than the view
+    assert_eq!(press(&mut view, KeyCode::Right), Some(Event::KeyDown(KeyCode::Right)));
+
+    view.scroll_to(Point::new(50, 1000));
+    assert_eq!(view.get_offset(), Point::new(0, 200));
+    assert_eq!(press(&mut view, KeyCode::Down), Some(Event::KeyDown(KeyCode::Down)));
+
+    // a smaller content keeps the view inside it
+    view.set_content_size(Size::new(96, 150));
+    assert_eq!(view.get_offset(), Point::new(0, 50));
+}
+
+#[test]
+fn rendering_is_clipped_to_the_viewport() {
+    let theme = Theme::default();
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    let mut view: ScrollView<ColorRect<()>> =
+        ScrollView::new(SMALL, Size::new(96, 300), ColorRect::new(Rectangle::default()));
+    view.render(&mut display, &theme).unwrap();
+    view.mark_clean();
+
+    assert_eq!(display.get_pixel(Point::new(50, 50)), Some(theme.background));
+    assert_eq!(display.get_pixel(Point::new(50, 150)), Some(Color::BLACK));
+    // the thumb is a third of the scrollbar
+    assert_eq!(display.get_pixel(Point::new(98, 2)), Some(theme.foreground));
+    assert_eq!(display.get_pixel(Point::new(98, 90)), Some(theme.background));
+
+    view.scroll_to(Point::new(0, 200));
+    assert_eq!(view.get_dirty_area(), Some(SMALL));
+    view.render_dirty(&mut display, &theme).unwrap();
+    assert_eq!(display.get_pixel(Point::new(98, 2)), Some(theme.background));
+    assert_eq!(display.get_pixel(Point::new(98, 90)), Some(theme.foreground));
+    assert_eq!(display.get_pixel(Point::new(50, 150)), Some(Color::BLACK));
+}
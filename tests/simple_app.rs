@@ -2,11 +2,11 @@ use embedded_graphics::prelude::*;
 use embedded_graphics::primitives::Rectangle;
 use nw_gui::{
     app::App,
-    calculator::{Calculator, Color, Event},
+    calculator::{Backend, Calculator, Color, Event, SimulatorBackend},
     gui::{
         text::{TextInputContext, TextInputState},
         theme::Theme,
-        widgets::{Button, SplitDirection, SplitLayout, TextBox},
+        widgets::{Button, SplitDirection, SplitLayout, StatusBar, TextBox},
         Widget,
     },
 };
@@ -15,18 +15,20 @@ use heapless::String;
 
 #[test]
 fn os_main() {
-    let mut calc = Calculator::new().unwrap();
+    let mut calc = Calculator::new(SimulatorBackend::new()).unwrap();
     calc.render();
 
     let mut app = SimpleApp::new();
     app.run(&mut calc);
 }
 
+type SimpleAppGui = SplitLayout<
+    SplitLayout<StatusBar<SharedAppState>, Button<SharedAppState>>,
+    SplitLayout<Button<SharedAppState>, TextBox<SharedAppState, 32>>,
+>;
+
 struct SimpleApp {
-    gui: SplitLayout<
-        SplitLayout<TextBox<SharedAppState, 16>, Button<SharedAppState>>,
-        SplitLayout<Button<SharedAppState>, TextBox<SharedAppState, 32>>,
-    >,
+    gui: SimpleAppGui,
     state: SharedAppState,
     theme: Theme,
 }
@@ -53,8 +55,7 @@ impl App for SimpleApp {
         app.gui
             .get_first_mut()
             .unwrap()
-            .attach_first(TextBox::new(Rectangle::default(), false), (2, 2));
-        app.gui.get_first_mut().unwrap().get_first_mut().unwrap().value = "Mods:      ".into();
+            .attach_first(StatusBar::new(String::from("Simple app"), Rectangle::default()), (0, 0));
         app.gui.get_first_mut().unwrap().attach_second(
             Button::new(String::from("Button!"), Rectangle::default(), |context| {
                 println!("Pressed!");
@@ -80,47 +81,36 @@ impl App for SimpleApp {
 
         app
     }
-    fn run(&mut self, calc: &mut Calculator) {
+    fn run<B: Backend>(&mut self, calc: &mut Calculator<B>) {
+        let mut theme_changed = true;
         'running: loop {
-            self.gui.render(calc.get_draw_target(), &self.theme);
-            calc.render();
+            if theme_changed {
+                self.gui.render(calc.get_draw_target(), &self.theme).unwrap_or_default();
+                self.gui.mark_clean();
+                calc.render();
+                theme_changed = false;
+            } else if let Some(area) = self.gui.get_dirty_area() {
+                self.gui
+                    .render_dirty(calc.get_draw_target(), &self.theme)
+                    .unwrap_or_default();
+                self.gui.mark_clean();
+                calc.render_area(area);
+            }
             for e in calc.events() {
-                if let Some(e) = self.gui.on_event(e, &mut self.state) {
-                    match e {
-                        Event::HardQuit => break 'running,
-                        _ => (),
-                    }
+                if let Some(Event::HardQuit) = self.gui.on_event(e, &mut self.state) {
+                    break 'running;
                 }
                 // apply state changes
-                if let Some(color) = self.state.new_color {
+                if let Some(color) = self.state.new_color.take() {
                     self.theme.background = color;
+                    theme_changed = true;
                 }
-                let mut input_state_string: String<16> = String::from("Mods: ");
-                if self.state.input_state.get_shift() {
-                    input_state_string.push_str(" S ").unwrap();
-                } else {
-                    input_state_string.push_str("   ").unwrap();
-                }
-                input_state_string
-                    .push_str(match self.state.input_state.get_alpha() {
-                        nw_gui::gui::text::AlphaState::Big(lock) => {
-                            if lock {
-                                "@A"
-                            } else {
-                                " A"
-                            }
-                        }
-                        nw_gui::gui::text::AlphaState::No => "  ",
-                        nw_gui::gui::text::AlphaState::Small(lock) => {
-                            if lock {
-                                "@a"
-                            } else {
-                                " a"
-                            }
-                        }
-                    })
-                    .unwrap();
-                self.gui.get_first_mut().unwrap().get_first_mut().unwrap().value = input_state_string;
+                self.gui
+                    .get_first_mut()
+                    .unwrap()
+                    .get_first_mut()
+                    .unwrap()
+                    .update(&mut self.state);
             }
         }
     }
@@ -0,0 +1,117 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use nw_gui::{
+    calculator::{Color, Framebuffer, PIXEL_COUNT},
+    gui::{
+        text::{TextInputContext, TextInputState},
+        theme::Theme,
+        widgets::{Button, ColorRect, SplitDirection, SplitLayout, TextBox},
+        FocusFrom, Widget,
+    },
+    snapshot::{assert_snapshot, Snapshot, SnapshotError, UPDATE_ENV_VAR},
+};
+
+use heapless::String;
+
+const AREA: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(100, 40));
+
+fn themes() -> [(&'static str, Theme); 2] {
+    let light = Theme {
+        rect_border: 1,
+        foreground: Color::BLACK,
+        focused: Color::CSS_DARK_BLUE,
+        active: Color::CSS_ORANGE,
+        background: Color::WHITE,
+    };
+    [("default", Theme::default()), ("light", light)]
+}
+
+fn check<W: Widget>(name: &str, widget: &W) {
+    for (theme_name, theme) in themes() {
+        let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+        let mut display = Framebuffer::new(&mut pixels);
+        widget.render(&mut display, &theme).unwrap();
+        assert_snapshot(
+            &display,
+            AREA,
+            format!("{}/tests/snapshots/{}_{}.ppm", env!("CARGO_MANIFEST_DIR"), name, theme_name),
+        );
+    }
+}
+
+#[test]
+fn color_rect() {
+    check("color_rect", &ColorRect::<()>::new(AREA));
+}
+
+#[test]
+fn button() {
+    let mut button: Button<()> = Button::new(String::from("Button!"), AREA, |_| ());
+    check("button", &button);
+    button.set_focus(Some(FocusFrom::Left)).unwrap();
+    check("button_focused", &button);
+}
+
+#[test]
+fn text_box() {
+    let mut text_box: TextBox<State, 16> = TextBox::new(AREA, true);
+    text_box.value = String::from("1+2");
+    check("text_box", &text_box);
+    text_box.set_focus(Some(FocusFrom::Left)).unwrap();
+    check("text_box_focused", &text_box);
+}
+
+#[test]
+fn split_layout() {
+    let mut layout: SplitLayout<ColorRect<()>, Button<()>> =
+        SplitLayout::new(AREA, SplitDirection::Horizontal, 0.3);
+    layout.attach_first(ColorRect::new(Rectangle::default()), (2, 2));
+    layout.attach_second(Button::new(String::from("Ok"), Rectangle::default(), |_| ()), (2, 4));
+    layout.set_focus(Some(FocusFrom::Left)).unwrap();
+    check("split_layout", &layout);
+}
+
+#[test]
+fn ppm_round_trip() {
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    ColorRect::<()>::new(AREA)
+        .render(&mut display, &Theme::default())
+        .unwrap();
+    let snapshot = Snapshot::capture(&display, AREA);
+    assert_eq!(Snapshot::from_ppm(&snapshot.to_ppm()).unwrap(), snapshot);
+}
+
+#[test]
+fn diff_counts_pixels() {
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    let before = Snapshot::capture(&display, AREA);
+    Pixel(Point::new(1, 1), Color::WHITE).draw(&mut display).unwrap();
+    Pixel(Point::new(3, 1), Color::WHITE).draw(&mut display).unwrap();
+    let (different_pixels, diff) = before.diff(&Snapshot::capture(&display, AREA)).unwrap();
+    assert_eq!(different_pixels, 2);
+    assert_eq!(diff.get_pixel(Point::new(1, 1)), Some(Color::RED));
+    assert_eq!(diff.get_pixel(Point::new(2, 2)), Some(Color::BLACK));
+}
+
+#[test]
+fn missing_reference_fails() {
+    // the reference would be created while updating the snapshots
+    if std::env::var_os(UPDATE_ENV_VAR).is_some() {
+        return;
+    }
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let display = Framebuffer::new(&mut pixels);
+    let path = format!("{}/tests/snapshots/missing.ppm", env!("CARGO_MANIFEST_DIR"));
+    let result = Snapshot::capture(&display, AREA).compare_with_reference(&path);
+    assert!(matches!(result, Err(SnapshotError::MissingReference(_))));
+    assert!(!std::path::Path::new(&path).exists());
+}
+
+struct State(TextInputState);
+impl TextInputContext for State {
+    fn get_context(&mut self) -> &mut TextInputState {
+        &mut self.0
+    }
+}
@@ -0,0 +1,72 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use heapless::String;
+use nw_gui::{
+    calculator::{Color, Event, Framebuffer, KeyCode, PIXEL_COUNT},
+    gui::{
+        text::AlphaState,
+        theme::Theme,
+        widgets::{StatusBar, STATUS_BAR_HEIGHT},
+        Widget,
+    },
+};
+
+mod common;
+use common::{state, State};
+
+const BAR: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(320, STATUS_BAR_HEIGHT));
+// where the state of Shift and Alpha is shown
+const MODIFIERS: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(120, STATUS_BAR_HEIGHT));
+
+fn status_bar() -> StatusBar<State> {
+    StatusBar::new(String::from("Calculation"), BAR)
+}
+
+#[test]
+fn modifiers_are_read_from_the_context() {
+    let theme = Theme::default();
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    let mut state = state();
+    let mut status_bar = status_bar();
+    status_bar.render(&mut display, &theme).unwrap();
+    status_bar.mark_clean();
+    assert!(MODIFIERS
+        .points()
+        .all(|point| display.get_pixel(point) == Some(theme.foreground)));
+
+    // nothing changed
+    status_bar.update(&mut state);
+    assert_eq!(status_bar.get_dirty_area(), None);
+
+    state.input.text_from_event(&Event::KeyDown(KeyCode::Shift));
+    state.input.text_from_event(&Event::KeyDown(KeyCode::Alpha));
+    status_bar.update(&mut state);
+    assert_eq!(status_bar.get_input_state().get_alpha(), AlphaState::Big(false));
+    assert_eq!(status_bar.get_dirty_area(), Some(BAR));
+    status_bar.render_dirty(&mut display, &theme).unwrap();
+    assert!(MODIFIERS
+        .points()
+        .any(|point| display.get_pixel(point) == Some(theme.background)));
+}
+
+#[test]
+fn battery_is_filled_with_its_level() {
+    let theme = Theme::default();
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    let mut status_bar = status_bar();
+    status_bar.set_clock(Some((9, 41)));
+
+    // the battery is drawn from x = 300 to 315, and is filled from x = 302
+    status_bar.set_battery(Some(50));
+    status_bar.render(&mut display, &theme).unwrap();
+    assert_eq!(display.get_pixel(Point::new(303, 8)), Some(theme.background));
+    assert_eq!(display.get_pixel(Point::new(309, 8)), Some(theme.foreground));
+
+    status_bar.mark_clean();
+    status_bar.set_battery(Some(250));
+    assert_eq!(status_bar.get_dirty_area(), Some(BAR));
+    status_bar.render(&mut display, &theme).unwrap();
+    assert_eq!(display.get_pixel(Point::new(309, 8)), Some(theme.background));
+}
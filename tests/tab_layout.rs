@@ -0,0 +1,126 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use heapless::{String, Vec};
+use nw_gui::{
+    calculator::{Color, Event, Framebuffer, KeyCode, PIXEL_COUNT},
+    gui::{
+        theme::Theme,
+        widgets::{Button, ChildSize, LinearLayout, SplitDirection, TabLayout},
+        FocusFrom, Widget,
+    },
+};
+
+type Page = LinearLayout<Vec<Button<()>, 2>>;
+
+/// A column of two buttons.
+fn page() -> Page {
+    let mut page: Page = LinearLayout::new(Rectangle::default(), SplitDirection::Vertical, 0, Vec::new());
+    for _ in 0..2 {
+        let button = Button::new(String::from("Button"), Rectangle::default(), |_| ());
+        page.add_child(button, ChildSize::Weight(1), (0, 0)).unwrap();
+    }
+    page
+}
+
+/// Three tabs across the screen.
+fn tabs() -> TabLayout<Vec<Page, 3>> {
+    let mut tabs = TabLayout::new(Rectangle::new(Point::zero(), Size::new(300, 240)), Vec::new());
+    for title in ["One", "Two", "Three"] {
+        tabs.add_tab(String::from(title), page()).unwrap();
+    }
+    tabs
+}
+
+fn press(tabs: &mut impl Widget<Context = ()>, key: KeyCode) -> Option<Event> {
+    tabs.on_event(Event::KeyDown(key), &mut ())
+}
+
+#[test]
+fn pages_are_below_the_tab_strip() {
+    let tabs = tabs();
+    let page_area = Rectangle::new(Point::new(0, 21), Size::new(300, 219));
+    assert_eq!(tabs.get_page_area(), page_area);
+    for n in 0..3 {
+        assert_eq!(tabs.get(n).unwrap().get_bounding_box(), page_area);
+    }
+    assert_eq!(
+        tabs.get(0).unwrap().get(1).unwrap().get_bounding_box(),
+        Rectangle::new(Point::new(0, 130), Size::new(300, 110))
+    );
+}
+
+#[test]
+fn strip_switches_tabs() {
+    let mut tabs = tabs();
+    tabs.set_focus(Some(FocusFrom::Up)).unwrap();
+    assert!(tabs.is_strip_focused());
+    assert_eq!(
+        tabs.get_focused_area(),
+        Some(Rectangle::new(Point::zero(), Size::new(100, 21)))
+    );
+    assert_eq!(press(&mut tabs, KeyCode::Left), Some(Event::KeyDown(KeyCode::Left)));
+    assert_eq!(press(&mut tabs, KeyCode::Right), None);
+    assert_eq!(tabs.on_event(Event::KeyRepeat(KeyCode::Right), &mut ()), None);
+    assert_eq!(tabs.get_selected_tab(), 2);
+    assert_eq!(press(&mut tabs, KeyCode::Right), Some(Event::KeyDown(KeyCode::Right)));
+    assert_eq!(
+        tabs.get_focused_area(),
+        Some(Rectangle::new(Point::new(200, 0), Size::new(100, 21)))
+    );
+    // the page doesn't get the arrows while the strip is focused
+    assert_eq!(tabs.get(2).unwrap().get_focus(), Some(false));
+}
+
+#[test]
+fn page_is_entered_and_left() {
+    let mut tabs = tabs();
+    tabs.set_focus(Some(FocusFrom::Up)).unwrap();
+    press(&mut tabs, KeyCode::Right);
+    assert_eq!(press(&mut tabs, KeyCode::Down), None);
+    assert!(!tabs.is_strip_focused());
+    assert_eq!(tabs.get(1).unwrap().get_focused_child(), Some(0));
+
+    // arrows go to the page
+    assert_eq!(press(&mut tabs, KeyCode::Down), None);
+    assert_eq!(tabs.get(1).unwrap().get_focused_child(), Some(1));
+    assert_eq!(press(&mut tabs, KeyCode::Right), Some(Event::KeyDown(KeyCode::Right)));
+    assert_eq!(tabs.get_selected_tab(), 1);
+
+    assert_eq!(press(&mut tabs, KeyCode::Back), None);
+    assert!(tabs.is_strip_focused());
+    assert_eq!(tabs.get(1).unwrap().get_focus(), Some(false));
+
+    // going up from the top of the page also goes back to the strip
+    press(&mut tabs, KeyCode::Down);
+    assert_eq!(tabs.get(1).unwrap().get_focused_child(), Some(0));
+    assert_eq!(press(&mut tabs, KeyCode::Up), None);
+    assert!(tabs.is_strip_focused());
+    assert_eq!(press(&mut tabs, KeyCode::Up), Some(Event::KeyDown(KeyCode::Up)));
+}
+
+#[test]
+fn selected_tab_is_highlighted() {
+    let theme = Theme::default();
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    let mut tabs = tabs();
+    tabs.render(&mut display, &theme).unwrap();
+    tabs.mark_clean();
+    assert_eq!(display.get_pixel(Point::new(1, 1)), Some(theme.foreground));
+    assert_eq!(display.get_pixel(Point::new(101, 1)), Some(theme.background));
+
+    tabs.set_focus(Some(FocusFrom::Up)).unwrap();
+    assert_eq!(
+        tabs.get_dirty_area(),
+        Some(Rectangle::new(Point::zero(), Size::new(300, 21)))
+    );
+    tabs.render_dirty(&mut display, &theme).unwrap();
+    tabs.mark_clean();
+    assert_eq!(display.get_pixel(Point::new(1, 1)), Some(theme.focused));
+
+    press(&mut tabs, KeyCode::Right);
+    assert_eq!(tabs.get_dirty_area(), Some(tabs.get_bounding_box()));
+    tabs.render_dirty(&mut display, &theme).unwrap();
+    assert_eq!(display.get_pixel(Point::new(1, 1)), Some(theme.background));
+    assert_eq!(display.get_pixel(Point::new(101, 1)), Some(theme.focused));
+}
@@ -0,0 +1,127 @@
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use heapless::String;
+use nw_gui::{
+    calculator::{Event, KeyCode},
+    gui::{widgets::TextArea, FocusFrom, Widget},
+};
+
+mod common;
+use common::{press, state, State};
+
+// 10 columns and 3 lines
+const AREA: Rectangle = Rectangle::new(Point::new(0, 0), Size::new(78, 47));
+
+fn text_area<const N: usize>(text: &str, read_only: bool) -> TextArea<State, N> {
+    let mut text_area = TextArea::new(AREA, read_only);
+    text_area.set_text(String::from(text));
+    text_area.set_focus(Some(FocusFrom::Up)).unwrap();
+    text_area
+}
+
+#[test]
+fn text_is_wrapped() {
+    // "hello" fits after "one two", "abcdefghijkl" is broken in the middle
+    let mut text_area: TextArea<State, 64> = text_area("one two hello\nabcdefghijkl", false);
+    assert_eq!(text_area.line_count(), 4);
+
+    text_area.set_text(String::from("0123456789\n"));
+    assert_eq!(text_area.line_count(), 2);
+}
+
+#[test]
+fn cursor_moves_between_lines() {
+    let mut state = state();
+    let mut text_area: TextArea<State, 64> = text_area("ab\ncdef\ng", false);
+    press(&mut text_area, &mut state, &[KeyCode::Right, KeyCode::Down]);
+    assert_eq!(text_area.get_cursor(), 4);
+    // the column is kept inside the line
+    press(
+        &mut text_area,
+        &mut state,
+        &[KeyCode::Right, KeyCode::Right, KeyCode::Down],
+    );
+    assert_eq!(text_area.get_cursor(), 9);
+    assert_eq!(
+        press(&mut text_area, &mut state, &[KeyCode::Down]),
+        Some(Event::KeyDown(KeyCode::Down))
+    );
+
+    press(&mut text_area, &mut state, &[KeyCode::Exe, KeyCode::Num1]);
+    assert_eq!(text_area.get_text(), "ab\ncdef\ng\n1");
+    // the text is scrolled to show the cursor
+    assert_eq!(text_area.get_first_line(), 1);
+    press(&mut text_area, &mut state, &[KeyCode::Up, KeyCode::Up, KeyCode::Up]);
+    assert_eq!(text_area.get_cursor(), 1);
+    assert_eq!(text_area.get_first_line(), 0);
+}
+
+#[test]
+fn read_only_text_is_scrolled() {
+    let mut state = state();
+    let mut text_area: TextArea<State, 64> = text_area("1\n2\n3\n4\n5", true);
+    assert_eq!(
+        press(&mut text_area, &mut state, &[KeyCode::Num6]),
+        Some(Event::KeyDown(KeyCode::Num6))
+    );
+    assert_eq!(text_area.get_text(), "1\n2\n3\n4\n5");
+
+    press(&mut text_area, &mut state, &[KeyCode::Down, KeyCode::Down]);
+    assert_eq!(text_area.get_first_line(), 2);
+    assert_eq!(
+        press(&mut text_area, &mut state, &[KeyCode::Down]),
+        Some(Event::KeyDown(KeyCode::Down))
+    );
+}
+
+#[test]
+fn overflow_is_not_inserted() {
+    let mut state = state();
+    let mut text_area: TextArea<State, 4> = text_area("123", false);
+    assert_eq!(
+        press(&mut text_area, &mut state, &[KeyCode::Sine]),
+        Some(Event::KeyDown(KeyCode::Sine))
+    );
+    assert_eq!(text_area.get_text(), "123");
+
+    // the oldest text is dropped to append new text
+    text_area.append("45");
+    assert_eq!(text_area.get_text(), "2345");
+    assert_eq!(text_area.get_cursor(), 4);
+}
+
+#[test]
+fn appended_text_is_not_undone() {
+    let mut state = state();
+    let mut text_area: TextArea<State, 8> = text_area("", false);
+    press(&mut text_area, &mut state, &[KeyCode::Num1]);
+    text_area.append("=1\n");
+    // Shift + Minus
+    press(&mut text_area, &mut state, &[KeyCode::Shift, KeyCode::Minus]);
+    assert_eq!(text_area.get_text(), "1=1\n");
+
+    press(
+        &mut text_area,
+        &mut state,
+        &[KeyCode::Num2, KeyCode::Shift, KeyCode::Minus],
+    );
+    assert_eq!(text_area.get_text(), "1=1\n");
+}
+
+#[test]
+fn multi_byte_text_is_appended() {
+    let mut text_area: TextArea<State, 5> = text_area("", true);
+    text_area.append("ab");
+    // "é" is 2 bytes long, so only 2 of them fit and "a" is dropped
+    text_area.append("ééé");
+    assert_eq!(text_area.get_text(), "béé");
+    // "b" and the first "é" are dropped to make room
+    text_area.append("é");
+    assert_eq!(text_area.get_text(), "éé");
+
+    // only the end of text too long is kept
+    text_area.append("abcdéé");
+    assert_eq!(text_area.get_text(), "déé");
+    text_area.append("ééé");
+    assert_eq!(text_area.get_text(), "éé");
+}
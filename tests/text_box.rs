@@ -0,0 +1,194 @@
+use embedded_graphics::prelude::*;
+use heapless::String;
+use nw_gui::{
+    calculator::{Color, Event, Framebuffer, KeyCode, PIXEL_COUNT},
+    gui::{
+        text::EditHistory,
+        theme::Theme,
+        widgets::{TextBox, CURSOR_BLINK_PERIOD},
+        Widget,
+    },
+};
+
+mod common;
+use common::{press, state, text_box, State, LINE};
+
+#[test]
+fn typo_is_fixed_in_the_middle() {
+    let mut state = state();
+    let mut text_box = text_box();
+    press(
+        &mut text_box,
+        &mut state,
+        &[KeyCode::Num1, KeyCode::Plus, KeyCode::Num3],
+    );
+    assert_eq!(text_box.get_cursor(), 3);
+
+    press(&mut text_box, &mut state, &[KeyCode::Left, KeyCode::Backspace]);
+    assert_eq!(text_box.value, "13");
+    assert_eq!(text_box.get_cursor(), 1);
+    press(&mut text_box, &mut state, &[KeyCode::Multiplication, KeyCode::Sine]);
+    assert_eq!(text_box.value, "1*sin(3");
+    assert_eq!(text_box.get_cursor(), 6);
+}
+
+#[test]
+fn cursor_moves_inside_the_text() {
+    let mut state = state();
+    let mut text_box = text_box();
+    // not being edited, the arrows move the focus
+    assert_eq!(
+        press(&mut text_box, &mut state, &[KeyCode::Left]),
+        Some(Event::KeyDown(KeyCode::Left))
+    );
+
+    press(&mut text_box, &mut state, &[KeyCode::Num1, KeyCode::Num2]);
+    assert_eq!(press(&mut text_box, &mut state, &[KeyCode::Up]), None);
+    assert_eq!(text_box.get_cursor(), 0);
+    assert_eq!(
+        press(&mut text_box, &mut state, &[KeyCode::Up]),
+        Some(Event::KeyDown(KeyCode::Up))
+    );
+    assert_eq!(
+        press(&mut text_box, &mut state, &[KeyCode::Left]),
+        Some(Event::KeyDown(KeyCode::Left))
+    );
+    assert_eq!(press(&mut text_box, &mut state, &[KeyCode::Right]), None);
+    assert_eq!(text_box.get_cursor(), 1);
+    // nothing is deleted before the start
+    press(&mut text_box, &mut state, &[KeyCode::Backspace, KeyCode::Backspace]);
+    assert_eq!(text_box.value, "2");
+
+    assert_eq!(press(&mut text_box, &mut state, &[KeyCode::Down]), None);
+    assert_eq!(text_box.get_cursor(), 1);
+    assert_eq!(
+        press(&mut text_box, &mut state, &[KeyCode::Right]),
+        Some(Event::KeyDown(KeyCode::Right))
+    );
+
+    // the cursor is kept in the text when it changes from outside
+    text_box.value.clear();
+    assert_eq!(text_box.get_cursor(), 0);
+}
+
+#[test]
+fn cursor_blinks() {
+    let theme = Theme::default();
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut display = Framebuffer::new(&mut pixels);
+    let mut state = state();
+    let mut text_box = text_box();
+    text_box.set_blink_timer(Some(7));
+    press(&mut text_box, &mut state, &[KeyCode::Num1, KeyCode::Num2]);
+    assert!(state.timers.is_scheduled(7));
+    assert_eq!(state.timers.next_deadline(), Some(CURSOR_BLINK_PERIOD as u64));
+
+    // "12" is centered from x = 153 to 167
+    text_box.render(&mut display, &theme).unwrap();
+    text_box.mark_clean();
+    assert_eq!(display.get_pixel(Point::new(167, 8)), Some(theme.active));
+
+    state.timers.set_now(CURSOR_BLINK_PERIOD as u64);
+    let timer = state.timers.poll_event().unwrap();
+    assert_eq!(text_box.on_event(timer, &mut state), None);
+    assert!(state.timers.is_scheduled(7));
+    assert_eq!(text_box.get_dirty_area(), Some(LINE));
+    text_box.render_dirty(&mut display, &theme).unwrap();
+    assert_eq!(display.get_pixel(Point::new(167, 8)), Some(theme.background));
+
+    // the timer stops once the edition is over
+    press(&mut text_box, &mut state, &[KeyCode::Ok]);
+    state.timers.set_now(2 * CURSOR_BLINK_PERIOD as u64);
+    let timer = state.timers.poll_event().unwrap();
+    assert_eq!(text_box.on_event(timer, &mut state), None);
+    assert!(!state.timers.is_scheduled(7));
+}
+
+#[test]
+fn clear_is_undone() {
+    let mut state = state();
+    let mut text_box = text_box();
+    press(&mut text_box, &mut state, &[KeyCode::Num1, KeyCode::Num2]);
+    // Shift + Backspace
+    press(&mut text_box, &mut state, &[KeyCode::Shift, KeyCode::Backspace]);
+    assert_eq!(text_box.value, "");
+
+    // Shift + Minus
+    press(&mut text_box, &mut state, &[KeyCode::Shift, KeyCode::Minus]);
+    assert_eq!(text_box.value, "12");
+    assert_eq!(text_box.get_cursor(), 2);
+    press(&mut text_box, &mut state, &[KeyCode::Shift, KeyCode::Minus]);
+    assert_eq!(text_box.value, "1");
+    assert_eq!(text_box.get_cursor(), 1);
+
+    // Shift + Plus
+    press(&mut text_box, &mut state, &[KeyCode::Shift, KeyCode::Plus]);
+    assert_eq!(text_box.value, "12");
+    press(&mut text_box, &mut state, &[KeyCode::Shift, KeyCode::Plus]);
+    assert_eq!(text_box.value, "");
+    assert_eq!(
+        press(&mut text_box, &mut state, &[KeyCode::Shift, KeyCode::Plus]),
+        Some(Event::KeyDown(KeyCode::Plus))
+    );
+
+    // a new change can't be redone
+    press(
+        &mut text_box,
+        &mut state,
+        &[KeyCode::Shift, KeyCode::Minus, KeyCode::Num3],
+    );
+    assert_eq!(text_box.value, "123");
+    assert!(!text_box.get_history().can_redo());
+}
+
+#[test]
+fn history_is_bounded() {
+    // each change of one character takes 25 bytes
+    let mut history: EditHistory<60> = EditHistory::new();
+    let mut text: String<8> = String::from("abc");
+    let mut cursor = 3;
+    history.record("", "a", 0);
+    history.record("a", "ab", 1);
+    history.record("ab", "abc", 2);
+
+    assert!(history.undo(&mut text, &mut cursor));
+    assert!(history.undo(&mut text, &mut cursor));
+    assert_eq!(text, "a");
+    assert_eq!(cursor, 1);
+    assert!(!history.undo(&mut text, &mut cursor));
+
+    assert!(history.redo(&mut text, &mut cursor));
+    assert!(history.redo(&mut text, &mut cursor));
+    assert_eq!(text, "abc");
+    assert_eq!(cursor, 3);
+    assert!(!history.redo(&mut text, &mut cursor));
+
+    // a change too large is forgotten with the others
+    history.record("abc", "abc + 0123456789 + 0123456789 + 0123456789", 3);
+    assert!(!history.can_undo());
+}
+
+#[test]
+fn history_keeps_only_the_changes() {
+    let mut history: EditHistory = EditHistory::new();
+    let mut text: String<16> = String::from("é+u=2");
+    let mut cursor = 0;
+    history.record("é=2", "é+ü=2", 2);
+    history.record("é+ü=2", "é+u=2", 5);
+    assert!(history.undo(&mut text, &mut cursor));
+    assert!(history.undo(&mut text, &mut cursor));
+    assert_eq!(text, "é=2");
+    assert_eq!(cursor, 2);
+    assert!(history.redo(&mut text, &mut cursor));
+    assert_eq!(text, "é+ü=2");
+    assert_eq!(cursor, 5);
+
+    // the history is forgotten if the text has been changed without being recorded
+    text.clear();
+    assert!(!history.redo(&mut text, &mut cursor));
+    assert!(!history.can_undo());
+
+    // the history doesn't depend on the size of the text
+    assert!(core::mem::size_of::<TextBox<State, 64>>() < 512);
+    assert!(core::mem::size_of::<TextBox<State, 64, 0>>() < 256);
+}
@@ -0,0 +1,95 @@
+use embedded_graphics::prelude::*;
+use heapless::Vec;
+use nw_gui::calculator::{Calculator, Color, Event, HeadlessBackend, KeyCode, KeyRepeat, Timers, PIXEL_COUNT};
+
+#[test]
+fn timer_events_follow_simulated_time() {
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut calc = Calculator::new(HeadlessBackend::<4>::new(&mut pixels)).unwrap();
+    calc.get_backend_mut().push_event_at(2, Event::KeyDown(KeyCode::Ok)).unwrap();
+    let mut timers: Timers = Timers::new();
+    timers.schedule(1, 50).unwrap();
+    timers.schedule(2, 100).unwrap();
+
+    let mut received: Vec<(u64, Event), 8> = Vec::new();
+    for _ in 0..6 {
+        calc.render();
+        let now = calc.now();
+        for e in calc.events() {
+            received.push((now, e)).unwrap();
+        }
+        timers.set_now(now);
+        while let Some(e) = timers.poll_event() {
+            received.push((now, e)).unwrap();
+            if e == Event::Timer(1) {
+                // periodic timer
+                timers.schedule(1, 50).unwrap();
+            }
+        }
+    }
+
+    assert_eq!(
+        received,
+        [
+            (60, Event::KeyDown(KeyCode::Ok)),
+            (60, Event::Timer(1)),
+            (100, Event::Timer(2)),
+            (120, Event::Timer(1)),
+        ]
+    );
+    assert!(timers.is_scheduled(1));
+    assert_eq!(timers.next_deadline(), Some(170));
+}
+
+#[test]
+fn reschedule_and_cancel() {
+    let mut timers: Timers<2> = Timers::new();
+    timers.schedule(1, 10).unwrap();
+    timers.schedule(2, 20).unwrap();
+    assert_eq!(timers.schedule(3, 30), Err(3));
+
+    timers.schedule(1, 40).unwrap();
+    timers.set_now(30);
+    assert_eq!(timers.pop_expired(), Some(2));
+    assert_eq!(timers.pop_expired(), None);
+
+    timers.cancel(1);
+    timers.set_now(100);
+    assert_eq!(timers.pop_expired(), None);
+    assert_eq!(timers.next_deadline(), None);
+}
+
+#[test]
+fn held_keys_repeat() {
+    let mut pixels = vec![Color::BLACK; PIXEL_COUNT];
+    let mut calc = Calculator::new(HeadlessBackend::<8>::new(&mut pixels)).unwrap();
+    calc.set_key_repeat(Some(KeyRepeat {
+        delay: 100,
+        interval: 40,
+    }));
+    let backend = calc.get_backend_mut();
+    backend.push_event_at(0, Event::KeyDown(KeyCode::Left)).unwrap();
+    backend.push_event_at(9, Event::KeyUp(KeyCode::Left)).unwrap();
+    // modifiers are not repeated
+    backend.push_event_at(10, Event::KeyDown(KeyCode::Shift)).unwrap();
+
+    let mut received: Vec<(u64, Event), 16> = Vec::new();
+    for _ in 0..20 {
+        calc.render();
+        let now = calc.now();
+        for e in calc.events() {
+            received.push((now, e)).unwrap();
+        }
+    }
+
+    assert_eq!(
+        received,
+        [
+            (20, Event::KeyDown(KeyCode::Left)),
+            (120, Event::KeyRepeat(KeyCode::Left)),
+            (160, Event::KeyRepeat(KeyCode::Left)),
+            (200, Event::KeyUp(KeyCode::Left)),
+            (220, Event::KeyDown(KeyCode::Shift)),
+        ]
+    );
+}
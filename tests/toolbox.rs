@@ -0,0 +1,172 @@
+use either::Either;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::Rectangle;
+use heapless::String;
+use nw_gui::{
+    calculator::{Event, KeyCode},
+    gui::{
+        text::{Popup, TextInputContext, TextInputState},
+        widgets::{Dialog, Modal, OverlayStack, TextBox, Toolbox, ToolboxEntry, DEFAULT_TOOLBOX},
+        FocusFrom, Widget,
+    },
+};
+
+mod common;
+use common::{state, State, LINE};
+
+const PANEL: Rectangle = Rectangle::new(Point::new(160, 0), Size::new(160, 240));
+
+/// Press and release `keys` one after the other.
+fn tap<W: Widget<Context = State>>(widget: &mut W, state: &mut State, keys: &[KeyCode]) {
+    for key in keys {
+        widget.on_event(Event::KeyDown(*key), state);
+        widget.on_event(Event::KeyUp(*key), state);
+    }
+}
+
+fn toolbox() -> Toolbox<State> {
+    let mut toolbox = Toolbox::new(DEFAULT_TOOLBOX);
+    toolbox.set_bounding_box(PANEL);
+    toolbox.set_focus(Some(FocusFrom::Right)).unwrap();
+    toolbox
+}
+
+#[test]
+fn submenus_are_opened_and_closed() {
+    let mut state = state();
+    let mut toolbox = toolbox();
+    // Probability > Combinatorics
+    tap(&mut toolbox, &mut state, &[KeyCode::Down; 5]);
+    assert_eq!(toolbox.get_entries()[toolbox.get_selected()].label(), "Probability");
+    tap(&mut toolbox, &mut state, &[KeyCode::Right, KeyCode::Ok]);
+    assert_eq!(toolbox.get_depth(), 2);
+    assert_eq!(
+        toolbox.get_entries()[0],
+        ToolboxEntry::Snippet {
+            label: "binomial(n,k)",
+            text: "binomial("
+        }
+    );
+
+    // the selection is restored in the parent menus
+    tap(&mut toolbox, &mut state, &[KeyCode::Left]);
+    assert_eq!(toolbox.get_depth(), 1);
+    assert_eq!(toolbox.get_selected(), 0);
+    tap(&mut toolbox, &mut state, &[KeyCode::Back]);
+    assert_eq!(toolbox.get_selected(), 5);
+    assert!(!toolbox.is_dismissed());
+    // Left doesn't close the top menu
+    tap(&mut toolbox, &mut state, &[KeyCode::Left]);
+    assert!(!toolbox.is_dismissed());
+
+    tap(&mut toolbox, &mut state, &[KeyCode::Back]);
+    assert!(toolbox.is_dismissed());
+    assert_eq!(state.popups.take_chosen(), None);
+
+    // focusing the toolbox again starts from the top
+    toolbox.set_focus(Some(FocusFrom::Right)).unwrap();
+    assert!(!toolbox.is_dismissed());
+    assert_eq!(toolbox.get_selected(), 0);
+}
+
+#[test]
+fn snippet_is_chosen() {
+    let mut state = state();
+    let mut toolbox = toolbox();
+    // Approximation > floor(x)
+    tap(
+        &mut toolbox,
+        &mut state,
+        &[KeyCode::Up, KeyCode::Up, KeyCode::Ok, KeyCode::Exe],
+    );
+    assert!(toolbox.is_dismissed());
+    assert_eq!(state.popups.take_chosen().as_deref(), Some("floor("));
+    assert_eq!(state.popups.take_chosen(), None);
+}
+
+#[test]
+fn text_box_inserts_from_the_toolbox() {
+    let mut state = state();
+    let mut screen: OverlayStack<TextBox<State, 16>, Toolbox<State>> = OverlayStack::new(TextBox::new(LINE, true));
+    screen.set_focus(Some(FocusFrom::Up)).unwrap();
+    tap(
+        &mut screen,
+        &mut state,
+        &[KeyCode::Num2, KeyCode::Multiplication, KeyCode::Toolbox],
+    );
+    assert_eq!(state.popups.take_request(), Some(Popup::Toolbox));
+    assert!(screen.push(Toolbox::new(DEFAULT_TOOLBOX)).is_ok());
+
+    // the toolbox captures the keys
+    tap(&mut screen, &mut state, &[KeyCode::Num1]);
+    assert_eq!(screen.get_base().value, "2*");
+
+    // the snippet is inserted as soon as the toolbox is dismissed
+    screen.on_event(Event::KeyDown(KeyCode::Ok), &mut state);
+    assert!(screen.is_empty());
+    assert_eq!(screen.get_base().value, "2*abs(");
+    screen.on_event(Event::KeyUp(KeyCode::Ok), &mut state);
+    assert_eq!(screen.get_base().value, "2*abs(");
+
+    // nothing is inserted when the toolbox is closed without choosing
+    tap(&mut screen, &mut state, &[KeyCode::Toolbox]);
+    assert_eq!(state.popups.take_request(), Some(Popup::Toolbox));
+    assert!(screen.push(Toolbox::new(DEFAULT_TOOLBOX)).is_ok());
+    tap(&mut screen, &mut state, &[KeyCode::Back, KeyCode::Num3]);
+    assert_eq!(screen.get_base().value, "2*abs(3");
+}
+
+#[test]
+fn toolbox_is_stacked_with_dialogs() {
+    let mut state = state();
+    let mut screen: OverlayStack<TextBox<State, 16>, Either<Toolbox<State>, Dialog<State>>> =
+        OverlayStack::new(TextBox::new(LINE, true));
+    screen.set_focus(Some(FocusFrom::Up)).unwrap();
+    tap(&mut screen, &mut state, &[KeyCode::Toolbox]);
+    assert_eq!(state.popups.take_request(), Some(Popup::Toolbox));
+    assert!(screen.push(Either::Left(Toolbox::new(DEFAULT_TOOLBOX))).is_ok());
+    tap(&mut screen, &mut state, &[KeyCode::Down, KeyCode::Ok]);
+    assert!(screen.is_empty());
+    assert_eq!(screen.get_base().value, "root(");
+
+    let mut dialog = Dialog::new(String::from("Error"), String::from("Syntax error"));
+    assert!(dialog.add_button(String::from("OK"), |_| ()).is_ok());
+    assert!(screen.push(Either::Right(dialog)).is_ok());
+    tap(&mut screen, &mut state, &[KeyCode::Ok]);
+    assert!(screen.is_empty());
+    assert_eq!(screen.get_base().value, "root(");
+}
+
+#[test]
+fn text_box_asks_for_variables() {
+    let mut state = state();
+    let mut text_box: TextBox<State, 16> = TextBox::new(LINE, true);
+    text_box.set_focus(Some(FocusFrom::Up)).unwrap();
+    tap(&mut text_box, &mut state, &[KeyCode::Var]);
+    assert_eq!(state.popups.take_request(), Some(Popup::Var));
+
+    // the application shows its variables, one of them is chosen, and the popup is dismissed
+    state.popups.choose(String::from("x1"));
+    assert_eq!(text_box.on_event(Event::LayerDismissed, &mut state), None);
+    tap(&mut text_box, &mut state, &[KeyCode::Plus]);
+    assert_eq!(text_box.value, "x1+");
+}
+
+#[test]
+fn text_box_without_popups_returns_the_key() {
+    struct NoPopups(TextInputState);
+    impl TextInputContext for NoPopups {
+        fn get_context(&mut self) -> &mut TextInputState {
+            &mut self.0
+        }
+    }
+    let mut text_box: TextBox<NoPopups, 16> = TextBox::new(LINE, true);
+    text_box.set_focus(Some(FocusFrom::Up)).unwrap();
+    let mut state = NoPopups(TextInputState::new());
+    for key in [KeyCode::Toolbox, KeyCode::Var] {
+        assert_eq!(
+            text_box.on_event(Event::KeyDown(key), &mut state),
+            Some(Event::KeyDown(key))
+        );
+    }
+}